@@ -0,0 +1,116 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use forge_config::{load_run_config, user_config_path, validate_config, CliOverrides, ConfigEntry};
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct ConfigCommand {
+    #[command(subcommand)]
+    action: ConfigAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigAction {
+    /// Print every effective value and where it came from.
+    Show(ConfigShowCommand),
+    /// Check config files and FORGE_* variables without running anything.
+    Validate(ConfigValidateCommand),
+}
+
+#[derive(Debug, clap::Args)]
+struct ConfigShowCommand {
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct ConfigValidateCommand {
+    #[arg(long)]
+    json: bool,
+}
+
+pub(crate) fn config_command(cmd: ConfigCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
+    match cmd.action {
+        ConfigAction::Show(show) => config_show(&cwd, &base, show.json),
+        ConfigAction::Validate(validate) => config_validate(&cwd, &base, validate.json),
+    }
+}
+
+fn config_show(cwd: &Path, base: &CliOverrides, as_json: bool) -> Result<()> {
+    let cfg = load_run_config(cwd, base)?;
+    let entries: Vec<ConfigEntry> = cfg.entries().into_iter().map(mask_entry).collect();
+
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(&entries)?);
+        return Ok(());
+    }
+
+    println!("user config: {}", describe_user_config(base));
+    let width = entries.iter().map(|e| e.key.len()).max().unwrap_or(0);
+    for entry in &entries {
+        println!(
+            "{:<width$}  {}  ({})",
+            entry.key,
+            render_value(&entry.value),
+            entry.source
+        );
+    }
+    Ok(())
+}
+
+fn config_validate(cwd: &Path, base: &CliOverrides, as_json: bool) -> Result<()> {
+    let problems = validate_config(cwd, base);
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "ok": problems.is_empty(),
+                "problems": problems,
+            }))?
+        );
+    } else if problems.is_empty() {
+        println!("config ok");
+    } else {
+        for problem in &problems {
+            println!("- {problem}");
+        }
+    }
+    if !problems.is_empty() {
+        bail!("config has {} problem(s)", problems.len());
+    }
+    Ok(())
+}
+
+/// `engine_env` values are masked the same way `forge doctor` masks them.
+fn mask_entry(mut entry: ConfigEntry) -> ConfigEntry {
+    if entry.key == "engine_env" {
+        if let Value::Object(vars) = &mut entry.value {
+            for value in vars.values_mut() {
+                if let Value::String(raw) = value {
+                    *value = Value::String(forge_engine::mask_env_value(raw));
+                }
+            }
+        }
+    }
+    entry
+}
+
+fn describe_user_config(base: &CliOverrides) -> String {
+    if base.no_user_config {
+        return "disabled (--no-user-config)".to_string();
+    }
+    match user_config_path() {
+        Some(path) if path.exists() => path.display().to_string(),
+        Some(path) => format!("{} (not found)", path.display()),
+        None => "none (XDG_CONFIG_HOME and HOME unset)".to_string(),
+    }
+}
+
+fn render_value(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        other => other.to_string(),
+    }
+}
@@ -1,18 +1,35 @@
 use anyhow::{bail, Context, Result};
 use chrono::{DateTime, Datelike, Local, TimeZone, Utc};
 use clap::{Parser, Subcommand};
-use forge_config::{load_run_config, CliOverrides, EngineKind, ThinkingMode};
-use forge_core::{read_status, run_loop, ExitReason, RunRequest};
+use forge_config::{
+    load_run_config, validate_config, CliOverrides, EngineKind, ReasoningEffort, RunConfig,
+    RuntimeLayout, ThinkingMode, DEFAULT_RUNTIME_DIR,
+};
+use forge_core::{
+    current_run_segment, read_plan_history, read_status, run_loop, ExitReason, RunRequest,
+};
+use forge_engine::LiveLog;
 use forge_monitor::run_monitor;
+use forge_types::LiveLogEntry;
 use serde_json::Value;
 use std::env;
 use std::fs;
 use std::io::{self, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::Command;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+mod config;
+mod plan;
+mod queue;
+mod schema;
+
+use config::{config_command, ConfigCommand};
+use plan::{plan_command, PlanCommand};
+use queue::{queue_command, QueueCommand};
+use schema::{schema_command, SchemaCommand};
+
 #[derive(Debug, Parser)]
 #[command(
     name = "forge",
@@ -24,6 +41,14 @@ struct Cli {
     #[arg(long, global = true)]
     cwd: Option<PathBuf>,
 
+    /// Apply `[profile.<name>]` from .forgerc (or set FORGE_PROFILE).
+    #[arg(long, global = true)]
+    profile: Option<String>,
+
+    /// Ignore $XDG_CONFIG_HOME/forge/config.toml.
+    #[arg(long, global = true)]
+    no_user_config: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -36,12 +61,17 @@ enum Commands {
     Status(StatusCommand),
     Monitor(MonitorCommand),
     Sdd(SddCommand),
+    Plan(PlanCommand),
+    Queue(QueueCommand),
+    Config(ConfigCommand),
+    /// Print the JSON Schema of status.json or progress.json.
+    Schema(SchemaCommand),
 }
 
 #[derive(Debug, clap::Args)]
 struct RunCommand {
-    #[arg(long, value_enum, default_value = "codex")]
-    engine: EngineArg,
+    #[arg(long, value_enum)]
+    engine: Option<EngineArg>,
 
     #[arg(long = "engine-arg")]
     engine_pre_args: Vec<String>,
@@ -52,6 +82,13 @@ struct RunCommand {
     #[arg(long, value_enum)]
     thinking: Option<ThinkingArg>,
 
+    /// Model to use, in the engine's own naming.
+    #[arg(long)]
+    model: Option<String>,
+
+    #[arg(long, value_enum)]
+    reasoning_effort: Option<ReasoningEffortArg>,
+
     #[arg(long)]
     resume: Option<String>,
 
@@ -72,6 +109,10 @@ struct RunCommand {
 
     #[arg(long, default_value_t = 100)]
     max_loops: u64,
+
+    /// Override any .forgerc key, e.g. `--set no_progress_limit=5`.
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
 }
 
 #[derive(Debug, clap::Args)]
@@ -82,8 +123,8 @@ struct StatusCommand {
 
 #[derive(Debug, clap::Args)]
 struct AnalyzeCommand {
-    #[arg(long, value_enum, default_value = "codex")]
-    engine: EngineArg,
+    #[arg(long, value_enum)]
+    engine: Option<EngineArg>,
 
     #[arg(long = "engine-arg")]
     engine_pre_args: Vec<String>,
@@ -94,6 +135,13 @@ struct AnalyzeCommand {
     #[arg(long, value_enum)]
     thinking: Option<ThinkingArg>,
 
+    /// Model to use, in the engine's own naming.
+    #[arg(long)]
+    model: Option<String>,
+
+    #[arg(long, value_enum)]
+    reasoning_effort: Option<ReasoningEffortArg>,
+
     #[arg(long, default_value_t = true)]
     modified_only: bool,
 
@@ -108,6 +156,10 @@ struct AnalyzeCommand {
 
     #[arg(long)]
     json: bool,
+
+    /// Override any .forgerc key, e.g. `--set no_progress_limit=5`.
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
 }
 
 #[derive(Debug, clap::Args)]
@@ -129,6 +181,10 @@ struct DoctorCommand {
 
     #[arg(long)]
     strict: bool,
+
+    /// Override any .forgerc key, e.g. `--set no_progress_limit=5`.
+    #[arg(long = "set", value_name = "KEY=VALUE")]
+    set: Vec<String>,
 }
 
 #[derive(Debug, clap::Args)]
@@ -171,10 +227,31 @@ impl From<ThinkingArg> for ThinkingMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum ReasoningEffortArg {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl From<ReasoningEffortArg> for ReasoningEffort {
+    fn from(value: ReasoningEffortArg) -> Self {
+        match value {
+            ReasoningEffortArg::Minimal => ReasoningEffort::Minimal,
+            ReasoningEffortArg::Low => ReasoningEffort::Low,
+            ReasoningEffortArg::Medium => ReasoningEffort::Medium,
+            ReasoningEffortArg::High => ReasoningEffort::High,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, clap::ValueEnum)]
 enum EngineArg {
     Codex,
     OpenCode,
+    Custom,
+    Mock,
 }
 
 impl From<EngineArg> for EngineKind {
@@ -182,6 +259,8 @@ impl From<EngineArg> for EngineKind {
         match value {
             EngineArg::Codex => EngineKind::Codex,
             EngineArg::OpenCode => EngineKind::OpenCode,
+            EngineArg::Custom => EngineKind::Custom,
+            EngineArg::Mock => EngineKind::Mock,
         }
     }
 }
@@ -204,29 +283,43 @@ struct SddInterview {
 fn main() -> Result<()> {
     let cli = Cli::parse();
     let cwd = resolve_cwd(cli.cwd)?;
+    // Global flags shared by every command that loads the run config.
+    let base = CliOverrides {
+        profile: cli.profile,
+        no_user_config: cli.no_user_config,
+        ..CliOverrides::default()
+    };
 
     match cli.command {
-        Some(Commands::Run(cmd)) => run_command(cmd, cwd),
-        Some(Commands::Analyze(cmd)) => analyze_command(cmd, cwd),
-        Some(Commands::Doctor(cmd)) => doctor_command(cmd, cwd),
-        Some(Commands::Status(cmd)) => status_command(cmd, cwd),
-        Some(Commands::Monitor(cmd)) => monitor_command(cmd, cwd),
+        Some(Commands::Run(cmd)) => run_command(cmd, cwd, base),
+        Some(Commands::Analyze(cmd)) => analyze_command(cmd, cwd, base),
+        Some(Commands::Doctor(cmd)) => doctor_command(cmd, cwd, base),
+        Some(Commands::Status(cmd)) => status_command(cmd, cwd, base),
+        Some(Commands::Monitor(cmd)) => monitor_command(cmd, cwd, base),
         Some(Commands::Sdd(cmd)) => sdd_command(cmd, cwd),
-        None => assistant_mode(cwd),
+        Some(Commands::Plan(cmd)) => plan_command(cmd, cwd),
+        Some(Commands::Queue(cmd)) => queue_command(cmd, cwd, base),
+        Some(Commands::Config(cmd)) => config_command(cmd, cwd, base),
+        Some(Commands::Schema(cmd)) => schema_command(cmd),
+        None => assistant_mode(cwd, base),
     }
 }
 
-fn assistant_mode(cwd: PathBuf) -> Result<()> {
+fn assistant_mode(cwd: PathBuf, base: CliOverrides) -> Result<()> {
     println!("forge assistant mode");
     println!("answer the SDD questions. forge will generate specs and run the loop.\n");
 
+    let layout = RuntimeLayout::discover(&cwd)?;
     let answers = collect_sdd_answers()?;
-    let sdd_id = create_sdd_snapshot(&cwd, &answers)?;
-    activate_sdd(&cwd, &sdd_id)?;
+    let sdd_id = create_sdd_snapshot(&cwd, &layout, &answers)?;
+    activate_sdd(&cwd, &layout, &sdd_id)?;
 
     println!("\nGenerated and activated SDD: {sdd_id}");
-    println!("- .forge/sdds/{sdd_id}/plan.md");
-    println!("- .forge/plan.md");
+    println!(
+        "- {}",
+        layout.display(&cwd, &layout.sdd_dir(&sdd_id).join("plan.md"))
+    );
+    println!("- {}", layout.display(&cwd, &layout.plan_file()));
     println!("- docs/specs/session/spec.md");
     println!("- docs/specs/session/acceptance.md");
     println!("- docs/specs/session/scenarios.md");
@@ -235,10 +328,12 @@ fn assistant_mode(cwd: PathBuf) -> Result<()> {
 
     run_command(
         RunCommand {
-            engine: EngineArg::Codex,
+            engine: None,
             engine_pre_args: Vec::new(),
             full_access: false,
             thinking: Some(answers.thinking),
+            model: None,
+            reasoning_effort: None,
             resume: None,
             resume_last: false,
             fresh: false,
@@ -246,25 +341,28 @@ fn assistant_mode(cwd: PathBuf) -> Result<()> {
             timeout_minutes: None,
             json: false,
             max_loops: answers.max_loops,
+            set: Vec::new(),
         },
         cwd,
+        base,
     )
 }
 
 fn sdd_command(cmd: SddCommand, cwd: PathBuf) -> Result<()> {
+    let layout = RuntimeLayout::discover(&cwd)?;
     match cmd.action {
-        SddAction::List(list) => sdd_list(cwd.as_path(), list.json),
+        SddAction::List(list) => sdd_list(&layout, list.json),
         SddAction::Load(load) => {
-            activate_sdd(cwd.as_path(), &load.id)?;
+            activate_sdd(&cwd, &layout, &load.id)?;
             println!("loaded sdd: {}", load.id);
             Ok(())
         }
     }
 }
 
-fn sdd_list(cwd: &Path, as_json: bool) -> Result<()> {
-    let root = sdd_root(cwd);
-    let current = current_sdd_id(cwd)?;
+fn sdd_list(layout: &RuntimeLayout, as_json: bool) -> Result<()> {
+    let root = layout.sdds_dir();
+    let current = current_sdd_id(layout)?;
 
     if !root.exists() {
         if as_json {
@@ -289,7 +387,7 @@ fn sdd_list(cwd: &Path, as_json: bool) -> Result<()> {
             .iter()
             .map(|e| {
                 let id = e.file_name().to_string_lossy().to_string();
-                let meta = read_sdd_meta(cwd, &id).unwrap_or_default();
+                let meta = read_sdd_meta(layout, &id).unwrap_or_default();
                 serde_json::json!({
                     "id": id,
                     "project_name": meta.project_name,
@@ -311,7 +409,7 @@ fn sdd_list(cwd: &Path, as_json: bool) -> Result<()> {
     println!("available sdds:");
     for entry in entries {
         let id = entry.file_name().to_string_lossy().to_string();
-        let meta = read_sdd_meta(cwd, &id).unwrap_or_default();
+        let meta = read_sdd_meta(layout, &id).unwrap_or_default();
         let marker = if current.as_deref() == Some(id.as_str()) {
             "*"
         } else {
@@ -415,18 +513,21 @@ fn ask(label: &str, default: &str) -> Result<String> {
     }
 }
 
-fn create_sdd_snapshot(cwd: &Path, answers: &SddInterview) -> Result<String> {
-    let forge_dir = cwd.join(".forge");
-    let sdds_dir = sdd_root(cwd);
+fn create_sdd_snapshot(
+    cwd: &Path,
+    layout: &RuntimeLayout,
+    answers: &SddInterview,
+) -> Result<String> {
+    let sdds_dir = layout.sdds_dir();
     let docs_dir = cwd.join("docs/specs/session");
-    fs::create_dir_all(&forge_dir)?;
+    fs::create_dir_all(layout.root())?;
     fs::create_dir_all(&docs_dir)?;
     fs::create_dir_all(&sdds_dir)?;
 
     let spec = render_spec(answers);
     let acceptance = render_acceptance(answers);
     let scenarios = render_scenarios(answers);
-    let plan = render_plan(answers);
+    let plan = render_plan(answers, &layout.display(cwd, layout.root()));
 
     let id = format!(
         "{}-{}",
@@ -455,18 +556,22 @@ fn create_sdd_snapshot(cwd: &Path, answers: &SddInterview) -> Result<String> {
     Ok(id)
 }
 
-fn activate_sdd(cwd: &Path, id: &str) -> Result<()> {
-    let source_dir = sdd_root(cwd).join(id);
+fn activate_sdd(cwd: &Path, layout: &RuntimeLayout, id: &str) -> Result<()> {
+    let source_dir = layout.sdd_dir(id);
     if !source_dir.exists() {
         bail!("sdd id not found: {}", id);
     }
 
-    let forge_dir = cwd.join(".forge");
     let docs_dir = cwd.join("docs/specs/session");
-    fs::create_dir_all(&forge_dir)?;
+    fs::create_dir_all(layout.root())?;
     fs::create_dir_all(&docs_dir)?;
 
-    copy_required(source_dir.join("plan.md"), forge_dir.join("plan.md"))?;
+    copy_required(source_dir.join("plan.md"), layout.plan_file())?;
+    let plan_history = layout.plan_history_file();
+    if plan_history.exists() {
+        fs::remove_file(&plan_history)
+            .with_context(|| format!("failed to remove {}", plan_history.display()))?;
+    }
     copy_required(source_dir.join("spec.md"), docs_dir.join("spec.md"))?;
     copy_required(
         source_dir.join("acceptance.md"),
@@ -477,7 +582,7 @@ fn activate_sdd(cwd: &Path, id: &str) -> Result<()> {
         docs_dir.join("scenarios.md"),
     )?;
 
-    fs::write(forge_dir.join("current_sdd"), id)?;
+    fs::write(layout.current_sdd_file(), id)?;
     Ok(())
 }
 
@@ -526,11 +631,11 @@ fn render_scenarios(a: &SddInterview) -> String {
     out
 }
 
-fn render_plan(a: &SddInterview) -> String {
+fn render_plan(a: &SddInterview, runtime_dir: &str) -> String {
     let epoch = epoch_now();
 
     format!(
-        "# Execution Plan\n\nGenerated at epoch {}\n\n## Goal\n{}\n\n## Scope\n- In: {}\n- Out: {}\n\n## Constraints\n{}\n\n## Acceptance\n{}\n\n## Scenarios\n{}\n\n## Test Strategy\n{}\n\n## Thinking Mode\n{}\n\nExecute this plan incrementally. Only stop when completion indicators are present and EXIT_SIGNAL is true. Persist status and progress in .forge/.\n",
+        "# Execution Plan\n\nGenerated at epoch {}\n\n## Goal\n{}\n\n## Scope\n- In: {}\n- Out: {}\n\n## Constraints\n{}\n\n## Acceptance\n{}\n\n## Scenarios\n{}\n\n## Test Strategy\n{}\n\n## Thinking Mode\n{}\n\nExecute this plan incrementally. Only stop when completion indicators are present and EXIT_SIGNAL is true. Persist status and progress in {}/.\n",
         epoch,
         a.product_goal,
         a.in_scope,
@@ -544,14 +649,11 @@ fn render_plan(a: &SddInterview) -> String {
             ThinkingArg::Summary => "summary",
             ThinkingArg::Raw => "raw",
         },
+        runtime_dir,
     )
 }
 
-fn run_command(cmd: RunCommand, cwd: PathBuf) -> Result<()> {
-    if cmd.fresh {
-        cleanup_runtime_state(&cwd)?;
-    }
-
+fn run_command(cmd: RunCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
     let engine_pre_args = with_full_access_args(cmd.engine_pre_args.clone(), cmd.full_access);
     let engine_exec_args = if cmd.fresh {
         Some(vec!["--ephemeral".to_string()])
@@ -562,7 +664,7 @@ fn run_command(cmd: RunCommand, cwd: PathBuf) -> Result<()> {
     let cfg = load_run_config(
         &cwd,
         &CliOverrides {
-            engine: Some(cmd.engine.into()),
+            engine: cmd.engine.map(Into::into),
             engine_pre_args: if engine_pre_args.is_empty() {
                 None
             } else {
@@ -570,12 +672,19 @@ fn run_command(cmd: RunCommand, cwd: PathBuf) -> Result<()> {
             },
             engine_exec_args,
             thinking_mode: cmd.thinking.map(Into::into),
+            model: cmd.model,
+            reasoning_effort: cmd.reasoning_effort.map(Into::into),
             max_calls_per_hour: cmd.max_calls_per_hour,
             timeout_minutes: cmd.timeout_minutes,
             resume: cmd.resume,
             resume_last: cmd.resume_last,
+            set: cmd.set,
+            ..base
         },
     )?;
+    if cmd.fresh {
+        cleanup_runtime_state(&RuntimeLayout::from_config(&cwd, &cfg))?;
+    }
 
     let outcome = run_loop(RunRequest {
         cwd,
@@ -607,7 +716,7 @@ fn run_command(cmd: RunCommand, cwd: PathBuf) -> Result<()> {
     });
 }
 
-fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf) -> Result<()> {
+fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
     let engine_pre_args = with_full_access_args(cmd.engine_pre_args.clone(), cmd.full_access);
     let engine_pre_args_override = if engine_pre_args.is_empty() {
         None
@@ -618,20 +727,27 @@ fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf) -> Result<()> {
     let cfg = load_run_config(
         &cwd,
         &CliOverrides {
-            engine: Some(cmd.engine.into()),
+            engine: cmd.engine.map(Into::into),
             engine_pre_args: engine_pre_args_override,
             engine_exec_args: Some(vec!["--ephemeral".to_string()]),
             thinking_mode: cmd.thinking.map(Into::into),
+            model: cmd.model.clone(),
+            reasoning_effort: cmd.reasoning_effort.map(Into::into),
             max_calls_per_hour: None,
             timeout_minutes: cmd.timeout_minutes,
             resume: None,
             resume_last: false,
+            set: cmd.set.clone(),
+            analyze: true,
+            ..base
         },
     )?;
 
+    let layout = RuntimeLayout::from_config(&cwd, &cfg);
     if cmd.resume_latest_report {
-        return analyze_resume_latest(cmd, cwd, cfg);
+        return analyze_resume_latest(cmd, cwd, &layout, cfg);
     }
+    let engine = create_checked_engine(&cfg)?;
 
     let files = if cmd.modified_only {
         list_modified_files(&cwd)?
@@ -671,14 +787,7 @@ fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf) -> Result<()> {
             chunk.len()
         );
         let prompt = build_analyze_prompt(chunk, &format!("chunk {}/{}", idx + 1, chunks.len()));
-        let run = run_engine_exec_with_timeout(
-            &cfg.engine_cmd,
-            &cfg.engine_pre_args,
-            &cfg.engine_exec_args,
-            &cwd,
-            &prompt,
-            cfg.timeout_minutes,
-        )?;
+        let run = run_engine_exec_with_timeout(engine.as_ref(), &cfg, &cwd, &prompt)?;
         if run.timed_out {
             timed_out_chunks += 1;
         }
@@ -712,14 +821,8 @@ fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf) -> Result<()> {
         let synthesis_prompt = format!(
             "Consolidate the following chunk analyses into exactly:\n1) Critical risks\n2) High risks\n3) Medium risks\n4) Suggested next actions\nEnd with: EXIT_SIGNAL: true\n\n{joined}"
         );
-        let synthesis = run_engine_exec_with_timeout(
-            &cfg.engine_cmd,
-            &cfg.engine_pre_args,
-            &cfg.engine_exec_args,
-            &cwd,
-            &synthesis_prompt,
-            cfg.timeout_minutes,
-        )?;
+        let synthesis =
+            run_engine_exec_with_timeout(engine.as_ref(), &cfg, &cwd, &synthesis_prompt)?;
         eprintln!(
             "analyze: synthesis done (exit_code={:?}, timed_out={})",
             synthesis.exit_code, synthesis.timed_out
@@ -735,7 +838,7 @@ fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf) -> Result<()> {
     };
 
     let persisted = persist_analyze_report(
-        &cwd,
+        &layout,
         AnalyzePersistInput {
             files: &files,
             chunks: chunks.len(),
@@ -744,6 +847,7 @@ fn analyze_command(cmd: AnalyzeCommand, cwd: PathBuf) -> Result<()> {
             failed_chunks,
             chunk_reports: &chunk_reports,
             report: &report,
+            config: &cfg,
         },
     )?;
 
@@ -808,9 +912,10 @@ fn with_full_access_args(args: Vec<String>, full_access: bool) -> Vec<String> {
 fn analyze_resume_latest(
     cmd: AnalyzeCommand,
     cwd: PathBuf,
+    layout: &RuntimeLayout,
     cfg: forge_config::RunConfig,
 ) -> Result<()> {
-    let latest = load_latest_analyze_payload(&cwd)?;
+    let latest = load_latest_analyze_payload(layout)?;
     let files = latest
         .get("files")
         .and_then(|v| v.as_array())
@@ -832,7 +937,10 @@ fn analyze_resume_latest(
         .unwrap_or_default();
 
     if chunk_reports.is_empty() {
-        bail!("no chunk_reports found in .forge/analyze/latest.json");
+        bail!(
+            "no chunk_reports found in {}",
+            layout.analyze_latest_file().display()
+        );
     }
 
     eprintln!(
@@ -840,18 +948,12 @@ fn analyze_resume_latest(
         chunk_reports.len()
     );
 
+    let engine = create_checked_engine(&cfg)?;
     let joined = chunk_reports.join("\n\n");
     let synthesis_prompt = format!(
         "Consolidate the following chunk analyses into exactly:\n1) Critical risks\n2) High risks\n3) Medium risks\n4) Suggested next actions\nEnd with: EXIT_SIGNAL: true\n\n{joined}"
     );
-    let synthesis = run_engine_exec_with_timeout(
-        &cfg.engine_cmd,
-        &cfg.engine_pre_args,
-        &cfg.engine_exec_args,
-        &cwd,
-        &synthesis_prompt,
-        cfg.timeout_minutes,
-    )?;
+    let synthesis = run_engine_exec_with_timeout(engine.as_ref(), &cfg, &cwd, &synthesis_prompt)?;
 
     let report = if synthesis.timed_out || synthesis.exit_code != Some(0) {
         format!(
@@ -863,7 +965,7 @@ fn analyze_resume_latest(
     };
 
     let persisted = persist_analyze_report(
-        &cwd,
+        layout,
         AnalyzePersistInput {
             files: &files,
             chunks: chunk_reports.len(),
@@ -875,6 +977,7 @@ fn analyze_resume_latest(
             failed_chunks: if synthesis.exit_code == Some(0) { 0 } else { 1 },
             chunk_reports: &chunk_reports,
             report: &report,
+            config: &cfg,
         },
     )?;
 
@@ -944,8 +1047,8 @@ fn build_analyze_prompt(files: &[String], scope_label: &str) -> String {
     out
 }
 
-fn load_latest_analyze_payload(cwd: &Path) -> Result<serde_json::Value> {
-    let path = cwd.join(".forge").join("analyze").join("latest.json");
+fn load_latest_analyze_payload(layout: &RuntimeLayout) -> Result<serde_json::Value> {
+    let path = layout.analyze_latest_file();
     if !path.exists() {
         bail!("latest analyze report not found at {}", path.display());
     }
@@ -977,13 +1080,14 @@ struct AnalyzePersistInput<'a> {
     failed_chunks: u64,
     chunk_reports: &'a [String],
     report: &'a str,
+    config: &'a RunConfig,
 }
 
 fn persist_analyze_report(
-    cwd: &Path,
+    layout: &RuntimeLayout,
     input: AnalyzePersistInput<'_>,
 ) -> Result<AnalyzePersistPaths> {
-    let analyze_dir = cwd.join(".forge").join("analyze");
+    let analyze_dir = layout.analyze_dir();
     let history_dir = analyze_dir.join("history");
     fs::create_dir_all(&history_dir)
         .with_context(|| format!("failed to create {}", history_dir.display()))?;
@@ -991,6 +1095,9 @@ fn persist_analyze_report(
     let now = epoch_now();
     let payload = serde_json::json!({
         "created_at_epoch": now,
+        "engine": input.config.engine.as_str(),
+        "model": input.config.model,
+        "reasoning_effort": input.config.reasoning_effort.map(ReasoningEffort::as_str),
         "modified_files": input.files.len(),
         "chunks": input.chunks,
         "chunk_size": input.chunk_size,
@@ -1001,7 +1108,7 @@ fn persist_analyze_report(
         "report": input.report,
     });
 
-    let latest_path = analyze_dir.join("latest.json");
+    let latest_path = layout.analyze_latest_file();
     fs::write(&latest_path, serde_json::to_string_pretty(&payload)?)
         .with_context(|| format!("failed to write {}", latest_path.display()))?;
 
@@ -1015,35 +1122,52 @@ fn persist_analyze_report(
     })
 }
 
+/// Creates the engine, detects its version and reports flags it lacks.
+fn create_checked_engine(cfg: &forge_config::RunConfig) -> Result<Box<dyn forge_engine::Engine>> {
+    let mut engine = forge_engine::create_engine(cfg)?;
+    engine.detect_version();
+    let compat = forge_engine::check_compatibility(engine.as_ref(), cfg);
+    if compat.is_refused() {
+        bail!("{}", compat.warnings().join("; "));
+    }
+    for warning in compat.warnings() {
+        eprintln!("analyze: warning: {warning}");
+    }
+    Ok(engine)
+}
+
 fn run_engine_exec_with_timeout(
-    engine_cmd: &str,
-    engine_pre_args: &[String],
-    engine_exec_args: &[String],
+    engine: &dyn forge_engine::Engine,
+    cfg: &forge_config::RunConfig,
     cwd: &Path,
     prompt: &str,
-    timeout_minutes: u64,
 ) -> Result<EngineExecRun> {
-    let mut args = engine_pre_args.to_vec();
-    args.push("exec".to_string());
-    args.extend(engine_exec_args.iter().cloned());
-    args.push("--json".to_string());
-    args.push(prompt.to_string());
-
-    let timeout = Duration::from_secs(timeout_minutes.saturating_mul(60));
-    let mut child = Command::new(engine_cmd)
-        .args(&args)
-        .current_dir(cwd)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .with_context(|| format!("failed to execute {}", engine_cmd))?;
+    let layout = RuntimeLayout::from_config(cwd, cfg);
+    let live_log = LiveLog::new(layout.live_log());
+    let params = forge_engine::EngineExecParams {
+        cwd,
+        config: cfg,
+        prompt: Some(prompt.to_string()),
+        live_log: &live_log,
+        output_dir: layout.root(),
+    };
+    let delivery = forge_engine::select_prompt_delivery(engine, Some(prompt));
+    eprintln!(
+        "analyze: {}",
+        forge_engine::describe_prompt_delivery(delivery, Some(prompt))
+    );
+
+    // 0 disables the timeout, as it does for `forge run`.
+    let timeout = (cfg.timeout_minutes > 0)
+        .then(|| Duration::from_secs(cfg.timeout_minutes.saturating_mul(60)));
+    let mut child = forge_engine::spawn_engine(engine, &params, delivery)?;
     let started = Instant::now();
     let mut timed_out = false;
     loop {
         if child.try_wait()?.is_some() {
             break;
         }
-        if started.elapsed() >= timeout {
+        if timeout.is_some_and(|timeout| started.elapsed() >= timeout) {
             timed_out = true;
             let _ = child.kill();
             break;
@@ -1052,13 +1176,16 @@ fn run_engine_exec_with_timeout(
     }
     let output = child
         .wait_with_output()
-        .with_context(|| format!("failed waiting for {}", engine_cmd))?;
+        .with_context(|| format!("failed waiting for {}", cfg.engine_cmd))?;
     let stdout = String::from_utf8_lossy(&output.stdout).to_string();
     let stderr = String::from_utf8_lossy(&output.stderr).to_string();
-    let report = extract_last_agent_message(&stdout).unwrap_or_else(|| {
-        let merged = format!("{} {}", stdout.trim(), stderr.trim());
-        merged.chars().take(4000).collect()
-    });
+    let report = engine
+        .parse_output(&stdout, &stderr, &[])
+        .final_message
+        .unwrap_or_else(|| {
+            let merged = format!("{} {}", stdout.trim(), stderr.trim());
+            merged.chars().take(4000).collect()
+        });
 
     Ok(EngineExecRun {
         report,
@@ -1067,46 +1194,11 @@ fn run_engine_exec_with_timeout(
     })
 }
 
-fn extract_last_agent_message(stdout: &str) -> Option<String> {
-    let mut last = None;
-    for line in stdout.lines() {
-        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
-            continue;
-        };
-        if value.get("type").and_then(|v| v.as_str()) != Some("item.completed") {
-            continue;
-        }
-        let Some(item) = value.get("item") else {
-            continue;
-        };
-        if item.get("type").and_then(|v| v.as_str()) != Some("agent_message") {
-            continue;
-        }
-        if let Some(text) = item.get("text").and_then(|v| v.as_str()) {
-            last = Some(text.to_string());
-        }
-    }
-    last
-}
+fn cleanup_runtime_state(layout: &RuntimeLayout) -> Result<()> {
+    fs::create_dir_all(layout.root())
+        .with_context(|| format!("failed to create {}", layout.root().display()))?;
 
-fn cleanup_runtime_state(cwd: &Path) -> Result<()> {
-    let runtime_dir = cwd.join(".forge");
-    fs::create_dir_all(&runtime_dir)
-        .with_context(|| format!("failed to create {}", runtime_dir.display()))?;
-
-    let files = [
-        "status.json",
-        "progress.json",
-        "live.log",
-        ".session_id",
-        ".call_count",
-        ".last_reset",
-        ".circuit_breaker_state",
-        ".circuit_breaker_history",
-    ];
-
-    for file in files {
-        let path = runtime_dir.join(file);
+    for path in layout.fresh_run_files() {
         if path.exists() {
             fs::remove_file(&path)
                 .with_context(|| format!("failed to remove {}", path.display()))?;
@@ -1116,19 +1208,22 @@ fn cleanup_runtime_state(cwd: &Path) -> Result<()> {
     Ok(())
 }
 
-fn status_command(cmd: StatusCommand, cwd: PathBuf) -> Result<()> {
-    let cfg = load_run_config(&cwd, &CliOverrides::default())?;
-    let runtime_dir = cwd.join(cfg.runtime_dir);
-    let status = read_status(&runtime_dir)?;
-    let session_id = infer_session_id(&runtime_dir, &status);
+fn status_command(cmd: StatusCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
+    let cfg = load_run_config(&cwd, &base)?;
+    let layout = RuntimeLayout::from_config(&cwd, &cfg);
+    let status = read_status(&layout)?;
+    let session_id = infer_session_id(&layout, &status);
     let usage = session_id
         .as_deref()
         .and_then(read_codex_usage_for_session_id);
 
+    let plan_progress = read_plan_history(&layout).summary();
+
     if cmd.json {
         let mut out = serde_json::json!({
             "status": status,
             "usage": usage,
+            "plan_progress": plan_progress,
         });
         if let Some(session_id) = session_id {
             out["session_id"] = serde_json::json!(session_id);
@@ -1147,7 +1242,19 @@ fn status_command(cmd: StatusCommand, cwd: PathBuf) -> Result<()> {
         };
 
         println!("state: {}", status.state);
+        if !status.engine.is_empty() {
+            println!("engine: {}", status.engine);
+        }
+        if let Some(profile) = &status.profile {
+            println!("profile: {profile}");
+        }
         println!("thinking_mode: {}", status.thinking_mode);
+        if let Some(model) = &status.model {
+            println!("model: {model}");
+        }
+        if let Some(effort) = &status.reasoning_effort {
+            println!("reasoning_effort: {effort}");
+        }
         println!("run_timer: {}", run_timer);
         println!("current_loop: {}", status.current_loop);
         println!("command_timer: {}", command_timer);
@@ -1162,7 +1269,17 @@ fn status_command(cmd: StatusCommand, cwd: PathBuf) -> Result<()> {
             "session_id: {}",
             session_id.unwrap_or_else(|| "-".to_string())
         );
+        if let Some(tokens) = &status.last_token_usage {
+            println!("last_loop_tokens: {}", tokens.summary());
+        }
         println!("context: {}", format_context_line(usage.as_ref()));
+        println!(
+            "plan_progress: {}/{} remaining over {} loop(s), {}",
+            plan_progress.remaining,
+            plan_progress.total,
+            plan_progress.loops_recorded,
+            plan_progress.trend.as_str()
+        );
         println!(
             "5h limit: {}",
             format_limit_line(
@@ -1197,20 +1314,28 @@ struct CodexUsageSnapshot {
     seven_day_resets_at: Option<String>,
 }
 
-fn infer_session_id(runtime_dir: &Path, status: &forge_types::RunStatus) -> Option<String> {
+fn infer_session_id(layout: &RuntimeLayout, status: &forge_types::RunStatus) -> Option<String> {
     if let Some(session_id) = status.session_id.clone() {
         if !session_id.trim().is_empty() {
             return Some(session_id);
         }
     }
 
-    let path = runtime_dir.join("live.log");
-    let raw = fs::read_to_string(path).ok()?;
-    for line in raw.lines().rev() {
+    let raw = fs::read_to_string(layout.live_log()).ok()?;
+    for line in current_run_segment(&raw).lines().rev() {
         let trimmed = line.trim();
-        let Ok(value) = serde_json::from_str::<Value>(trimmed) else {
-            continue;
+        let value = match serde_json::from_str::<LiveLogEntry>(trimmed) {
+            Ok(entry) => entry.payload,
+            Err(_) => match serde_json::from_str::<Value>(trimmed) {
+                Ok(value) => value,
+                Err(_) => continue,
+            },
         };
+        if let Some(session_id) = value.get("sessionID").and_then(Value::as_str) {
+            if !session_id.trim().is_empty() {
+                return Some(session_id.to_string());
+            }
+        }
         if value.get("type").and_then(Value::as_str) == Some("thread.started") {
             if let Some(thread_id) = value.get("thread_id").and_then(Value::as_str) {
                 if !thread_id.trim().is_empty() {
@@ -1465,27 +1590,39 @@ fn format_reset_timestamp(epoch_seconds: i64) -> String {
     }
 }
 
-fn monitor_command(cmd: MonitorCommand, cwd: PathBuf) -> Result<()> {
-    let cfg = load_run_config(&cwd, &CliOverrides::default())?;
-    let runtime_dir: PathBuf = cwd.join(cfg.runtime_dir);
+fn monitor_command(cmd: MonitorCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
+    let cfg = load_run_config(&cwd, &base)?;
     run_monitor(
-        &runtime_dir,
+        &RuntimeLayout::from_config(&cwd, &cfg),
         cmd.refresh_ms,
         cmd.stall_threshold_secs.max(1),
     )
 }
 
-fn doctor_command(cmd: DoctorCommand, cwd: PathBuf) -> Result<()> {
-    let before = collect_doctor_checks(&cwd);
-    let before_warnings = collect_doctor_warnings(&cwd);
+fn doctor_command(cmd: DoctorCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
+    let overrides = CliOverrides {
+        set: cmd.set.clone(),
+        ..base
+    };
+    // A broken .forgerc is reported by config_loadable; the checks still
+    // need somewhere to look for runtime state.
+    let layout = load_run_config(&cwd, &overrides)
+        .map(|cfg| RuntimeLayout::from_config(&cwd, &cfg))
+        .or_else(|_| RuntimeLayout::discover(&cwd))
+        .unwrap_or_else(|_| RuntimeLayout::new(cwd.join(DEFAULT_RUNTIME_DIR)));
+    let before = collect_doctor_checks(&cwd, &layout, &overrides);
+    let before_warnings = collect_doctor_warnings(&cwd, &layout, &overrides);
     let mut attempted_fixes = Vec::new();
     if cmd.fix {
-        attempted_fixes = apply_doctor_fixes(&cwd)?;
+        attempted_fixes = apply_doctor_fixes(&cwd, &layout)?;
     }
-    let checks = collect_doctor_checks(&cwd);
+    let checks = collect_doctor_checks(&cwd, &layout, &overrides);
     let failed = checks.iter().filter(|c| !c.ok).count();
-    let warnings = collect_doctor_warnings(&cwd);
+    let warnings = collect_doctor_warnings(&cwd, &layout, &overrides);
     let strict_failed = cmd.strict && !warnings.is_empty();
+    let engine_env = load_run_config(&cwd, &overrides)
+        .map(|cfg| forge_engine::effective_engine_env(&cfg))
+        .unwrap_or_default();
 
     if cmd.json {
         let report = serde_json::json!({
@@ -1505,6 +1642,14 @@ fn doctor_command(cmd: DoctorCommand, cwd: PathBuf) -> Result<()> {
                 }))
                 .collect::<Vec<_>>(),
             "warnings": warnings,
+            "engine_env": engine_env
+                .iter()
+                .map(|var| serde_json::json!({
+                    "name": var.name,
+                    "value": forge_engine::mask_env_value(&var.value),
+                    "source": var.source,
+                }))
+                .collect::<Vec<_>>(),
             "checks": checks
                 .iter()
                 .map(|c| serde_json::json!({
@@ -1539,6 +1684,17 @@ fn doctor_command(cmd: DoctorCommand, cwd: PathBuf) -> Result<()> {
                 println!("- warning: {}", warning);
             }
         }
+        println!(
+            "- engine_env: {} variable(s), values masked",
+            engine_env.len()
+        );
+        for var in &engine_env {
+            let source = match var.source {
+                forge_engine::EnvSource::Inherited => "inherited",
+                forge_engine::EnvSource::Explicit => "explicit",
+            };
+            println!("  {} ({})", var.masked(), source);
+        }
     }
 
     if failed > 0 {
@@ -1557,22 +1713,37 @@ struct DoctorCheck {
     detail: String,
 }
 
-fn collect_doctor_checks(cwd: &Path) -> Vec<DoctorCheck> {
-    let cfg = load_run_config(cwd, &CliOverrides::default()).ok();
-    let engine = if let Some(ref c) = cfg {
-        check_engine_available(c.engine, &c.engine_cmd)
+fn collect_doctor_checks(
+    cwd: &Path,
+    layout: &RuntimeLayout,
+    overrides: &CliOverrides,
+) -> Vec<DoctorCheck> {
+    let cfg = load_run_config(cwd, overrides).ok();
+    let engine = if let Some(c) = cfg
+        .as_ref()
+        .filter(|c| matches!(c.engine, EngineKind::Custom | EngineKind::Mock))
+    {
+        check_custom_engine_available(c)
+    } else if let Some(ref c) = cfg {
+        check_engine_available(c.engine, &c.engine_cmd, Some(&forge_engine::engine_env(c)))
     } else {
-        check_engine_available(EngineKind::Codex, "codex")
+        check_engine_available(EngineKind::Codex, "codex", None)
     };
+    let compat = check_engine_compatibility(cfg.as_ref());
     let git = check_git_repo(cwd);
-    let write = check_runtime_writable(cwd);
-    let config = check_config_loadable(cwd);
+    let write = check_runtime_writable(layout);
+    let config = check_config_loadable(cwd, overrides);
     vec![
         DoctorCheck {
             name: "engine_available",
             ok: engine.0,
             detail: engine.1,
         },
+        DoctorCheck {
+            name: "engine_compatibility",
+            ok: compat.0,
+            detail: compat.1,
+        },
         DoctorCheck {
             name: "git_repository",
             ok: git.0,
@@ -1591,14 +1762,17 @@ fn collect_doctor_checks(cwd: &Path) -> Vec<DoctorCheck> {
     ]
 }
 
-fn apply_doctor_fixes(cwd: &Path) -> Result<Vec<String>> {
+fn apply_doctor_fixes(cwd: &Path, layout: &RuntimeLayout) -> Result<Vec<String>> {
     let mut fixes = Vec::new();
 
-    let runtime_dir = cwd.join(".forge");
+    let runtime_dir = layout.root();
     if !runtime_dir.exists() {
-        fs::create_dir_all(&runtime_dir)
+        fs::create_dir_all(runtime_dir)
             .with_context(|| format!("failed to create {}", runtime_dir.display()))?;
-        fixes.push("created .forge runtime directory".to_string());
+        fixes.push(format!(
+            "created {} runtime directory",
+            layout.display(cwd, runtime_dir)
+        ));
     }
 
     let forgerc = cwd.join(".forgerc");
@@ -1612,24 +1786,79 @@ fn apply_doctor_fixes(cwd: &Path) -> Result<Vec<String>> {
     Ok(fixes)
 }
 
-fn collect_doctor_warnings(cwd: &Path) -> Vec<String> {
+fn collect_doctor_warnings(
+    cwd: &Path,
+    layout: &RuntimeLayout,
+    overrides: &CliOverrides,
+) -> Vec<String> {
     let mut warnings = Vec::new();
     if !cwd.join(".forgerc").exists() {
         warnings.push("missing .forgerc (using only env/defaults)".to_string());
     }
-    if !cwd
-        .join(".forge")
-        .join("analyze")
-        .join("latest.json")
-        .exists()
+    let latest_report = layout.analyze_latest_file();
+    if !latest_report.exists() {
+        warnings.push(format!(
+            "no persisted analyze report yet ({})",
+            layout.display(cwd, &latest_report)
+        ));
+    }
+    if let Some(compat) = load_run_config(cwd, overrides)
+        .ok()
+        .and_then(|cfg| detect_engine_compatibility(&cfg))
+        .filter(|compat| !compat.is_refused())
     {
-        warnings.push("no persisted analyze report yet (.forge/analyze/latest.json)".to_string());
+        warnings.extend(compat.warnings());
     }
     warnings
 }
 
-fn check_engine_available(engine: EngineKind, cmd: &str) -> (bool, String) {
-    match Command::new(cmd).arg("--version").output() {
+/// Probes the configured engine's version, or `None` if it cannot be built.
+fn detect_engine_compatibility(
+    cfg: &forge_config::RunConfig,
+) -> Option<forge_engine::EngineCompatibility> {
+    let mut engine = forge_engine::create_engine(cfg).ok()?;
+    engine.detect_version();
+    Some(forge_engine::check_compatibility(engine.as_ref(), cfg))
+}
+
+fn check_engine_compatibility(cfg: Option<&forge_config::RunConfig>) -> (bool, String) {
+    let Some(compat) = cfg.and_then(detect_engine_compatibility) else {
+        return (true, "skipped: no usable engine config".to_string());
+    };
+    let version = compat
+        .version
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "version unknown".to_string());
+    if compat.is_refused() {
+        return (false, compat.warnings().join("; "));
+    }
+    if compat.dropped.is_empty() {
+        (
+            true,
+            format!("{} {}: all flags supported", compat.engine, version),
+        )
+    } else {
+        (
+            true,
+            format!(
+                "{} {}: {} flag(s) will be omitted",
+                compat.engine,
+                version,
+                compat.dropped.len()
+            ),
+        )
+    }
+}
+
+fn check_engine_available(
+    engine: EngineKind,
+    cmd: &str,
+    env: Option<&[(String, String)]>,
+) -> (bool, String) {
+    match forge_engine::engine_command(cmd, env)
+        .arg("--version")
+        .output()
+    {
         Ok(output) if output.status.success() => {
             let v = String::from_utf8_lossy(&output.stdout).trim().to_string();
             (
@@ -1653,6 +1882,24 @@ fn check_engine_available(engine: EngineKind, cmd: &str) -> (bool, String) {
     }
 }
 
+fn check_custom_engine_available(cfg: &forge_config::RunConfig) -> (bool, String) {
+    match forge_engine::create_engine(cfg) {
+        Ok(engine) if engine.is_available() => (
+            true,
+            format!("{} found ({})", engine.name(), cfg.engine_cmd),
+        ),
+        Ok(engine) => (
+            false,
+            format!(
+                "{} not available: probe failed for {}",
+                engine.name(),
+                cfg.engine_cmd
+            ),
+        ),
+        Err(err) => (false, format!("{err:#}")),
+    }
+}
+
 fn check_git_repo(cwd: &Path) -> (bool, String) {
     match Command::new("git")
         .args(["rev-parse", "--is-inside-work-tree"])
@@ -1675,10 +1922,13 @@ fn check_git_repo(cwd: &Path) -> (bool, String) {
     }
 }
 
-fn check_runtime_writable(cwd: &Path) -> (bool, String) {
-    let runtime_dir = cwd.join(".forge");
-    if let Err(err) = fs::create_dir_all(&runtime_dir) {
-        return (false, format!("cannot create .forge: {}", err));
+fn check_runtime_writable(layout: &RuntimeLayout) -> (bool, String) {
+    let runtime_dir = layout.root();
+    if let Err(err) = fs::create_dir_all(runtime_dir) {
+        return (
+            false,
+            format!("cannot create {}: {}", runtime_dir.display(), err),
+        );
     }
     let probe = runtime_dir.join(".doctor_write_probe");
     match fs::write(&probe, "ok") {
@@ -1690,10 +1940,12 @@ fn check_runtime_writable(cwd: &Path) -> (bool, String) {
     }
 }
 
-fn check_config_loadable(cwd: &Path) -> (bool, String) {
-    match load_run_config(cwd, &CliOverrides::default()) {
-        Ok(_) => (true, ".forgerc/env/defaults load".to_string()),
-        Err(err) => (false, format!("config error: {}", err)),
+fn check_config_loadable(cwd: &Path, overrides: &CliOverrides) -> (bool, String) {
+    let problems = validate_config(cwd, overrides);
+    if problems.is_empty() {
+        (true, ".forgerc/env/defaults load".to_string())
+    } else {
+        (false, format!("config error: {}", problems.join("; ")))
     }
 }
 
@@ -1705,12 +1957,8 @@ fn resolve_cwd(cwd: Option<PathBuf>) -> Result<PathBuf> {
     Ok(path.canonicalize()?)
 }
 
-fn sdd_root(cwd: &Path) -> PathBuf {
-    cwd.join(".forge").join("sdds")
-}
-
-fn current_sdd_id(cwd: &Path) -> Result<Option<String>> {
-    let path = cwd.join(".forge").join("current_sdd");
+fn current_sdd_id(layout: &RuntimeLayout) -> Result<Option<String>> {
+    let path = layout.current_sdd_file();
     if !path.exists() {
         return Ok(None);
     }
@@ -1732,8 +1980,8 @@ struct SddMeta {
     created_at_epoch: u64,
 }
 
-fn read_sdd_meta(cwd: &Path, id: &str) -> Result<SddMeta> {
-    let path = sdd_root(cwd).join(id).join("meta.json");
+fn read_sdd_meta(layout: &RuntimeLayout, id: &str) -> Result<SddMeta> {
+    let path = layout.sdd_dir(id).join("meta.json");
     if !path.exists() {
         return Ok(SddMeta::default());
     }
@@ -0,0 +1,275 @@
+use anyhow::{bail, Context, Result};
+use clap::Subcommand;
+use forge_config::RuntimeLayout;
+use forge_core::{read_plan_history, PlanDocument, PlanHistory, PlanItem};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct PlanCommand {
+    #[arg(long, global = true)]
+    sdd: Option<String>,
+
+    #[command(subcommand)]
+    action: PlanAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum PlanAction {
+    Show(PlanShowCommand),
+    Add(PlanAddCommand),
+    Check(PlanItemCommand),
+    Uncheck(PlanItemCommand),
+    Move(PlanMoveCommand),
+    Lint(PlanLintCommand),
+    Progress(PlanProgressCommand),
+}
+
+#[derive(Debug, clap::Args)]
+struct PlanShowCommand {
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct PlanAddCommand {
+    text: String,
+
+    #[arg(long)]
+    after: Option<usize>,
+}
+
+#[derive(Debug, clap::Args)]
+struct PlanItemCommand {
+    id: usize,
+}
+
+#[derive(Debug, clap::Args)]
+struct PlanMoveCommand {
+    id: usize,
+
+    #[arg(long)]
+    to: usize,
+}
+
+#[derive(Debug, clap::Args)]
+struct PlanLintCommand {
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct PlanProgressCommand {
+    #[arg(long)]
+    json: bool,
+}
+
+pub(crate) fn plan_command(cmd: PlanCommand, cwd: PathBuf) -> Result<()> {
+    let layout = RuntimeLayout::discover(&cwd)?;
+    let sdd = cmd.sdd.as_deref();
+    let open = || -> Result<(PathBuf, PlanDocument)> {
+        let path = resolve_plan_path(&layout, sdd)?;
+        let doc = read_plan(&path)?;
+        Ok((path, doc))
+    };
+
+    match cmd.action {
+        PlanAction::Show(show) => {
+            let (path, doc) = open()?;
+            plan_show(&path, &doc, show.json)
+        }
+        PlanAction::Add(add) => {
+            let (path, mut doc) = open()?;
+            let id = doc.add(&add.text, add.after)?;
+            write_plan(&path, &doc)?;
+            println!("added item {}: {}", id, add.text.trim());
+            Ok(())
+        }
+        PlanAction::Check(item) => {
+            let (path, mut doc) = open()?;
+            doc.set_checked(item.id, true)?;
+            write_plan(&path, &doc)?;
+            println!("checked item {}", item.id);
+            Ok(())
+        }
+        PlanAction::Uncheck(item) => {
+            let (path, mut doc) = open()?;
+            doc.set_checked(item.id, false)?;
+            write_plan(&path, &doc)?;
+            println!("unchecked item {}", item.id);
+            Ok(())
+        }
+        PlanAction::Move(mv) => {
+            let (path, mut doc) = open()?;
+            doc.move_item(mv.id, mv.to)?;
+            write_plan(&path, &doc)?;
+            println!("moved item {} to position {}", mv.id, mv.to);
+            Ok(())
+        }
+        PlanAction::Lint(lint) => {
+            let (path, doc) = open()?;
+            plan_lint(&path, &doc, lint.json)
+        }
+        PlanAction::Progress(progress) => {
+            if sdd.is_some() {
+                bail!("plan progress only tracks the active plan; drop --sdd");
+            }
+            plan_progress(&read_plan_history(&layout), progress.json)
+        }
+    }
+}
+
+fn resolve_plan_path(layout: &RuntimeLayout, sdd: Option<&str>) -> Result<PathBuf> {
+    match sdd {
+        Some(id) => {
+            let dir = layout.sdd_dir(id);
+            if !dir.exists() {
+                bail!("sdd id not found: {}", id);
+            }
+            Ok(dir.join("plan.md"))
+        }
+        None => Ok(layout.plan_file()),
+    }
+}
+
+fn read_plan(path: &Path) -> Result<PlanDocument> {
+    if !path.exists() {
+        bail!("plan not found at {}", path.display());
+    }
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    Ok(PlanDocument::parse(&raw))
+}
+
+fn write_plan(path: &Path, doc: &PlanDocument) -> Result<()> {
+    fs::write(path, doc.render()).with_context(|| format!("failed to write {}", path.display()))
+}
+
+fn plan_show(path: &Path, doc: &PlanDocument, as_json: bool) -> Result<()> {
+    let items = doc.items();
+    let checked = items.iter().filter(|item| item.checked).count();
+
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "path": path.display().to_string(),
+                "total_items": items.len(),
+                "checked_items": checked,
+                "unchecked_items": items.len() - checked,
+                "items": items,
+            }))?
+        );
+        return Ok(());
+    }
+
+    println!("plan: {}", path.display());
+    if items.is_empty() {
+        println!("no checklist items");
+        return Ok(());
+    }
+    for item in &items {
+        println!("{}", format_item(item));
+    }
+    println!(
+        "\n{} item(s): {} checked, {} unchecked",
+        items.len(),
+        checked,
+        items.len() - checked
+    );
+    Ok(())
+}
+
+fn plan_lint(path: &Path, doc: &PlanDocument, as_json: bool) -> Result<()> {
+    let issues = doc.lint();
+
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "path": path.display().to_string(),
+                "ok": issues.is_empty(),
+                "issues": issues,
+            }))?
+        );
+    } else if issues.is_empty() {
+        println!("plan lint: ok ({})", path.display());
+    } else {
+        println!("plan lint: {}", path.display());
+        for issue in &issues {
+            match issue.line {
+                Some(line) => println!("- line {}: {}", line, issue.message),
+                None => println!("- {}", issue.message),
+            }
+        }
+    }
+
+    if !issues.is_empty() {
+        bail!("plan lint found {} issue(s)", issues.len());
+    }
+    Ok(())
+}
+
+fn plan_progress(history: &PlanHistory, as_json: bool) -> Result<()> {
+    let summary = history.summary();
+
+    if as_json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "summary": summary,
+                "items": history.items,
+            }))?
+        );
+        return Ok(());
+    }
+
+    if history.loops_recorded == 0 {
+        println!("no loops recorded yet");
+        return Ok(());
+    }
+
+    println!(
+        "{}/{} item(s) remaining after {} loop(s), trend: {}",
+        summary.remaining,
+        summary.total,
+        summary.loops_recorded,
+        summary.trend.as_str()
+    );
+    println!("\nitems:");
+    for item in &history.items {
+        let checked = item
+            .checked_in_loop
+            .map(|n| format!("checked in loop {n}"))
+            .unwrap_or_else(|| "open".to_string());
+        let reopened = if item.reopened > 0 {
+            format!(", reopened {}x", item.reopened)
+        } else {
+            String::new()
+        };
+        println!(
+            "- {} (seen loop {}, {}, {} loop(s) spent{})",
+            item.text, item.first_seen_loop, checked, item.loops_spent, reopened
+        );
+    }
+
+    println!("\nburndown:");
+    let widest = summary.burndown.iter().map(|p| p.total).max().unwrap_or(0);
+    for point in &summary.burndown {
+        let bar = "#".repeat(point.remaining);
+        println!(
+            "loop {:>3} {:<width$} {}/{}",
+            point.loop_number,
+            bar,
+            point.remaining,
+            point.total,
+            width = widest
+        );
+    }
+    Ok(())
+}
+
+fn format_item(item: &PlanItem) -> String {
+    let mark = if item.checked { "x" } else { " " };
+    format!("{:>3} [{}] {}", item.id, mark, item.text)
+}
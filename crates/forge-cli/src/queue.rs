@@ -0,0 +1,225 @@
+use anyhow::{bail, Result};
+use clap::Subcommand;
+use forge_config::{load_run_config, CliOverrides, RuntimeLayout};
+use forge_core::{
+    read_queue, run_loop, write_queue, ExitReason, QueueEntry, QueueEntryState, QueuePolicy,
+    RunRequest, SddQueue,
+};
+use std::path::{Path, PathBuf};
+
+use crate::{activate_sdd, epoch_now, EngineArg};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct QueueCommand {
+    #[command(subcommand)]
+    action: QueueAction,
+}
+
+#[derive(Debug, Subcommand)]
+enum QueueAction {
+    Add(QueueAddCommand),
+    List(QueueListCommand),
+    Run(QueueRunCommand),
+}
+
+#[derive(Debug, clap::Args)]
+struct QueueAddCommand {
+    sdd_id: String,
+
+    #[arg(long)]
+    max_loops: Option<u64>,
+}
+
+#[derive(Debug, clap::Args)]
+struct QueueListCommand {
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, clap::Args)]
+struct QueueRunCommand {
+    #[arg(long, value_enum, default_value = "stop-on-failure")]
+    policy: PolicyArg,
+
+    #[arg(long, value_enum)]
+    engine: Option<EngineArg>,
+
+    /// Loop budget for entries queued without their own --max-loops.
+    #[arg(long, default_value_t = 100)]
+    max_loops: u64,
+
+    #[arg(long)]
+    json: bool,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum PolicyArg {
+    StopOnFailure,
+    Continue,
+}
+
+impl From<PolicyArg> for QueuePolicy {
+    fn from(value: PolicyArg) -> Self {
+        match value {
+            PolicyArg::StopOnFailure => QueuePolicy::StopOnFailure,
+            PolicyArg::Continue => QueuePolicy::Continue,
+        }
+    }
+}
+
+pub(crate) fn queue_command(cmd: QueueCommand, cwd: PathBuf, base: CliOverrides) -> Result<()> {
+    let cfg = load_run_config(&cwd, &base)?;
+    let layout = RuntimeLayout::from_config(&cwd, &cfg);
+
+    match cmd.action {
+        QueueAction::Add(add) => {
+            if !layout.sdd_dir(&add.sdd_id).exists() {
+                bail!("sdd id not found: {}", add.sdd_id);
+            }
+            let mut queue = read_queue(&layout);
+            queue.add(&add.sdd_id, add.max_loops, epoch_now())?;
+            write_queue(&layout, &queue)?;
+            println!("queued {} at position {}", add.sdd_id, queue.entries.len());
+            Ok(())
+        }
+        QueueAction::List(list) => queue_list(&read_queue(&layout), list.json),
+        QueueAction::Run(run) => queue_run(&cwd, &base, &layout, run),
+    }
+}
+
+fn queue_list(queue: &SddQueue, as_json: bool) -> Result<()> {
+    if as_json {
+        println!("{}", serde_json::to_string_pretty(queue)?);
+        return Ok(());
+    }
+    if queue.entries.is_empty() {
+        println!("queue is empty");
+        return Ok(());
+    }
+    for (idx, entry) in queue.entries.iter().enumerate() {
+        println!("{:>3} {}", idx + 1, format_entry(entry));
+    }
+    Ok(())
+}
+
+fn queue_run(
+    cwd: &Path,
+    base: &CliOverrides,
+    layout: &RuntimeLayout,
+    cmd: QueueRunCommand,
+) -> Result<()> {
+    let policy = QueuePolicy::from(cmd.policy);
+    let mut queue = read_queue(layout);
+    let recovered = queue.recover_interrupted();
+    if recovered > 0 {
+        println!("requeued {} interrupted entr(ies)", recovered);
+    }
+
+    let mut ran = Vec::new();
+    let mut failures = 0_usize;
+    while let Some(index) = queue.next_pending() {
+        let sdd_id = queue.entries[index].sdd_id.clone();
+        let max_loops = queue.entries[index].max_loops.unwrap_or(cmd.max_loops);
+        queue.start(index, epoch_now());
+        write_queue(layout, &queue)?;
+        if !cmd.json {
+            println!("queue: running {} (max_loops={})", sdd_id, max_loops);
+        }
+
+        let result = activate_sdd(cwd, layout, &sdd_id)
+            .and_then(|_| {
+                load_run_config(
+                    cwd,
+                    &CliOverrides {
+                        engine: cmd.engine.map(Into::into),
+                        ..base.clone()
+                    },
+                )
+            })
+            .and_then(|config| {
+                run_loop(RunRequest {
+                    cwd: cwd.to_path_buf(),
+                    config,
+                    max_loops,
+                })
+            });
+
+        let succeeded = match result {
+            Ok(outcome) => {
+                let succeeded = outcome.reason == ExitReason::Completed;
+                queue.finish(
+                    index,
+                    succeeded,
+                    Some(outcome.reason),
+                    outcome.loops_executed,
+                    None,
+                    epoch_now(),
+                );
+                succeeded
+            }
+            Err(err) => {
+                queue.finish(index, false, None, 0, Some(format!("{err:#}")), epoch_now());
+                false
+            }
+        };
+        write_queue(layout, &queue)?;
+        if !cmd.json {
+            println!("queue: {}", format_entry(&queue.entries[index]));
+        }
+        ran.push(queue.entries[index].clone());
+
+        if !succeeded {
+            failures += 1;
+            if policy == QueuePolicy::StopOnFailure {
+                break;
+            }
+        }
+    }
+
+    let remaining = queue
+        .entries
+        .iter()
+        .filter(|entry| entry.state == QueueEntryState::Pending)
+        .count();
+    if cmd.json {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&serde_json::json!({
+                "ran": ran,
+                "failed": failures,
+                "remaining": remaining,
+            }))?
+        );
+    } else if ran.is_empty() {
+        println!("queue: nothing pending");
+    } else {
+        println!(
+            "queue: ran {}, failed {}, remaining {}",
+            ran.len(),
+            failures,
+            remaining
+        );
+    }
+
+    if failures > 0 {
+        bail!("queue finished with {} failed sdd(s)", failures);
+    }
+    Ok(())
+}
+
+fn format_entry(entry: &QueueEntry) -> String {
+    let mut line = format!("{} [{}]", entry.sdd_id, entry.state.as_str());
+    if let Some(max_loops) = entry.max_loops {
+        line.push_str(&format!(" max_loops={}", max_loops));
+    }
+    if let Some(reason) = &entry.exit_reason {
+        line.push_str(&format!(
+            " reason={} loops={}",
+            reason, entry.loops_executed
+        ));
+    }
+    if let Some(error) = &entry.error {
+        line.push_str(&format!(" error={}", error));
+    }
+    line
+}
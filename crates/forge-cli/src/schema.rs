@@ -0,0 +1,25 @@
+use anyhow::Result;
+use forge_types::{progress_schema, status_schema};
+
+#[derive(Debug, clap::Args)]
+pub(crate) struct SchemaCommand {
+    /// Runtime file to describe.
+    #[arg(value_enum, default_value = "status")]
+    file: SchemaFile,
+}
+
+#[derive(Debug, Clone, Copy, clap::ValueEnum)]
+enum SchemaFile {
+    Status,
+    Progress,
+}
+
+/// Prints the JSON Schema of a runtime state file.
+pub(crate) fn schema_command(cmd: SchemaCommand) -> Result<()> {
+    let schema = match cmd.file {
+        SchemaFile::Status => status_schema(),
+        SchemaFile::Progress => progress_schema(),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
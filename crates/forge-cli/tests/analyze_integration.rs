@@ -30,6 +30,10 @@ fn setup_fake_codex(script: &Path, call_log: &Path) {
     let body = format!(
         r#"#!/usr/bin/env bash
 set -euo pipefail
+if [[ "${{1:-}}" == "--version" ]]; then
+  echo "codex-cli 0.60.0"
+  exit 0
+fi
 last="${{!#}}"
 if [[ "$last" == *"Consolidate the following chunk analyses"* ]]; then
   echo "SYNTH" >> "{call_log}"
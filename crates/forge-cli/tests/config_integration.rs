@@ -0,0 +1,395 @@
+use assert_cmd::Command;
+use predicates::str::contains;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn forge_cmd(root: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_forge"));
+    cmd.env_remove("FORGE_ENGINE")
+        .env_remove("FORGE_PROFILE")
+        .env_remove("FORGE_THINKING_MODE")
+        .env_remove("FORGE_TIMEOUT_MINUTES")
+        .env_remove("FORGE_MAX_CALLS_PER_HOUR")
+        .env_remove("FORGE_RUNTIME_DIR")
+        .env("XDG_CONFIG_HOME", root.join("xdg"))
+        .args(["--cwd", root.to_string_lossy().as_ref()]);
+    cmd
+}
+
+fn setup_mock_project(root: &Path) {
+    fs::create_dir_all(root.join(".forge")).expect("create runtime dir");
+    fs::write(root.join(".forge/plan.md"), "- [ ] ship it\n").expect("write plan");
+    fs::write(
+        root.join("scenario.toml"),
+        "[[loops]]\nsession_id = \"mock-thread-1\"\nagent_message = \"STATUS: COMPLETE\\nEXIT_SIGNAL: true\"\n",
+    )
+    .expect("write scenario");
+    fs::write(
+        root.join(".forgerc"),
+        "engine = \"mock\"\nmock_scenario = \"scenario.toml\"\nmax_calls_per_hour = 40\n",
+    )
+    .expect("write forgerc");
+}
+
+fn entry<'a>(entries: &'a Value, key: &str) -> &'a Value {
+    entries
+        .as_array()
+        .expect("entries array")
+        .iter()
+        .find(|e| e["key"] == key)
+        .unwrap_or_else(|| panic!("missing {key}"))
+}
+
+#[test]
+fn forgerc_engine_applies_without_engine_flag() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+
+    let output = forge_cmd(dir.path())
+        .args(["run", "--max-loops", "2", "--json"])
+        .output()
+        .expect("run forge");
+    let run: Value = serde_json::from_slice(&output.stdout).expect("run json");
+    assert_eq!(run["reason"], "Completed");
+}
+
+#[test]
+fn config_show_reports_value_sources() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+
+    let output = forge_cmd(dir.path())
+        .env("FORGE_MAX_CALLS_PER_HOUR", "7")
+        .args(["config", "show", "--json"])
+        .output()
+        .expect("config show");
+    assert!(output.status.success());
+    let entries: Value = serde_json::from_slice(&output.stdout).expect("config json");
+
+    assert_eq!(entry(&entries, "engine")["value"], "mock");
+    assert_eq!(entry(&entries, "engine")["source"], ".forgerc");
+    assert_eq!(entry(&entries, "max_calls_per_hour")["value"], 7);
+    assert_eq!(
+        entry(&entries, "max_calls_per_hour")["source"],
+        "env FORGE_MAX_CALLS_PER_HOUR"
+    );
+    assert_eq!(entry(&entries, "timeout_minutes")["value"], 15);
+    assert_eq!(entry(&entries, "timeout_minutes")["source"], "default");
+
+    forge_cmd(dir.path())
+        .args(["config", "show"])
+        .assert()
+        .success()
+        .stdout(contains("engine"))
+        .stdout(contains("(.forgerc)"))
+        .stdout(contains("(default)"));
+}
+
+#[test]
+fn profile_flag_applies_and_is_recorded_in_status() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+    let mut forgerc = fs::read_to_string(dir.path().join(".forgerc")).expect("read forgerc");
+    forgerc.push_str("\n[profile.quick]\ntimeout_minutes = 3\n");
+    fs::write(dir.path().join(".forgerc"), forgerc).expect("write forgerc");
+
+    let output = forge_cmd(dir.path())
+        .args(["--profile", "quick", "config", "show", "--json"])
+        .output()
+        .expect("config show");
+    let entries: Value = serde_json::from_slice(&output.stdout).expect("config json");
+    assert_eq!(entry(&entries, "profile")["value"], "quick");
+    assert_eq!(entry(&entries, "profile")["source"], "flag");
+    assert_eq!(entry(&entries, "timeout_minutes")["value"], 3);
+    assert_eq!(entry(&entries, "timeout_minutes")["source"], "profile");
+
+    forge_cmd(dir.path())
+        .env("FORGE_PROFILE", "quick")
+        .args(["run", "--max-loops", "2", "--json"])
+        .assert()
+        .success();
+    let status: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".forge/status.json")).expect("status"),
+    )
+    .expect("status json");
+    assert_eq!(status["profile"], "quick");
+
+    forge_cmd(dir.path())
+        .args(["run", "--profile", "nope", "--max-loops", "1"])
+        .assert()
+        .failure()
+        .stderr(contains("profile \"nope\" not found"));
+}
+
+#[test]
+fn user_config_sits_below_project_forgerc() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+    let mut forgerc = fs::read_to_string(dir.path().join(".forgerc")).expect("read forgerc");
+    forgerc.push_str("engine_pre_args = [\"--project\"]\n");
+    fs::write(dir.path().join(".forgerc"), forgerc).expect("write forgerc");
+    let user_dir = dir.path().join("xdg/forge");
+    fs::create_dir_all(&user_dir).expect("create user config dir");
+    fs::write(
+        user_dir.join("config.toml"),
+        "timeout_minutes = 30\nmax_calls_per_hour = 5\nengine_pre_args = [\"--user\"]\n",
+    )
+    .expect("write user config");
+
+    let show = |extra: &[&str]| -> Value {
+        let output = forge_cmd(dir.path())
+            .args(extra)
+            .args(["config", "show", "--json"])
+            .output()
+            .expect("config show");
+        assert!(output.status.success());
+        serde_json::from_slice(&output.stdout).expect("config json")
+    };
+
+    let entries = show(&[]);
+    assert_eq!(entry(&entries, "timeout_minutes")["value"], 30);
+    assert_eq!(entry(&entries, "timeout_minutes")["source"], "user config");
+    assert_eq!(entry(&entries, "max_calls_per_hour")["value"], 40);
+    assert_eq!(entry(&entries, "max_calls_per_hour")["source"], ".forgerc");
+    assert_eq!(
+        entry(&entries, "engine_pre_args")["value"],
+        serde_json::json!(["--project"])
+    );
+
+    let entries = show(&["--no-user-config"]);
+    assert_eq!(entry(&entries, "timeout_minutes")["value"], 15);
+    assert_eq!(entry(&entries, "timeout_minutes")["source"], "default");
+}
+
+#[test]
+fn user_config_cannot_set_runtime_dir() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+    let user_dir = dir.path().join("xdg/forge");
+    fs::create_dir_all(&user_dir).expect("create user config dir");
+    fs::write(user_dir.join("config.toml"), "runtime_dir = \"state\"\n")
+        .expect("write user config");
+
+    forge_cmd(dir.path())
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "runtime_dir can only be set in the project .forgerc",
+        ));
+}
+
+#[test]
+fn config_validate_names_bad_env_values() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+
+    forge_cmd(dir.path())
+        .args(["config", "validate"])
+        .assert()
+        .success()
+        .stdout(contains("config ok"));
+
+    let output = forge_cmd(dir.path())
+        .env("FORGE_THINKING_MODE", "verbose")
+        .args(["config", "validate", "--json"])
+        .output()
+        .expect("config validate");
+    assert!(!output.status.success());
+    let report: Value = serde_json::from_slice(&output.stdout).expect("validate json");
+    assert_eq!(report["ok"], false);
+    assert_eq!(
+        report["problems"][0],
+        "FORGE_THINKING_MODE=verbose is not a valid thinking mode (expected off, summary or raw)"
+    );
+
+    let output = forge_cmd(dir.path())
+        .env("FORGE_TIMEOUT_MINUTES", "soon")
+        .args(["doctor", "--json"])
+        .output()
+        .expect("doctor");
+    let doctor: Value = serde_json::from_slice(&output.stdout).expect("doctor json");
+    let config_check = doctor["checks"]
+        .as_array()
+        .expect("checks")
+        .iter()
+        .find(|c| c["name"] == "config_loadable")
+        .expect("config check");
+    assert_eq!(config_check["ok"], false);
+    assert!(config_check["detail"]
+        .as_str()
+        .expect("detail")
+        .contains("FORGE_TIMEOUT_MINUTES=soon is not a valid non-negative integer"));
+}
+
+#[test]
+fn set_overrides_apply_to_run_and_doctor() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+    fs::write(dir.path().join(".forgerc"), "engine = \"codex\"\n").expect("write forgerc");
+
+    let output = forge_cmd(dir.path())
+        .args([
+            "run",
+            "--set",
+            "engine=mock",
+            "--set",
+            "mock_scenario=scenario.toml",
+            "--set",
+            "no_progress_limit=4",
+            "--max-loops",
+            "2",
+            "--json",
+        ])
+        .output()
+        .expect("run forge");
+    let run: Value = serde_json::from_slice(&output.stdout).expect("run json");
+    assert_eq!(run["reason"], "Completed");
+    assert_eq!(run["status"]["engine"], "mock");
+
+    let output = forge_cmd(dir.path())
+        .args(["doctor", "--json", "--set", "max_calls_per_hour=0"])
+        .output()
+        .expect("doctor");
+    let doctor: Value = serde_json::from_slice(&output.stdout).expect("doctor json");
+    let config_check = doctor["checks"]
+        .as_array()
+        .expect("checks")
+        .iter()
+        .find(|c| c["name"] == "config_loadable")
+        .expect("config check");
+    assert_eq!(config_check["ok"], false);
+    assert!(config_check["detail"]
+        .as_str()
+        .expect("detail")
+        .contains("max_calls_per_hour must be greater than 0 (from --set)"));
+}
+
+#[test]
+fn env_args_are_shell_split_and_forgerc_strings_interpolated() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+    let mut forgerc = fs::read_to_string(dir.path().join(".forgerc")).expect("read forgerc");
+    forgerc.push_str("engine_cmd = \"${TOOLS}/codex\"\n[engine_env]\nLEVEL = \"${LEVEL:-info}\"\n");
+    fs::write(dir.path().join(".forgerc"), forgerc).expect("write forgerc");
+
+    // The mock ignores engine_cmd, so look at it through a real engine.
+    let output = forge_cmd(dir.path())
+        .env("FORGE_ENGINE", "opencode")
+        .env("TOOLS", "/opt/tools")
+        .env_remove("LEVEL")
+        .env(
+            "FORGE_ENGINE_PRE_ARGS",
+            "--config model_reasoning_summary=\"concise\" --label 'two words'",
+        )
+        .args(["config", "show", "--json"])
+        .output()
+        .expect("config show");
+    assert!(output.status.success());
+    let entries: Value = serde_json::from_slice(&output.stdout).expect("config json");
+    assert_eq!(
+        entry(&entries, "engine_pre_args")["value"],
+        serde_json::json!([
+            "--config",
+            "model_reasoning_summary=concise",
+            "--label",
+            "two words"
+        ])
+    );
+    assert_eq!(entry(&entries, "engine_cmd")["value"], "/opt/tools/codex");
+
+    forge_cmd(dir.path())
+        .env("FORGE_ENGINE", "opencode")
+        .env_remove("TOOLS")
+        .args(["config", "show"])
+        .assert()
+        .failure()
+        .stderr(contains("engine_cmd: ${TOOLS} is not set"));
+
+    forge_cmd(dir.path())
+        .env("TOOLS", "/opt/tools")
+        .env("FORGE_ENGINE_EXEC_ARGS", "--label 'unclosed")
+        .args(["config", "validate"])
+        .assert()
+        .failure()
+        .stdout(contains("FORGE_ENGINE_EXEC_ARGS=--label 'unclosed"));
+}
+
+#[test]
+fn only_selected_config_values_are_interpolated() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+    let mut forgerc = fs::read_to_string(dir.path().join(".forgerc")).expect("read forgerc");
+    forgerc.push_str(
+        "[engine_env]\nPREFIX = \"${FORGE_TEST_EMPTY}\"\n\
+         [profile.ci]\nengine_cmd = \"${FORGE_TEST_UNDEFINED}/codex\"\n",
+    );
+    fs::write(dir.path().join(".forgerc"), forgerc).expect("write forgerc");
+
+    let output = forge_cmd(dir.path())
+        .env("FORGE_TEST_EMPTY", "")
+        .env_remove("FORGE_TEST_UNDEFINED")
+        .args(["config", "show", "--json"])
+        .output()
+        .expect("config show");
+    assert!(output.status.success());
+    let entries: Value = serde_json::from_slice(&output.stdout).expect("config json");
+    assert_eq!(entry(&entries, "engine_env")["value"]["PREFIX"], "");
+
+    forge_cmd(dir.path())
+        .env("FORGE_TEST_EMPTY", "")
+        .env("FORGE_ENGINE", "codex")
+        .env_remove("FORGE_TEST_UNDEFINED")
+        .args(["--profile", "ci", "config", "show"])
+        .assert()
+        .failure()
+        .stderr(contains(
+            "profile: engine_cmd: ${FORGE_TEST_UNDEFINED} is not set",
+        ));
+}
+
+#[test]
+fn model_and_reasoning_effort_are_recorded_per_run_and_loop() {
+    let dir = tempdir().expect("tempdir");
+    setup_mock_project(dir.path());
+
+    forge_cmd(dir.path())
+        .env("FORGE_REASONING_EFFORT", "low")
+        .args(["run", "--model", "gpt-5", "--max-loops", "2", "--json"])
+        .assert()
+        .success();
+
+    let status: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".forge/status.json")).expect("status"),
+    )
+    .expect("status json");
+    assert_eq!(status["schema_version"], 1);
+    assert_eq!(status["state"], "completed");
+    assert_eq!(status["model"], "gpt-5");
+    assert_eq!(status["reasoning_effort"], "low");
+
+    let run_id = status["run_id"].as_str().expect("run id");
+    let meta: Value = serde_json::from_str(
+        &fs::read_to_string(
+            dir.path()
+                .join(format!(".forge/iterations/{run_id}/loop-1/meta.json")),
+        )
+        .expect("meta"),
+    )
+    .expect("meta json");
+    assert_eq!(meta["loop"], 1);
+    assert_eq!(meta["engine"], "mock");
+    assert_eq!(meta["model"], "gpt-5");
+    assert_eq!(meta["reasoning_effort"], "low");
+
+    forge_cmd(dir.path())
+        .env("FORGE_REASONING_EFFORT", "maximum")
+        .args(["config", "validate"])
+        .assert()
+        .failure()
+        .stdout(contains(
+            "FORGE_REASONING_EFFORT=maximum is not a valid reasoning effort",
+        ));
+}
@@ -167,3 +167,119 @@ fn sdd_list_json_marks_current_snapshot() {
         .stdout(contains("\"id\": \"5678-refactor\""))
         .stdout(contains("\"current\": true"));
 }
+
+#[test]
+fn plan_commands_edit_active_plan() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    fs::create_dir_all(root.join(".forge")).expect("create forge dir");
+    fs::write(
+        root.join(".forge/plan.md"),
+        "# Plan\n- [ ] Task A\n- [ ] Task B\n",
+    )
+    .expect("write plan");
+    let dir_str = root.to_string_lossy().to_string();
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "plan", "add", "Task C", "--after", "1"])
+        .assert()
+        .success()
+        .stdout(contains("added item 2: Task C"));
+    forge_cmd()
+        .args(["--cwd", &dir_str, "plan", "check", "3"])
+        .assert()
+        .success();
+    forge_cmd()
+        .args(["--cwd", &dir_str, "plan", "move", "3", "--to", "1"])
+        .assert()
+        .success();
+
+    assert_eq!(
+        fs::read_to_string(root.join(".forge/plan.md")).expect("read plan"),
+        "# Plan\n- [x] Task B\n- [ ] Task A\n- [ ] Task C\n"
+    );
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "plan", "show", "--json"])
+        .assert()
+        .success()
+        .stdout(contains("\"checked_items\": 1"))
+        .stdout(contains("\"text\": \"Task C\""));
+}
+
+#[test]
+fn plan_lint_fails_on_named_sdd_with_duplicates() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    let sdd_dir = root.join(".forge/sdds/42-dup");
+    fs::create_dir_all(&sdd_dir).expect("create sdd dir");
+    fs::write(sdd_dir.join("plan.md"), "- [ ] Same\n- [ ] same\n").expect("write plan");
+    let dir_str = root.to_string_lossy().to_string();
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "plan", "--sdd", "42-dup", "lint"])
+        .assert()
+        .failure()
+        .stdout(contains("line 2: duplicate of item on line 1"))
+        .stderr(contains("plan lint found 1 issue(s)"));
+}
+
+#[test]
+fn plan_progress_reports_recorded_history() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    fs::create_dir_all(root.join(".forge")).expect("create forge dir");
+    fs::write(
+        root.join(".forge/plan_history.json"),
+        r#"{"loops_recorded":2,"items":[{"key":"task a","text":"Task A","first_seen_loop":1,"checked_in_loop":2,"loops_spent":2}],"burndown":[{"loop_number":1,"total":1,"remaining":1},{"loop_number":2,"total":1,"remaining":0}]}"#,
+    )
+    .expect("write history");
+    fs::write(root.join(".forge/status.json"), r#"{"state":"completed"}"#).expect("write status");
+    let dir_str = root.to_string_lossy().to_string();
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "plan", "progress"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "0/1 item(s) remaining after 2 loop(s), trend: converging",
+        ))
+        .stdout(contains(
+            "Task A (seen loop 1, checked in loop 2, 2 loop(s) spent)",
+        ));
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "status", "--json"])
+        .assert()
+        .success()
+        .stdout(contains("\"plan_progress\""))
+        .stdout(contains("\"trend\": \"converging\""));
+}
+
+#[test]
+fn schema_publishes_status_and_progress_formats() {
+    let output = forge_cmd().arg("schema").output().expect("schema");
+    assert!(output.status.success());
+    let schema: serde_json::Value = serde_json::from_slice(&output.stdout).expect("schema json");
+    assert_eq!(schema["title"], "RunStatus");
+    assert!(schema["properties"]["schema_version"].is_object());
+    let states = schema["$defs"]["RunState"].to_string();
+    for state in [
+        "idle",
+        "running",
+        "completed",
+        "timed_out",
+        "rate_limited",
+        "circuit_open",
+        "max_loops_reached",
+        "stale_runner",
+    ] {
+        assert!(states.contains(&format!("\"{state}\"")), "missing {state}");
+    }
+
+    forge_cmd()
+        .args(["schema", "progress"])
+        .assert()
+        .success()
+        .stdout(contains("\"title\": \"ProgressSnapshot\""));
+}
@@ -0,0 +1,129 @@
+use assert_cmd::Command;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn forge_cmd() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_forge"))
+}
+
+fn setup_project(root: &Path, scenario: &str) {
+    fs::create_dir_all(root.join(".forge")).expect("create runtime dir");
+    fs::write(root.join(".forge/plan.md"), "- [ ] ship it\n").expect("write plan");
+    fs::write(root.join("scenario.toml"), scenario).expect("write scenario");
+    fs::write(
+        root.join(".forgerc"),
+        "engine = \"mock\"\nmock_scenario = \"scenario.toml\"\nno_progress_limit = 2\n",
+    )
+    .expect("write forgerc");
+}
+
+fn run_json(root: &Path) -> (Option<i32>, Value) {
+    let output = forge_cmd()
+        .args([
+            "--cwd",
+            root.to_string_lossy().as_ref(),
+            "run",
+            "--engine",
+            "mock",
+            "--max-loops",
+            "5",
+            "--json",
+        ])
+        .output()
+        .expect("run forge");
+    let json = serde_json::from_slice(&output.stdout).expect("run json");
+    (output.status.code(), json)
+}
+
+#[test]
+fn mock_scenario_drives_run_to_completion() {
+    let dir = tempdir().expect("tempdir");
+    setup_project(
+        dir.path(),
+        r#"
+[[loops]]
+session_id = "mock-thread-1"
+file_changes = ["src/lib.rs"]
+agent_message = "edited the parser"
+
+[[loops.commands]]
+command = "cargo test"
+output = "1 failed"
+exit_code = 101
+
+[[loops]]
+agent_message = "all green\nSTATUS: COMPLETE\nEXIT_SIGNAL: true"
+usage = { input_tokens = 1200, cached_input_tokens = 200, output_tokens = 80 }
+"#,
+    );
+
+    let (code, json) = run_json(dir.path());
+
+    assert_eq!(code, Some(0));
+    assert_eq!(json["reason"], "Completed");
+    assert_eq!(json["loops_executed"], 2);
+    assert_eq!(json["status"]["engine"], "mock");
+    assert_eq!(json["status"]["session_id"], "mock-thread-1");
+    assert_eq!(json["status"]["last_token_usage"]["input_tokens"], 1200);
+
+    let progress: Value = serde_json::from_str(
+        &fs::read_to_string(dir.path().join(".forge/progress.json")).expect("progress"),
+    )
+    .expect("progress json");
+    assert_eq!(
+        progress["carry_over"]["failed_commands"][0]["command"],
+        "cargo test"
+    );
+    let live = fs::read_to_string(dir.path().join(".forge/live.log")).expect("live log");
+    assert!(live.contains("loop 1: mock exec started"));
+    assert!(live.contains("\"thread_id\":\"mock-thread-1\""));
+    let iterations = dir
+        .path()
+        .join(".forge/iterations")
+        .join(json["status"]["run_id"].as_str().expect("run id"));
+    let first_stdout =
+        fs::read_to_string(iterations.join("loop-1/stdout.log")).expect("loop 1 stdout");
+    assert!(first_stdout.contains("\"command\":\"cargo test\""));
+    assert!(iterations.join("loop-2/stderr.log").exists());
+}
+
+#[test]
+fn mock_scenario_without_progress_opens_the_circuit() {
+    let dir = tempdir().expect("tempdir");
+    setup_project(
+        dir.path(),
+        r#"
+[[loops]]
+exit_code = 1
+
+[[loops.steps]]
+stderr = "model overloaded"
+"#,
+    );
+
+    let (code, json) = run_json(dir.path());
+
+    assert_eq!(code, Some(2));
+    assert_eq!(json["reason"], "CircuitOpened");
+    assert_eq!(json["loops_executed"], 2);
+}
+
+#[test]
+fn mock_ignores_engine_cmd_meant_for_the_real_engine() {
+    let dir = tempdir().expect("tempdir");
+    setup_project(
+        dir.path(),
+        "[[loops]]\nagent_message = \"STATUS: COMPLETE\\nEXIT_SIGNAL: true\"\n",
+    );
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine = \"codex\"\nengine_cmd = \"/nonexistent/codex\"\nmock_scenario = \"scenario.toml\"\n",
+    )
+    .expect("write forgerc");
+
+    let (code, run) = run_json(dir.path());
+    assert_eq!(code, Some(0));
+    assert_eq!(run["reason"], "Completed");
+}
@@ -0,0 +1,129 @@
+use assert_cmd::Command;
+use predicates::str::contains;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn forge_cmd() -> Command {
+    Command::new(env!("CARGO_BIN_EXE_forge"))
+}
+
+fn setup_fake_codex(script: &Path) {
+    let body = r#"#!/usr/bin/env bash
+set -euo pipefail
+if grep -q "never done" .forge/plan.md; then
+  msg="still working"
+else
+  msg="done. EXIT_SIGNAL: true STATUS: COMPLETE"
+fi
+printf '{"type":"item.completed","item":{"type":"agent_message","text":"%s"}}\n' "$msg"
+"#;
+    fs::write(script, body).expect("write fake codex");
+    Command::new("chmod")
+        .args(["+x", script.to_string_lossy().as_ref()])
+        .assert()
+        .success();
+}
+
+fn write_sdd(root: &Path, id: &str, plan: &str) {
+    let dir = root.join(".forge/sdds").join(id);
+    fs::create_dir_all(&dir).expect("create sdd dir");
+    fs::write(dir.join("plan.md"), plan).expect("write plan");
+    fs::write(dir.join("spec.md"), "# spec").expect("write spec");
+    fs::write(dir.join("acceptance.md"), "# acceptance").expect("write acceptance");
+    fs::write(dir.join("scenarios.md"), "# scenarios").expect("write scenarios");
+}
+
+fn queue_json(root: &Path) -> Value {
+    let raw = fs::read_to_string(root.join(".forge/queue.json")).expect("read queue");
+    serde_json::from_str(&raw).expect("parse queue")
+}
+
+#[test]
+fn queue_run_applies_stop_and_continue_policies() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    let dir_str = root.to_string_lossy().to_string();
+    write_sdd(root, "01-stuck", "- [ ] never done\n");
+    write_sdd(root, "02-easy", "- [ ] quick win\n");
+    let fake_codex = root.join("fake-codex.sh");
+    setup_fake_codex(&fake_codex);
+
+    forge_cmd()
+        .args([
+            "--cwd",
+            &dir_str,
+            "queue",
+            "add",
+            "01-stuck",
+            "--max-loops",
+            "1",
+        ])
+        .assert()
+        .success();
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "add", "02-easy"])
+        .assert()
+        .success();
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "run"])
+        .env("FORGE_ENGINE_CMD", fake_codex.to_string_lossy().as_ref())
+        .assert()
+        .failure()
+        .stderr(contains("queue finished with 1 failed sdd(s)"));
+
+    let queue = queue_json(root);
+    assert_eq!(queue["entries"][0]["state"], "failed");
+    assert_eq!(queue["entries"][0]["exit_reason"], "max_loops_reached");
+    assert_eq!(queue["entries"][1]["state"], "pending");
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "run", "--policy", "continue"])
+        .env("FORGE_ENGINE_CMD", fake_codex.to_string_lossy().as_ref())
+        .assert()
+        .success()
+        .stdout(contains("queue: ran 1, failed 0, remaining 0"));
+
+    let queue = queue_json(root);
+    assert_eq!(queue["entries"][1]["state"], "completed");
+    assert_eq!(queue["entries"][1]["exit_reason"], "completed");
+    assert_eq!(
+        fs::read_to_string(root.join(".forge/current_sdd")).expect("current sdd"),
+        "02-easy"
+    );
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "list"])
+        .assert()
+        .success()
+        .stdout(contains(
+            "01-stuck [failed] max_loops=1 reason=max_loops_reached loops=1",
+        ))
+        .stdout(contains("02-easy [completed] reason=completed loops=1"));
+}
+
+#[test]
+fn queue_add_rejects_unknown_and_duplicate_sdds() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    let dir_str = root.to_string_lossy().to_string();
+    write_sdd(root, "01-a", "- [ ] a\n");
+
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "add", "99-missing"])
+        .assert()
+        .failure()
+        .stderr(contains("sdd id not found: 99-missing"));
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "add", "01-a"])
+        .assert()
+        .success()
+        .stdout(contains("queued 01-a at position 1"));
+    forge_cmd()
+        .args(["--cwd", &dir_str, "queue", "add", "01-a"])
+        .assert()
+        .failure()
+        .stderr(contains("sdd 01-a is already queued"));
+}
@@ -0,0 +1,133 @@
+use assert_cmd::Command;
+use predicates::str::contains;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+use tempfile::tempdir;
+
+fn forge_cmd(root: &Path) -> Command {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_forge"));
+    cmd.env_remove("FORGE_RUNTIME_DIR")
+        .args(["--cwd", root.to_string_lossy().as_ref()]);
+    cmd
+}
+
+fn write_sdd(runtime_dir: &Path, id: &str, plan: &str) {
+    let dir = runtime_dir.join("sdds").join(id);
+    fs::create_dir_all(&dir).expect("create sdd dir");
+    fs::write(dir.join("plan.md"), plan).expect("write plan");
+    fs::write(dir.join("spec.md"), "# spec").expect("write spec");
+    fs::write(dir.join("acceptance.md"), "# acceptance").expect("write acceptance");
+    fs::write(dir.join("scenarios.md"), "# scenarios").expect("write scenarios");
+}
+
+fn stdout_json(cmd: &mut Command) -> Value {
+    let output = cmd.output().expect("run forge");
+    serde_json::from_slice(&output.stdout).expect("json output")
+}
+
+#[test]
+fn configured_runtime_dir_holds_all_state() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    let state = root.join("state");
+    fs::write(
+        root.join(".forgerc"),
+        "engine = \"mock\"\nmock_scenario = \"scenario.toml\"\nruntime_dir = \"state\"\n",
+    )
+    .expect("write forgerc");
+    fs::write(
+        root.join("scenario.toml"),
+        "[[loops]]\nsession_id = \"mock-thread-1\"\nagent_message = \"STATUS: COMPLETE\\nEXIT_SIGNAL: true\"\n",
+    )
+    .expect("write scenario");
+    write_sdd(&state, "01-custom", "- [ ] ship it\n");
+
+    forge_cmd(root)
+        .args(["sdd", "load", "01-custom"])
+        .assert()
+        .success();
+    assert_eq!(
+        fs::read_to_string(state.join("plan.md")).expect("active plan"),
+        "- [ ] ship it\n"
+    );
+    assert_eq!(
+        fs::read_to_string(state.join("current_sdd")).expect("current sdd"),
+        "01-custom"
+    );
+    let list = stdout_json(forge_cmd(root).args(["sdd", "list", "--json"]));
+    assert_eq!(list[0]["id"], "01-custom");
+    assert_eq!(list[0]["current"], true);
+
+    forge_cmd(root)
+        .args(["plan", "add", "write docs"])
+        .assert()
+        .success();
+    assert!(fs::read_to_string(state.join("plan.md"))
+        .expect("plan")
+        .contains("write docs"));
+
+    let run = stdout_json(forge_cmd(root).args([
+        "run",
+        "--engine",
+        "mock",
+        "--max-loops",
+        "2",
+        "--json",
+    ]));
+    assert_eq!(run["reason"], "Completed");
+    let run_id = run["status"]["run_id"].as_str().expect("run id");
+    for file in [
+        "status.json".to_string(),
+        "progress.json".to_string(),
+        "live.log".to_string(),
+        "plan_history.json".to_string(),
+        format!("iterations/{run_id}/loop-1/stdout.log"),
+    ] {
+        assert!(state.join(&file).exists(), "state/{file} missing");
+    }
+
+    let status = stdout_json(forge_cmd(root).args(["status", "--json"]));
+    assert_eq!(status["status"]["state"], "completed");
+    assert_eq!(status["status"]["session_id"], "mock-thread-1");
+    forge_cmd(root)
+        .args(["plan", "progress"])
+        .assert()
+        .success();
+
+    let doctor = stdout_json(forge_cmd(root).args(["doctor", "--json"]));
+    assert!(doctor["warnings"]
+        .as_array()
+        .expect("warnings")
+        .iter()
+        .any(|w| w == "no persisted analyze report yet (state/analyze/latest.json)"));
+
+    forge_cmd(root)
+        .args(["run", "--engine", "mock", "--fresh", "--max-loops", "1"])
+        .assert()
+        .code(0);
+    assert!(!root.join(".forge").exists());
+}
+
+#[test]
+fn runtime_dir_env_overrides_forgerc() {
+    let dir = tempdir().expect("tempdir");
+    let root = dir.path();
+    fs::write(root.join(".forgerc"), "runtime_dir = \"state\"\n").expect("write forgerc");
+    write_sdd(&root.join("from-env"), "01-env", "- [ ] env plan\n");
+
+    forge_cmd(root)
+        .env("FORGE_RUNTIME_DIR", "from-env")
+        .args(["sdd", "load", "01-env"])
+        .assert()
+        .success();
+    assert!(root.join("from-env/plan.md").exists());
+
+    forge_cmd(root)
+        .args(["sdd", "load", "01-env"])
+        .assert()
+        .failure()
+        .stderr(contains("sdd id not found"));
+    assert!(!root.join("state").exists());
+    assert!(!root.join(".forge").exists());
+}
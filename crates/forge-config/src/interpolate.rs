@@ -0,0 +1,144 @@
+use crate::{ConfigSource, ConfigSources};
+use anyhow::{anyhow, Result};
+use std::env;
+
+/// Expands `${VAR}`, `${VAR:-default}` and `${VAR:?message}` in a value
+/// that came from a config file. Values from flags, `--set`, the
+/// environment or defaults are returned unchanged, as are values nobody
+/// selected: unused profiles and engine tables are never expanded.
+///
+/// `key` may name a table entry (`engine_env.LEVEL`); the source is that of
+/// the table.
+pub(crate) fn expand_file_value(
+    sources: &ConfigSources,
+    key: &str,
+    value: String,
+) -> Result<String> {
+    let table = key.split_once('.').map_or(key, |(table, _)| table);
+    let source = sources.get(table);
+    match source {
+        ConfigSource::Profile | ConfigSource::Forgerc | ConfigSource::User => {
+            interpolate(&value, &|name| env::var(name).ok())
+                .map_err(|err| anyhow!("{source}: {key}: {err}"))
+        }
+        _ => Ok(value),
+    }
+}
+
+/// `$${` is a literal `${`. A `$` not followed by `{` is kept as is.
+fn interpolate(raw: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String, String> {
+    let mut out = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('$') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        if let Some(escaped) = after.strip_prefix("${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let Some(body) = after.strip_prefix('{') else {
+            out.push('$');
+            rest = after;
+            continue;
+        };
+        let Some(end) = body.find('}') else {
+            return Err(format!("unterminated ${{ in \"{raw}\""));
+        };
+        let reference = &body[..end];
+        let (name, operator) = match reference.find(":-").or_else(|| reference.find(":?")) {
+            Some(at) => (&reference[..at], Some(&reference[at..])),
+            None => (reference, None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c == '_' || c.is_ascii_alphanumeric()) {
+            return Err(format!("invalid variable name \"{name}\" in \"{raw}\""));
+        }
+        let value = lookup(name);
+        match operator {
+            // Plain `${VAR}` accepts an empty value; only the `:` forms treat
+            // empty as unset.
+            None => match value {
+                Some(value) => out.push_str(&value),
+                None => return Err(format!("${{{name}}} is not set")),
+            },
+            Some(operator) => match value.filter(|value| !value.is_empty()) {
+                Some(value) => out.push_str(&value),
+                None if operator.starts_with(":-") => out.push_str(&operator[2..]),
+                None if operator.len() > 2 => {
+                    return Err(format!("${{{name}}}: {}", &operator[2..]))
+                }
+                None => return Err(format!("${{{name}}} is not set or empty")),
+            },
+        }
+        rest = &body[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/dev".to_string()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_variables_and_defaults() {
+        assert_eq!(
+            interpolate("${HOME}/bin/codex", &lookup).expect("interpolate"),
+            "/home/dev/bin/codex"
+        );
+        assert_eq!(
+            interpolate("${STATE_DIR:-.forge}", &lookup).expect("interpolate"),
+            ".forge"
+        );
+        assert_eq!(
+            interpolate("${EMPTY:-fallback}", &lookup).expect("interpolate"),
+            "fallback"
+        );
+        assert_eq!(
+            interpolate("${HOME:-x}", &lookup).expect("interpolate"),
+            "/home/dev"
+        );
+        assert_eq!(
+            interpolate("cost $5, $${HOME}", &lookup).expect("interpolate"),
+            "cost $5, ${HOME}"
+        );
+    }
+
+    #[test]
+    fn empty_is_only_unset_for_the_colon_forms() {
+        assert_eq!(
+            interpolate("[${EMPTY}]", &lookup).expect("interpolate"),
+            "[]"
+        );
+        assert_eq!(
+            interpolate("${EMPTY:?set a token}", &lookup).expect_err("error"),
+            "${EMPTY}: set a token"
+        );
+        assert_eq!(
+            interpolate("${HOME:?unused}", &lookup).expect("interpolate"),
+            "/home/dev"
+        );
+    }
+
+    #[test]
+    fn rejects_undefined_and_malformed_references() {
+        assert_eq!(
+            interpolate("${MISSING}/x", &lookup).expect_err("error"),
+            "${MISSING} is not set"
+        );
+        assert!(interpolate("${HOME", &lookup)
+            .expect_err("error")
+            .starts_with("unterminated"));
+        assert!(interpolate("${NOT-A-NAME}", &lookup)
+            .expect_err("error")
+            .starts_with("invalid variable name"));
+    }
+}
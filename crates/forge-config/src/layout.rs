@@ -0,0 +1,176 @@
+use crate::{read_forgerc, resolve_runtime_dir, ConfigSources, RunConfig};
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+
+pub const DEFAULT_RUNTIME_DIR: &str = ".forge";
+
+const STATUS_FILE: &str = "status.json";
+const PROGRESS_FILE: &str = "progress.json";
+const LIVE_LOG_FILE: &str = "live.log";
+/// Compressed `live.log` segments, `live-<timestamp>.log.gz`.
+const LOG_ARCHIVE_DIR: &str = "logs";
+const SESSION_ID_FILE: &str = ".session_id";
+const CALL_COUNT_FILE: &str = ".call_count";
+const LAST_RESET_FILE: &str = ".last_reset";
+const CIRCUIT_BREAKER_STATE_FILE: &str = ".circuit_breaker_state";
+const CIRCUIT_BREAKER_HISTORY_FILE: &str = ".circuit_breaker_history";
+const RUNNER_PID_FILE: &str = ".runner_pid";
+const PLAN_FILE: &str = "plan.md";
+const PLAN_HISTORY_FILE: &str = "plan_history.json";
+const QUEUE_FILE: &str = "queue.json";
+const ENGINE_PROMPT_FILE: &str = "engine_prompt.md";
+/// Engine stdout/stderr per loop of the current run, cleared when a run starts.
+const ITERATIONS_DIR: &str = "iterations";
+const ITERATION_META_FILE: &str = "meta.json";
+const ANALYZE_DIR: &str = "analyze";
+const SDDS_DIR: &str = "sdds";
+const CURRENT_SDD_FILE: &str = "current_sdd";
+
+/// Every path forge reads or writes under the runtime directory. Build it
+/// once from the resolved config so a custom `runtime_dir` applies to all
+/// state, not just some of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeLayout {
+    root: PathBuf,
+}
+
+impl RuntimeLayout {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `runtime_dir` from the config, resolved against `cwd`.
+    pub fn from_config(cwd: &Path, config: &RunConfig) -> Self {
+        Self::new(cwd.join(&config.runtime_dir))
+    }
+
+    /// `runtime_dir` from `FORGE_RUNTIME_DIR` or `.forgerc`, for commands
+    /// that do not need the rest of the run config.
+    pub fn discover(cwd: &Path) -> Result<Self> {
+        let runtime_dir = resolve_runtime_dir(
+            None,
+            read_forgerc(cwd)?.runtime_dir,
+            &mut ConfigSources::default(),
+        )?;
+        Ok(Self::new(cwd.join(runtime_dir)))
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// `path` relative to `cwd` when it lies inside it, for messages and
+    /// prompts.
+    pub fn display(&self, cwd: &Path, path: &Path) -> String {
+        path.strip_prefix(cwd).unwrap_or(path).display().to_string()
+    }
+
+    pub fn status_file(&self) -> PathBuf {
+        self.root.join(STATUS_FILE)
+    }
+
+    pub fn progress_file(&self) -> PathBuf {
+        self.root.join(PROGRESS_FILE)
+    }
+
+    pub fn live_log(&self) -> PathBuf {
+        self.root.join(LIVE_LOG_FILE)
+    }
+
+    pub fn log_archive_dir(&self) -> PathBuf {
+        self.root.join(LOG_ARCHIVE_DIR)
+    }
+
+    pub fn session_id_file(&self) -> PathBuf {
+        self.root.join(SESSION_ID_FILE)
+    }
+
+    pub fn call_count_file(&self) -> PathBuf {
+        self.root.join(CALL_COUNT_FILE)
+    }
+
+    pub fn last_reset_file(&self) -> PathBuf {
+        self.root.join(LAST_RESET_FILE)
+    }
+
+    pub fn circuit_breaker_state_file(&self) -> PathBuf {
+        self.root.join(CIRCUIT_BREAKER_STATE_FILE)
+    }
+
+    pub fn circuit_breaker_history_file(&self) -> PathBuf {
+        self.root.join(CIRCUIT_BREAKER_HISTORY_FILE)
+    }
+
+    pub fn runner_pid_file(&self) -> PathBuf {
+        self.root.join(RUNNER_PID_FILE)
+    }
+
+    pub fn plan_file(&self) -> PathBuf {
+        self.root.join(PLAN_FILE)
+    }
+
+    pub fn plan_history_file(&self) -> PathBuf {
+        self.root.join(PLAN_HISTORY_FILE)
+    }
+
+    pub fn queue_file(&self) -> PathBuf {
+        self.root.join(QUEUE_FILE)
+    }
+
+    /// Where the prompt is written for engines that read it from a file.
+    pub fn engine_prompt_file(&self) -> PathBuf {
+        self.root.join(ENGINE_PROMPT_FILE)
+    }
+
+    pub fn iterations_dir(&self) -> PathBuf {
+        self.root.join(ITERATIONS_DIR)
+    }
+
+    /// `iterations/<run_id>/loop-<n>`, holding `stdout.log`, `stderr.log`
+    /// and `meta.json`. Loop numbers restart with every run, so each run
+    /// keeps its own directory.
+    pub fn iteration_dir(&self, run_id: &str, loop_number: u64) -> PathBuf {
+        self.iterations_dir()
+            .join(run_id)
+            .join(format!("loop-{loop_number}"))
+    }
+
+    pub fn iteration_meta_file(&self, run_id: &str, loop_number: u64) -> PathBuf {
+        self.iteration_dir(run_id, loop_number)
+            .join(ITERATION_META_FILE)
+    }
+
+    pub fn analyze_dir(&self) -> PathBuf {
+        self.root.join(ANALYZE_DIR)
+    }
+
+    pub fn analyze_latest_file(&self) -> PathBuf {
+        self.analyze_dir().join("latest.json")
+    }
+
+    pub fn sdds_dir(&self) -> PathBuf {
+        self.root.join(SDDS_DIR)
+    }
+
+    pub fn sdd_dir(&self, id: &str) -> PathBuf {
+        self.sdds_dir().join(id)
+    }
+
+    pub fn current_sdd_file(&self) -> PathBuf {
+        self.root.join(CURRENT_SDD_FILE)
+    }
+
+    /// Per-run state removed by `forge run --fresh`.
+    pub fn fresh_run_files(&self) -> Vec<PathBuf> {
+        vec![
+            self.status_file(),
+            self.progress_file(),
+            self.live_log(),
+            self.session_id_file(),
+            self.call_count_file(),
+            self.last_reset_file(),
+            self.circuit_breaker_state_file(),
+            self.circuit_breaker_history_file(),
+        ]
+    }
+}
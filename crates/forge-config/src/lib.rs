@@ -1,9 +1,22 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use serde::Deserialize;
+use serde_json::json;
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
 use std::path::{Path, PathBuf};
 
+mod interpolate;
+mod layout;
+mod source;
+mod validate;
+
+pub use layout::{RuntimeLayout, DEFAULT_RUNTIME_DIR};
+pub use source::{ConfigEntry, ConfigSource, ConfigSources};
+
+use interpolate::expand_file_value;
+use validate::{out_of_range, parse_engine, unknown_keys};
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ResumeMode {
     New,
@@ -58,12 +71,46 @@ impl ThinkingMode {
     }
 }
 
+/// How hard the model thinks before answering. Each engine maps it to its
+/// own flag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ReasoningEffort::Minimal => "minimal",
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+
+    pub fn from_str_effort(s: &str) -> Option<Self> {
+        match s.to_ascii_lowercase().as_str() {
+            "minimal" => Some(ReasoningEffort::Minimal),
+            "low" => Some(ReasoningEffort::Low),
+            "medium" => Some(ReasoningEffort::Medium),
+            "high" => Some(ReasoningEffort::High),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
 pub enum EngineKind {
     #[default]
     Codex,
     OpenCode,
+    Custom,
+    Mock,
 }
 
 impl EngineKind {
@@ -71,13 +118,28 @@ impl EngineKind {
         match self {
             EngineKind::Codex => "codex",
             EngineKind::OpenCode => "opencode",
+            EngineKind::Custom => "custom",
+            EngineKind::Mock => "mock",
         }
     }
 
-    pub fn default_cmd(self) -> &'static str {
+    /// Program run when no `engine_cmd` is configured. Custom engines have
+    /// none; theirs comes from `[custom_engine] command` or `engine_cmd`.
+    pub fn default_cmd(self) -> Option<&'static str> {
         match self {
-            EngineKind::Codex => "codex",
-            EngineKind::OpenCode => "opencode",
+            EngineKind::Codex => Some("codex"),
+            EngineKind::OpenCode => Some("opencode"),
+            EngineKind::Custom => None,
+            EngineKind::Mock => Some("sh"),
+        }
+    }
+
+    /// Arguments a thinking mode adds when `[engines.<name>] thinking_args`
+    /// does not say otherwise. Only codex has built-in flags for it.
+    pub fn default_thinking_args(self, mode: ThinkingMode) -> Vec<String> {
+        match self {
+            EngineKind::Codex => mode.codex_config_args(),
+            EngineKind::OpenCode | EngineKind::Custom | EngineKind::Mock => Vec::new(),
         }
     }
 
@@ -85,11 +147,76 @@ impl EngineKind {
         match s.to_ascii_lowercase().as_str() {
             "codex" => Some(EngineKind::Codex),
             "opencode" => Some(EngineKind::OpenCode),
+            "custom" => Some(EngineKind::Custom),
+            "mock" => Some(EngineKind::Mock),
             _ => None,
         }
     }
 }
 
+/// How the prompt reaches the engine process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PromptDelivery {
+    #[default]
+    Argv,
+    Stdin,
+    File,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    #[default]
+    Plain,
+    Jsonl,
+}
+
+/// `[custom_engine]` table used when `engine = "custom"`.
+///
+/// `args` is an argv template. `{prompt}`, `{prompt_file}` and `{session_id}`
+/// are substituted inside arguments; an argument that is exactly `{resume}` or
+/// `{exec_args}` is replaced by the resume arguments or `engine_exec_args`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomEngineConfig {
+    #[serde(default = "default_custom_engine_name")]
+    pub name: String,
+    pub command: Option<String>,
+    pub args: Vec<String>,
+    /// Spliced at `{resume}` when resuming a specific session.
+    #[serde(default)]
+    pub resume_args: Vec<String>,
+    /// Spliced at `{resume}` for `--resume-last`.
+    #[serde(default)]
+    pub resume_last_args: Vec<String>,
+    /// Appended after `engine_pre_args` when a model is set; `{model}` is
+    /// substituted.
+    #[serde(default)]
+    pub model_args: Vec<String>,
+    /// Appended after `engine_pre_args` when a reasoning effort is set;
+    /// `{reasoning_effort}` is substituted.
+    #[serde(default)]
+    pub reasoning_effort_args: Vec<String>,
+    #[serde(default)]
+    pub prompt_delivery: PromptDelivery,
+    #[serde(default)]
+    pub output_format: OutputFormat,
+    /// JSON keys searched for a session id in `jsonl` output.
+    #[serde(default)]
+    pub session_id_fields: Vec<String>,
+    /// Text markers in `plain` output; the word after the marker is the session id.
+    #[serde(default)]
+    pub session_id_markers: Vec<String>,
+    /// Command run by availability checks; it must exit successfully.
+    #[serde(default)]
+    pub probe: Vec<String>,
+}
+
+fn default_custom_engine_name() -> String {
+    "custom".to_string()
+}
+
 #[derive(Debug, Clone)]
 pub struct RunConfig {
     pub engine: EngineKind,
@@ -97,6 +224,20 @@ pub struct RunConfig {
     pub engine_pre_args: Vec<String>,
     pub engine_exec_args: Vec<String>,
     pub thinking_mode: ThinkingMode,
+    /// Model passed to the engine in its own flag syntax; `None` keeps the
+    /// engine's default.
+    pub model: Option<String>,
+    /// Reasoning effort passed to the engine; `None` keeps the engine's
+    /// default.
+    pub reasoning_effort: Option<ReasoningEffort>,
+    /// `analyze_model` as configured; `forge analyze` already folds it into
+    /// `model`.
+    pub analyze_model: Option<String>,
+    /// `analyze_reasoning_effort` as configured; folded into
+    /// `reasoning_effort` for `forge analyze`.
+    pub analyze_reasoning_effort: Option<ReasoningEffort>,
+    /// Arguments the thinking mode added to the end of `engine_pre_args`.
+    pub thinking_args: Vec<String>,
     pub max_calls_per_hour: u32,
     pub timeout_minutes: u64,
     pub runtime_dir: PathBuf,
@@ -105,6 +246,103 @@ pub struct RunConfig {
     pub sleep_on_rate_limit_secs: u64,
     pub no_progress_limit: u32,
     pub resume_mode: ResumeMode,
+    pub spec_context_budget_chars: usize,
+    pub spec_context_sets: Vec<String>,
+    pub custom_engine: Option<CustomEngineConfig>,
+    /// Scenario file replayed by `engine = "mock"`, resolved against the cwd.
+    pub mock_scenario: Option<PathBuf>,
+    /// Variables set on the engine process, after filtering.
+    pub engine_env: BTreeMap<String, String>,
+    /// When non-empty, only these inherited variables reach the engine.
+    /// A trailing `*` matches a prefix.
+    pub engine_env_allowlist: Vec<String>,
+    /// Inherited variables never passed to the engine.
+    pub engine_env_denylist: Vec<String>,
+    /// Start the engine from an empty environment; only allowlisted
+    /// variables are inherited.
+    pub engine_env_clear: bool,
+    /// `live.log` is archived when a run starts or a loop begins once it
+    /// reaches this size. 0 disables size-based rotation.
+    pub live_log_max_bytes: u64,
+    /// ... or once its oldest entry is this old. 0 disables age-based
+    /// rotation.
+    pub live_log_max_age_secs: u64,
+    /// Compressed archives kept under `<runtime>/logs`.
+    pub live_log_retention: usize,
+    /// Active `.forgerc` profile, if any.
+    pub profile: Option<String>,
+    /// Which layer each value above came from.
+    pub sources: ConfigSources,
+}
+
+impl RunConfig {
+    /// Every effective value with the layer it came from, in `.forgerc`
+    /// key order.
+    pub fn entries(&self) -> Vec<ConfigEntry> {
+        let resume = match &self.resume_mode {
+            ResumeMode::New => json!(null),
+            ResumeMode::Last => json!("last"),
+            ResumeMode::Explicit(id) => json!(id),
+        };
+        let values = [
+            ("profile", json!(self.profile)),
+            ("engine", json!(self.engine.as_str())),
+            ("engine_cmd", json!(self.engine_cmd)),
+            ("engine_pre_args", json!(self.engine_pre_args)),
+            ("engine_exec_args", json!(self.engine_exec_args)),
+            ("thinking_mode", json!(self.thinking_mode.as_str())),
+            ("thinking_args", json!(self.thinking_args)),
+            ("model", json!(self.model)),
+            (
+                "reasoning_effort",
+                json!(self.reasoning_effort.map(ReasoningEffort::as_str)),
+            ),
+            ("analyze_model", json!(self.analyze_model)),
+            (
+                "analyze_reasoning_effort",
+                json!(self.analyze_reasoning_effort.map(ReasoningEffort::as_str)),
+            ),
+            ("max_calls_per_hour", json!(self.max_calls_per_hour)),
+            ("timeout_minutes", json!(self.timeout_minutes)),
+            ("runtime_dir", json!(self.runtime_dir)),
+            ("completion_indicators", json!(self.completion_indicators)),
+            (
+                "auto_wait_on_rate_limit",
+                json!(self.auto_wait_on_rate_limit),
+            ),
+            (
+                "sleep_on_rate_limit_secs",
+                json!(self.sleep_on_rate_limit_secs),
+            ),
+            ("no_progress_limit", json!(self.no_progress_limit)),
+            ("resume", resume),
+            (
+                "spec_context_budget_chars",
+                json!(self.spec_context_budget_chars),
+            ),
+            ("spec_context_sets", json!(self.spec_context_sets)),
+            (
+                "custom_engine",
+                json!(self.custom_engine.as_ref().map(|custom| &custom.name)),
+            ),
+            ("mock_scenario", json!(self.mock_scenario)),
+            ("engine_env", json!(self.engine_env)),
+            ("engine_env_allowlist", json!(self.engine_env_allowlist)),
+            ("engine_env_denylist", json!(self.engine_env_denylist)),
+            ("engine_env_clear", json!(self.engine_env_clear)),
+            ("live_log_max_bytes", json!(self.live_log_max_bytes)),
+            ("live_log_max_age_secs", json!(self.live_log_max_age_secs)),
+            ("live_log_retention", json!(self.live_log_retention)),
+        ];
+        values
+            .into_iter()
+            .map(|(key, value)| ConfigEntry {
+                key,
+                value,
+                source: self.sources.get(key),
+            })
+            .collect()
+    }
 }
 
 #[derive(Debug, Clone, Default)]
@@ -113,19 +351,36 @@ pub struct CliOverrides {
     pub engine_pre_args: Option<Vec<String>>,
     pub engine_exec_args: Option<Vec<String>>,
     pub thinking_mode: Option<ThinkingMode>,
+    pub model: Option<String>,
+    pub reasoning_effort: Option<ReasoningEffort>,
     pub max_calls_per_hour: Option<u32>,
     pub timeout_minutes: Option<u64>,
     pub resume: Option<String>,
     pub resume_last: bool,
+    /// `--profile`; selects a `[profile.<name>]` table from `.forgerc`.
+    pub profile: Option<String>,
+    /// `--no-user-config`; skip the user-global config file.
+    pub no_user_config: bool,
+    /// `--set KEY=VALUE` entries, checked against the `.forgerc` keys.
+    pub set: Vec<String>,
+    /// Resolve for `forge analyze`: `analyze_model` and
+    /// `analyze_reasoning_effort` win over `model` and `reasoning_effort`
+    /// within each layer.
+    pub analyze: bool,
 }
 
 #[derive(Debug, Deserialize, Default)]
+#[serde(deny_unknown_fields)]
 struct Forgerc {
     engine: Option<String>,
     engine_cmd: Option<String>,
     engine_pre_args: Option<Vec<String>>,
     engine_exec_args: Option<Vec<String>>,
     thinking_mode: Option<ThinkingMode>,
+    model: Option<String>,
+    reasoning_effort: Option<ReasoningEffort>,
+    analyze_model: Option<String>,
+    analyze_reasoning_effort: Option<ReasoningEffort>,
     max_calls_per_hour: Option<u32>,
     timeout_minutes: Option<u64>,
     runtime_dir: Option<String>,
@@ -133,17 +388,273 @@ struct Forgerc {
     auto_wait_on_rate_limit: Option<bool>,
     sleep_on_rate_limit_secs: Option<u64>,
     no_progress_limit: Option<u32>,
+    spec_context_budget_chars: Option<usize>,
+    spec_context_sets: Option<Vec<String>>,
+    custom_engine: Option<CustomEngineConfig>,
+    mock_scenario: Option<String>,
+    engine_env: Option<BTreeMap<String, String>>,
+    engine_env_allowlist: Option<Vec<String>>,
+    engine_env_denylist: Option<Vec<String>>,
+    engine_env_clear: Option<bool>,
+    live_log_max_bytes: Option<u64>,
+    live_log_max_age_secs: Option<u64>,
+    live_log_retention: Option<usize>,
+    engines: Option<EnginesTable>,
+    /// `[profile.<name>]` tables; the selected one overlays the fields above.
+    #[serde(default)]
+    profile: BTreeMap<String, Forgerc>,
+}
+
+/// `[engines.codex]` and `[engines.opencode]`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EnginesTable {
+    codex: Option<EngineSection>,
+    opencode: Option<EngineSection>,
+}
+
+/// Settings that only apply while their engine is selected. Within one
+/// config file they take precedence over the engine-neutral keys.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct EngineSection {
+    cmd: Option<String>,
+    pre_args: Option<Vec<String>>,
+    exec_args: Option<Vec<String>>,
+    model: Option<String>,
+    reasoning_effort: Option<ReasoningEffort>,
+    env: Option<BTreeMap<String, String>>,
+    thinking_args: Option<ThinkingArgs>,
+}
+
+/// Arguments added to `engine_pre_args` for each thinking mode.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ThinkingArgs {
+    off: Option<Vec<String>>,
+    summary: Option<Vec<String>>,
+    raw: Option<Vec<String>>,
+}
+
+impl ThinkingArgs {
+    fn for_mode(&self, mode: ThinkingMode) -> Option<Vec<String>> {
+        match mode {
+            ThinkingMode::Off => self.off.clone(),
+            ThinkingMode::Summary => self.summary.clone(),
+            ThinkingMode::Raw => self.raw.clone(),
+        }
+    }
+}
+
+impl Forgerc {
+    /// The `[engines.<kind>]` table, empty for engines without one.
+    fn engine_section(&self, kind: EngineKind) -> EngineSection {
+        let engines = self.engines.as_ref();
+        let section = match kind {
+            EngineKind::Codex => engines.and_then(|e| e.codex.as_ref()),
+            EngineKind::OpenCode => engines.and_then(|e| e.opencode.as_ref()),
+            EngineKind::Custom | EngineKind::Mock => None,
+        };
+        section.cloned().unwrap_or_default()
+    }
+
+    /// Removes and returns `[profile.<name>]`.
+    fn take_profile(&mut self, name: &str) -> Result<Forgerc> {
+        let Some(profile) = self.profile.remove(name) else {
+            let known: Vec<&str> = self.profile.keys().map(String::as_str).collect();
+            if known.is_empty() {
+                bail!("profile \"{name}\" not found: .forgerc defines no [profile.<name>] tables");
+            }
+            bail!(
+                "profile \"{name}\" not found in .forgerc (available: {})",
+                known.join(", ")
+            );
+        };
+        if profile.runtime_dir.is_some() {
+            bail!(
+                "[profile.{name}] cannot set runtime_dir; all profiles share one runtime directory"
+            );
+        }
+        if !profile.profile.is_empty() {
+            bail!("[profile.{name}] cannot contain nested profiles");
+        }
+        Ok(profile)
+    }
+}
+
+fn read_forgerc(cwd: &Path) -> Result<Forgerc> {
+    read_config_file(&cwd.join(".forgerc"))
+}
+
+/// `$XDG_CONFIG_HOME/forge/config.toml`, or `~/.config/forge/config.toml`
+/// when `XDG_CONFIG_HOME` is unset. `None` when neither variable is set.
+pub fn user_config_path() -> Option<PathBuf> {
+    let base = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|path| path.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(base.join("forge").join("config.toml"))
+}
+
+/// The user-global config. It takes the same keys as `.forgerc`, except
+/// `runtime_dir` and profiles, which belong to the project.
+fn read_user_config() -> Result<Forgerc> {
+    let Some(path) = user_config_path() else {
+        return Ok(Forgerc::default());
+    };
+    let cfg = read_config_file(&path)?;
+    if cfg.runtime_dir.is_some() {
+        bail!(
+            "{}: runtime_dir can only be set in the project .forgerc",
+            path.display()
+        );
+    }
+    if !cfg.profile.is_empty() {
+        bail!(
+            "{}: [profile.<name>] tables can only be defined in the project .forgerc",
+            path.display()
+        );
+    }
+    Ok(cfg)
+}
+
+/// Builds a config layer from `--set KEY=VALUE` entries. `VALUE` is read as
+/// a TOML value (`5`, `true`, `["a", "b"]`) and otherwise taken as a plain
+/// string; dotted keys such as `custom_engine.command` address tables.
+fn parse_set_overrides(entries: &[String]) -> Result<Forgerc> {
+    let mut table = toml::Table::new();
+    for entry in entries {
+        let Some((key, raw)) = entry.split_once('=') else {
+            bail!("--set {entry}: expected KEY=VALUE");
+        };
+        let path: Vec<&str> = key.trim().split('.').map(str::trim).collect();
+        if path.iter().any(|part| part.is_empty()) {
+            bail!("--set {entry}: expected KEY=VALUE");
+        }
+        if path[0] == "profile" {
+            bail!("--set {entry}: select profiles with --profile");
+        }
+        let value = toml::from_str::<toml::Table>(&format!("value = {raw}"))
+            .ok()
+            .and_then(|mut parsed| parsed.remove("value"))
+            .unwrap_or_else(|| toml::Value::String(raw.to_string()));
+
+        let (last, parents) = path.split_last().expect("key has at least one part");
+        let mut target = &mut table;
+        for part in parents {
+            target = match target
+                .entry(part.to_string())
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            {
+                toml::Value::Table(inner) => inner,
+                _ => bail!("--set {entry}: {part} is not a table"),
+            };
+        }
+        target.insert(last.to_string(), value);
+    }
+    let unknown = unknown_keys(&table);
+    if !unknown.is_empty() {
+        bail!("--set: {}", unknown.join("; "));
+    }
+    table.try_into().context("invalid --set value")
+}
+
+fn read_config_file(path: &Path) -> Result<Forgerc> {
+    if !path.exists() {
+        return Ok(Forgerc::default());
+    }
+    let raw =
+        fs::read_to_string(path).with_context(|| format!("failed to read {}", path.display()))?;
+    let table: toml::Table =
+        toml::from_str(&raw).with_context(|| format!("failed to parse {}", path.display()))?;
+    let unknown = unknown_keys(&table);
+    if !unknown.is_empty() {
+        bail!("{}: {}", path.display(), unknown.join("; "));
+    }
+    table
+        .try_into()
+        .with_context(|| format!("failed to parse {}", path.display()))
+}
+
+fn resolve_runtime_dir(
+    set_value: Option<String>,
+    file_value: Option<String>,
+    sources: &mut ConfigSources,
+) -> Result<PathBuf> {
+    let runtime_dir = sources.pick(
+        "runtime_dir",
+        [
+            (ConfigSource::Set, set_value),
+            env_str("FORGE_RUNTIME_DIR"),
+            (ConfigSource::Forgerc, file_value),
+        ],
+        DEFAULT_RUNTIME_DIR.to_string(),
+    );
+    Ok(expand_file_value(sources, "runtime_dir", runtime_dir)?.into())
 }
 
 pub fn load_run_config(cwd: &Path, overrides: &CliOverrides) -> Result<RunConfig> {
-    let mut file_cfg = Forgerc::default();
-    let forgerc_path = cwd.join(".forgerc");
-    if forgerc_path.exists() {
-        let raw = fs::read_to_string(&forgerc_path)
-            .with_context(|| format!("failed to read {}", forgerc_path.display()))?;
-        file_cfg = toml::from_str(&raw)
-            .with_context(|| format!("failed to parse {}", forgerc_path.display()))?;
+    let cfg = resolve_run_config(cwd, overrides)?;
+    let problems = out_of_range(&cfg);
+    if !problems.is_empty() {
+        bail!("{}", problems.join("; "));
+    }
+    Ok(cfg)
+}
+
+/// Every problem `load_run_config` would reject, for `forge config validate`
+/// and `forge doctor`. Unknown keys are reported for all config files at
+/// once; an empty list means the config loads.
+pub fn validate_config(cwd: &Path, overrides: &CliOverrides) -> Vec<String> {
+    let mut files = vec![cwd.join(".forgerc")];
+    if !overrides.no_user_config {
+        files.extend(user_config_path());
+    }
+    let mut problems = Vec::new();
+    for path in files.iter().filter(|path| path.exists()) {
+        match fs::read_to_string(path)
+            .map_err(anyhow::Error::from)
+            .and_then(|raw| Ok(toml::from_str::<toml::Table>(&raw)?))
+        {
+            Ok(table) => problems.extend(
+                unknown_keys(&table)
+                    .into_iter()
+                    .map(|problem| format!("{}: {problem}", path.display())),
+            ),
+            Err(err) => problems.push(format!("{}: {err}", path.display())),
+        }
+    }
+    if !problems.is_empty() {
+        return problems;
+    }
+    match resolve_run_config(cwd, overrides) {
+        Ok(cfg) => out_of_range(&cfg),
+        Err(err) => vec![format!("{err:#}")],
     }
+}
+
+fn resolve_run_config(cwd: &Path, overrides: &CliOverrides) -> Result<RunConfig> {
+    let mut file_cfg = read_forgerc(cwd)?;
+    let user_cfg = if overrides.no_user_config {
+        Forgerc::default()
+    } else {
+        read_user_config()?
+    };
+    let set_cfg = parse_set_overrides(&overrides.set)?;
+    let mut sources = ConfigSources::default();
+
+    let profile = sources.pick_opt(
+        "profile",
+        [
+            (ConfigSource::Flag, overrides.profile.clone()),
+            env_str("FORGE_PROFILE"),
+        ],
+    );
+    let profile_cfg = match &profile {
+        Some(name) => file_cfg.take_profile(name)?,
+        None => Forgerc::default(),
+    };
+    let profile_origin = format!("[profile.{}]", profile.as_deref().unwrap_or_default());
 
     let resume_mode = if let Some(id) = &overrides.resume {
         ResumeMode::Explicit(id.clone())
@@ -152,114 +663,525 @@ pub fn load_run_config(cwd: &Path, overrides: &CliOverrides) -> Result<RunConfig
     } else {
         ResumeMode::New
     };
+    if resume_mode != ResumeMode::New {
+        sources.set("resume", ConfigSource::Flag);
+    }
+
+    let engine = sources.pick(
+        "engine",
+        [
+            (ConfigSource::Flag, overrides.engine),
+            (
+                ConfigSource::Set,
+                parse_engine(set_cfg.engine.as_deref(), "--set")?,
+            ),
+            env("FORGE_ENGINE", env_engine)?,
+            (
+                ConfigSource::Profile,
+                parse_engine(profile_cfg.engine.as_deref(), &profile_origin)?,
+            ),
+            (
+                ConfigSource::Forgerc,
+                parse_engine(file_cfg.engine.as_deref(), ".forgerc")?,
+            ),
+            (
+                ConfigSource::User,
+                parse_engine(user_cfg.engine.as_deref(), "user config")?,
+            ),
+        ],
+        EngineKind::default(),
+    );
+
+    let set_engine = set_cfg.engine_section(engine);
+    let profile_engine = profile_cfg.engine_section(engine);
+    let file_engine = file_cfg.engine_section(engine);
+    let user_engine = user_cfg.engine_section(engine);
+
+    let custom_engine = sources.pick_opt(
+        "custom_engine",
+        [
+            (ConfigSource::Set, set_cfg.custom_engine),
+            (ConfigSource::Profile, profile_cfg.custom_engine),
+            (ConfigSource::Forgerc, file_cfg.custom_engine),
+            (ConfigSource::User, user_cfg.custom_engine),
+        ],
+    );
+    if engine == EngineKind::Custom {
+        match &custom_engine {
+            None => bail!("engine \"custom\" requires a [custom_engine] table in .forgerc"),
+            Some(custom) if custom.args.is_empty() => {
+                bail!("[custom_engine] args must not be empty")
+            }
+            Some(_) => {}
+        }
+    }
+
+    let mock_scenario = sources
+        .pick_opt(
+            "mock_scenario",
+            [
+                (ConfigSource::Set, set_cfg.mock_scenario),
+                env_str("FORGE_MOCK_SCENARIO"),
+                (ConfigSource::Profile, profile_cfg.mock_scenario),
+                (ConfigSource::Forgerc, file_cfg.mock_scenario),
+                (ConfigSource::User, user_cfg.mock_scenario),
+            ],
+        )
+        .map(|path| expand_file_value(&sources, "mock_scenario", path))
+        .transpose()?
+        .map(|path| cwd.join(path));
+    if engine == EngineKind::Mock && mock_scenario.is_none() {
+        bail!("engine \"mock\" requires mock_scenario in .forgerc or FORGE_MOCK_SCENARIO");
+    }
+
+    // The mock replays its scenario through its own shell; an `engine_cmd`
+    // meant for the real engine must not replace it.
+    let engine_cmd = if engine == EngineKind::Mock {
+        engine.default_cmd().map(str::to_string)
+    } else {
+        sources.pick_opt(
+            "engine_cmd",
+            [
+                (ConfigSource::Set, set_engine.cmd),
+                (ConfigSource::Set, set_cfg.engine_cmd),
+                env_str("FORGE_ENGINE_CMD"),
+                (ConfigSource::Profile, profile_engine.cmd),
+                (ConfigSource::Profile, profile_cfg.engine_cmd),
+                (ConfigSource::Forgerc, file_engine.cmd),
+                (ConfigSource::Forgerc, file_cfg.engine_cmd),
+                (ConfigSource::User, user_engine.cmd),
+                (ConfigSource::User, user_cfg.engine_cmd),
+                (
+                    sources.get("custom_engine"),
+                    custom_engine
+                        .as_ref()
+                        .filter(|_| engine == EngineKind::Custom)
+                        .and_then(|custom| custom.command.clone()),
+                ),
+                (
+                    ConfigSource::Default,
+                    engine.default_cmd().map(str::to_string),
+                ),
+            ],
+        )
+    };
+    let Some(engine_cmd) = engine_cmd else {
+        bail!("engine \"custom\" needs [custom_engine] command or engine_cmd");
+    };
+    let engine_cmd = expand_file_value(&sources, "engine_cmd", engine_cmd)?;
+    if engine_cmd.is_empty() {
+        bail!(
+            "engine_cmd must not be empty (from {})",
+            sources.get("engine_cmd")
+        );
+    }
+
+    let thinking_mode = sources.pick(
+        "thinking_mode",
+        [
+            (ConfigSource::Flag, overrides.thinking_mode),
+            (ConfigSource::Set, set_cfg.thinking_mode),
+            env("FORGE_THINKING_MODE", env_thinking_mode)?,
+            (ConfigSource::Profile, profile_cfg.thinking_mode),
+            (ConfigSource::Forgerc, file_cfg.thinking_mode),
+            (ConfigSource::User, user_cfg.thinking_mode),
+        ],
+        ThinkingMode::Summary,
+    );
+
+    let thinking_args = sources.pick(
+        "thinking_args",
+        [
+            (
+                ConfigSource::Set,
+                set_engine
+                    .thinking_args
+                    .as_ref()
+                    .and_then(|args| args.for_mode(thinking_mode)),
+            ),
+            (
+                ConfigSource::Profile,
+                profile_engine
+                    .thinking_args
+                    .as_ref()
+                    .and_then(|args| args.for_mode(thinking_mode)),
+            ),
+            (
+                ConfigSource::Forgerc,
+                file_engine
+                    .thinking_args
+                    .as_ref()
+                    .and_then(|args| args.for_mode(thinking_mode)),
+            ),
+            (
+                ConfigSource::User,
+                user_engine
+                    .thinking_args
+                    .as_ref()
+                    .and_then(|args| args.for_mode(thinking_mode)),
+            ),
+        ],
+        engine.default_thinking_args(thinking_mode),
+    );
+
+    let analyze_model = sources.pick_opt(
+        "analyze_model",
+        [
+            (ConfigSource::Set, set_cfg.analyze_model.clone()),
+            env_str("FORGE_ANALYZE_MODEL"),
+            (ConfigSource::Profile, profile_cfg.analyze_model.clone()),
+            (ConfigSource::Forgerc, file_cfg.analyze_model.clone()),
+            (ConfigSource::User, user_cfg.analyze_model.clone()),
+        ],
+    );
+    let analyze_model = analyze_model
+        .map(|model| expand_file_value(&sources, "analyze_model", model))
+        .transpose()?;
+    let analyze_reasoning_effort = sources.pick_opt(
+        "analyze_reasoning_effort",
+        [
+            (ConfigSource::Set, set_cfg.analyze_reasoning_effort),
+            env("FORGE_ANALYZE_REASONING_EFFORT", env_reasoning_effort)?,
+            (ConfigSource::Profile, profile_cfg.analyze_reasoning_effort),
+            (ConfigSource::Forgerc, file_cfg.analyze_reasoning_effort),
+            (ConfigSource::User, user_cfg.analyze_reasoning_effort),
+        ],
+    );
+
+    let analyze = overrides.analyze;
+    let model = sources.pick_opt(
+        "model",
+        [
+            (ConfigSource::Flag, overrides.model.clone()),
+            (ConfigSource::Set, set_cfg.analyze_model.filter(|_| analyze)),
+            (ConfigSource::Set, set_engine.model),
+            (ConfigSource::Set, set_cfg.model),
+            (
+                ConfigSource::Env("FORGE_ANALYZE_MODEL"),
+                env_value("FORGE_ANALYZE_MODEL").filter(|_| analyze),
+            ),
+            env_str("FORGE_MODEL"),
+            (
+                ConfigSource::Profile,
+                profile_cfg.analyze_model.filter(|_| analyze),
+            ),
+            (ConfigSource::Profile, profile_engine.model),
+            (ConfigSource::Profile, profile_cfg.model),
+            (
+                ConfigSource::Forgerc,
+                file_cfg.analyze_model.filter(|_| analyze),
+            ),
+            (ConfigSource::Forgerc, file_engine.model),
+            (ConfigSource::Forgerc, file_cfg.model),
+            (
+                ConfigSource::User,
+                user_cfg.analyze_model.filter(|_| analyze),
+            ),
+            (ConfigSource::User, user_engine.model),
+            (ConfigSource::User, user_cfg.model),
+        ],
+    );
+    let model = model
+        .map(|model| expand_file_value(&sources, "model", model))
+        .transpose()?;
+
+    let (analyze_effort_source, analyze_effort) =
+        env("FORGE_ANALYZE_REASONING_EFFORT", env_reasoning_effort)?;
+    let reasoning_effort = sources.pick_opt(
+        "reasoning_effort",
+        [
+            (ConfigSource::Flag, overrides.reasoning_effort),
+            (
+                ConfigSource::Set,
+                set_cfg.analyze_reasoning_effort.filter(|_| analyze),
+            ),
+            (ConfigSource::Set, set_engine.reasoning_effort),
+            (ConfigSource::Set, set_cfg.reasoning_effort),
+            (analyze_effort_source, analyze_effort.filter(|_| analyze)),
+            env("FORGE_REASONING_EFFORT", env_reasoning_effort)?,
+            (
+                ConfigSource::Profile,
+                profile_cfg.analyze_reasoning_effort.filter(|_| analyze),
+            ),
+            (ConfigSource::Profile, profile_engine.reasoning_effort),
+            (ConfigSource::Profile, profile_cfg.reasoning_effort),
+            (
+                ConfigSource::Forgerc,
+                file_cfg.analyze_reasoning_effort.filter(|_| analyze),
+            ),
+            (ConfigSource::Forgerc, file_engine.reasoning_effort),
+            (ConfigSource::Forgerc, file_cfg.reasoning_effort),
+            (
+                ConfigSource::User,
+                user_cfg.analyze_reasoning_effort.filter(|_| analyze),
+            ),
+            (ConfigSource::User, user_engine.reasoning_effort),
+            (ConfigSource::User, user_cfg.reasoning_effort),
+        ],
+    );
+
+    let mut engine_pre_args = sources.pick(
+        "engine_pre_args",
+        [
+            (ConfigSource::Flag, overrides.engine_pre_args.clone()),
+            (ConfigSource::Set, set_engine.pre_args),
+            (ConfigSource::Set, set_cfg.engine_pre_args),
+            env("FORGE_ENGINE_PRE_ARGS", env_shell_words)?,
+            (ConfigSource::Profile, profile_engine.pre_args),
+            (ConfigSource::Profile, profile_cfg.engine_pre_args),
+            (ConfigSource::Forgerc, file_engine.pre_args),
+            (ConfigSource::Forgerc, file_cfg.engine_pre_args),
+            (ConfigSource::User, user_engine.pre_args),
+            (ConfigSource::User, user_cfg.engine_pre_args),
+        ],
+        Vec::new(),
+    );
+    engine_pre_args.extend(thinking_args.iter().cloned());
+
+    let engine_exec_args = sources.pick(
+        "engine_exec_args",
+        [
+            (ConfigSource::Flag, overrides.engine_exec_args.clone()),
+            (ConfigSource::Set, set_engine.exec_args),
+            (ConfigSource::Set, set_cfg.engine_exec_args),
+            env("FORGE_ENGINE_EXEC_ARGS", env_shell_words)?,
+            (ConfigSource::Profile, profile_engine.exec_args),
+            (ConfigSource::Profile, profile_cfg.engine_exec_args),
+            (ConfigSource::Forgerc, file_engine.exec_args),
+            (ConfigSource::Forgerc, file_cfg.engine_exec_args),
+            (ConfigSource::User, user_engine.exec_args),
+            (ConfigSource::User, user_cfg.engine_exec_args),
+        ],
+        Vec::new(),
+    );
+
+    let max_calls_per_hour = sources.pick(
+        "max_calls_per_hour",
+        [
+            (ConfigSource::Flag, overrides.max_calls_per_hour),
+            (ConfigSource::Set, set_cfg.max_calls_per_hour),
+            env("FORGE_MAX_CALLS_PER_HOUR", env_u32)?,
+            (ConfigSource::Profile, profile_cfg.max_calls_per_hour),
+            (ConfigSource::Forgerc, file_cfg.max_calls_per_hour),
+            (ConfigSource::User, user_cfg.max_calls_per_hour),
+        ],
+        100,
+    );
 
-    let engine = first_some(
-        overrides.engine,
-        env_engine("FORGE_ENGINE"),
-        file_cfg
-            .engine
-            .as_deref()
-            .and_then(EngineKind::from_str_kind),
-    )
-    .unwrap_or_default();
-
-    let engine_cmd = first_some(
-        env::var("FORGE_ENGINE_CMD").ok(),
-        file_cfg.engine_cmd,
-        Some(engine.default_cmd().to_string()),
-    )
-    .unwrap_or_else(|| engine.default_cmd().to_string());
-
-    let thinking_mode = first_some(
-        overrides.thinking_mode,
-        env_thinking_mode("FORGE_THINKING_MODE"),
-        file_cfg.thinking_mode,
-    )
-    .unwrap_or(ThinkingMode::Summary);
-
-    let mut engine_pre_args = first_some(
-        overrides.engine_pre_args.clone(),
-        env_whitespace_args("FORGE_ENGINE_PRE_ARGS"),
-        file_cfg.engine_pre_args,
-    )
-    .unwrap_or_default();
-
-    if engine == EngineKind::Codex {
-        engine_pre_args.extend(thinking_mode.codex_config_args());
-    }
-
-    let engine_exec_args = first_some(
-        overrides.engine_exec_args.clone(),
-        env_whitespace_args("FORGE_ENGINE_EXEC_ARGS"),
-        file_cfg.engine_exec_args,
-    )
-    .unwrap_or_default();
-
-    let max_calls_per_hour = first_some(
-        overrides.max_calls_per_hour,
-        env_u32("FORGE_MAX_CALLS_PER_HOUR"),
-        file_cfg.max_calls_per_hour,
-    )
-    .unwrap_or(100);
-
-    let timeout_minutes = first_some(
-        overrides.timeout_minutes,
-        env_u64("FORGE_TIMEOUT_MINUTES"),
-        file_cfg.timeout_minutes,
-    )
-    .unwrap_or(15);
-
-    let runtime_dir = first_some(
-        env::var("FORGE_RUNTIME_DIR").ok(),
-        file_cfg.runtime_dir,
-        Some(".forge".to_string()),
-    )
-    .map(PathBuf::from)
-    .unwrap_or_else(|| PathBuf::from(".forge"));
-
-    let completion_indicators = first_some(
-        env_csv("FORGE_COMPLETION_INDICATORS"),
-        file_cfg.completion_indicators,
-        Some(vec![
+    let timeout_minutes = sources.pick(
+        "timeout_minutes",
+        [
+            (ConfigSource::Flag, overrides.timeout_minutes),
+            (ConfigSource::Set, set_cfg.timeout_minutes),
+            env("FORGE_TIMEOUT_MINUTES", env_u64)?,
+            (ConfigSource::Profile, profile_cfg.timeout_minutes),
+            (ConfigSource::Forgerc, file_cfg.timeout_minutes),
+            (ConfigSource::User, user_cfg.timeout_minutes),
+        ],
+        15,
+    );
+
+    let runtime_dir = resolve_runtime_dir(set_cfg.runtime_dir, file_cfg.runtime_dir, &mut sources)?;
+
+    let completion_indicators = sources.pick(
+        "completion_indicators",
+        [
+            (ConfigSource::Set, set_cfg.completion_indicators),
+            env("FORGE_COMPLETION_INDICATORS", env_csv)?,
+            (ConfigSource::Profile, profile_cfg.completion_indicators),
+            (ConfigSource::Forgerc, file_cfg.completion_indicators),
+            (ConfigSource::User, user_cfg.completion_indicators),
+        ],
+        vec![
             "STATUS: COMPLETE".to_string(),
             "TASK_COMPLETE".to_string(),
             "NO_MORE_WORK".to_string(),
             "ALL_TASKS_DONE".to_string(),
-        ]),
-    )
-    .unwrap_or_default();
-
-    let auto_wait_on_rate_limit = first_some(
-        env_bool("FORGE_AUTO_WAIT_ON_RATE_LIMIT"),
-        file_cfg.auto_wait_on_rate_limit,
-        Some(false),
-    )
-    .unwrap_or(false);
-
-    let sleep_on_rate_limit_secs = first_some(
-        env_u64("FORGE_RATE_LIMIT_WAIT_SECS"),
-        file_cfg.sleep_on_rate_limit_secs,
-        Some(60),
-    )
-    .unwrap_or(60);
-
-    let no_progress_limit = first_some(
-        env_u32("FORGE_NO_PROGRESS_LIMIT"),
-        file_cfg.no_progress_limit,
-        Some(3),
-    )
-    .unwrap_or(3);
-
-    if max_calls_per_hour == 0 {
-        bail!("max_calls_per_hour must be greater than 0");
+        ],
+    );
+
+    let auto_wait_on_rate_limit = sources.pick(
+        "auto_wait_on_rate_limit",
+        [
+            (ConfigSource::Set, set_cfg.auto_wait_on_rate_limit),
+            env("FORGE_AUTO_WAIT_ON_RATE_LIMIT", env_bool)?,
+            (ConfigSource::Profile, profile_cfg.auto_wait_on_rate_limit),
+            (ConfigSource::Forgerc, file_cfg.auto_wait_on_rate_limit),
+            (ConfigSource::User, user_cfg.auto_wait_on_rate_limit),
+        ],
+        false,
+    );
+
+    let sleep_on_rate_limit_secs = sources.pick(
+        "sleep_on_rate_limit_secs",
+        [
+            (ConfigSource::Set, set_cfg.sleep_on_rate_limit_secs),
+            env("FORGE_RATE_LIMIT_WAIT_SECS", env_u64)?,
+            (ConfigSource::Profile, profile_cfg.sleep_on_rate_limit_secs),
+            (ConfigSource::Forgerc, file_cfg.sleep_on_rate_limit_secs),
+            (ConfigSource::User, user_cfg.sleep_on_rate_limit_secs),
+        ],
+        60,
+    );
+
+    let no_progress_limit = sources.pick(
+        "no_progress_limit",
+        [
+            (ConfigSource::Set, set_cfg.no_progress_limit),
+            env("FORGE_NO_PROGRESS_LIMIT", env_u32)?,
+            (ConfigSource::Profile, profile_cfg.no_progress_limit),
+            (ConfigSource::Forgerc, file_cfg.no_progress_limit),
+            (ConfigSource::User, user_cfg.no_progress_limit),
+        ],
+        3,
+    );
+
+    let spec_context_budget_chars = sources.pick(
+        "spec_context_budget_chars",
+        [
+            (ConfigSource::Set, set_cfg.spec_context_budget_chars),
+            env("FORGE_SPEC_CONTEXT_BUDGET_CHARS", env_usize)?,
+            (ConfigSource::Profile, profile_cfg.spec_context_budget_chars),
+            (ConfigSource::Forgerc, file_cfg.spec_context_budget_chars),
+            (ConfigSource::User, user_cfg.spec_context_budget_chars),
+        ],
+        8000,
+    );
+
+    let spec_context_sets = sources.pick(
+        "spec_context_sets",
+        [
+            (ConfigSource::Set, set_cfg.spec_context_sets),
+            env("FORGE_SPEC_CONTEXT_SETS", env_csv)?,
+            (ConfigSource::Profile, profile_cfg.spec_context_sets),
+            (ConfigSource::Forgerc, file_cfg.spec_context_sets),
+            (ConfigSource::User, user_cfg.spec_context_sets),
+        ],
+        Vec::new(),
+    );
+    if let Some(name) = spec_context_sets
+        .iter()
+        .find(|name| !is_spec_set_name(name.trim()))
+    {
+        bail!(
+            "spec_context_sets: invalid set name \"{name}\" (from {}); use letters, digits, `-` and `_`",
+            sources.get("spec_context_sets")
+        );
     }
 
+    let engine_env = sources.pick(
+        "engine_env",
+        [
+            (
+                ConfigSource::Set,
+                merge_env(set_cfg.engine_env, set_engine.env),
+            ),
+            (
+                ConfigSource::Profile,
+                merge_env(profile_cfg.engine_env, profile_engine.env),
+            ),
+            (
+                ConfigSource::Forgerc,
+                merge_env(file_cfg.engine_env, file_engine.env),
+            ),
+            (
+                ConfigSource::User,
+                merge_env(user_cfg.engine_env, user_engine.env),
+            ),
+        ],
+        BTreeMap::new(),
+    );
+    let engine_env = engine_env
+        .into_iter()
+        .map(|(name, value)| {
+            let value = expand_file_value(&sources, &format!("engine_env.{name}"), value)?;
+            Ok((name, value))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
+    let engine_env_allowlist = sources.pick(
+        "engine_env_allowlist",
+        [
+            (ConfigSource::Set, set_cfg.engine_env_allowlist),
+            env("FORGE_ENGINE_ENV_ALLOWLIST", env_csv)?,
+            (ConfigSource::Profile, profile_cfg.engine_env_allowlist),
+            (ConfigSource::Forgerc, file_cfg.engine_env_allowlist),
+            (ConfigSource::User, user_cfg.engine_env_allowlist),
+        ],
+        Vec::new(),
+    );
+
+    let engine_env_denylist = sources.pick(
+        "engine_env_denylist",
+        [
+            (ConfigSource::Set, set_cfg.engine_env_denylist),
+            env("FORGE_ENGINE_ENV_DENYLIST", env_csv)?,
+            (ConfigSource::Profile, profile_cfg.engine_env_denylist),
+            (ConfigSource::Forgerc, file_cfg.engine_env_denylist),
+            (ConfigSource::User, user_cfg.engine_env_denylist),
+        ],
+        Vec::new(),
+    );
+
+    let engine_env_clear = sources.pick(
+        "engine_env_clear",
+        [
+            (ConfigSource::Set, set_cfg.engine_env_clear),
+            env("FORGE_ENGINE_ENV_CLEAR", env_bool)?,
+            (ConfigSource::Profile, profile_cfg.engine_env_clear),
+            (ConfigSource::Forgerc, file_cfg.engine_env_clear),
+            (ConfigSource::User, user_cfg.engine_env_clear),
+        ],
+        false,
+    );
+
+    let live_log_max_bytes = sources.pick(
+        "live_log_max_bytes",
+        [
+            (ConfigSource::Set, set_cfg.live_log_max_bytes),
+            env("FORGE_LIVE_LOG_MAX_BYTES", env_u64)?,
+            (ConfigSource::Profile, profile_cfg.live_log_max_bytes),
+            (ConfigSource::Forgerc, file_cfg.live_log_max_bytes),
+            (ConfigSource::User, user_cfg.live_log_max_bytes),
+        ],
+        10 * 1024 * 1024,
+    );
+
+    let live_log_max_age_secs = sources.pick(
+        "live_log_max_age_secs",
+        [
+            (ConfigSource::Set, set_cfg.live_log_max_age_secs),
+            env("FORGE_LIVE_LOG_MAX_AGE_SECS", env_u64)?,
+            (ConfigSource::Profile, profile_cfg.live_log_max_age_secs),
+            (ConfigSource::Forgerc, file_cfg.live_log_max_age_secs),
+            (ConfigSource::User, user_cfg.live_log_max_age_secs),
+        ],
+        7 * 24 * 60 * 60,
+    );
+
+    let live_log_retention = sources.pick(
+        "live_log_retention",
+        [
+            (ConfigSource::Set, set_cfg.live_log_retention),
+            env("FORGE_LIVE_LOG_RETENTION", env_usize)?,
+            (ConfigSource::Profile, profile_cfg.live_log_retention),
+            (ConfigSource::Forgerc, file_cfg.live_log_retention),
+            (ConfigSource::User, user_cfg.live_log_retention),
+        ],
+        5,
+    );
+
     Ok(RunConfig {
         engine,
         engine_cmd,
         engine_pre_args,
         engine_exec_args,
         thinking_mode,
+        model,
+        reasoning_effort,
+        analyze_model,
+        analyze_reasoning_effort,
+        thinking_args,
         max_calls_per_hour,
         timeout_minutes,
         runtime_dir,
@@ -268,69 +1190,141 @@ pub fn load_run_config(cwd: &Path, overrides: &CliOverrides) -> Result<RunConfig
         sleep_on_rate_limit_secs,
         no_progress_limit,
         resume_mode,
+        spec_context_budget_chars,
+        spec_context_sets,
+        custom_engine,
+        mock_scenario,
+        engine_env,
+        engine_env_allowlist,
+        engine_env_denylist,
+        engine_env_clear,
+        live_log_max_bytes,
+        live_log_max_age_secs,
+        live_log_retention,
+        profile,
+        sources,
     })
 }
 
-fn first_some<T>(a: Option<T>, b: Option<T>, c: Option<T>) -> Option<T> {
-    a.or(b).or(c)
+/// A `docs/specs/<name>/` directory name: letters, digits, `-` and `_`, so
+/// a set can never point outside `docs/specs`. Blank names are skipped.
+pub fn is_spec_set_name(name: &str) -> bool {
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Within one layer, `[engines.<name>].env` overrides `[engine_env]` key by
+/// key.
+fn merge_env(
+    shared: Option<BTreeMap<String, String>>,
+    engine: Option<BTreeMap<String, String>>,
+) -> Option<BTreeMap<String, String>> {
+    match (shared, engine) {
+        (Some(mut shared), Some(engine)) => {
+            shared.extend(engine);
+            Some(shared)
+        }
+        (shared, engine) => engine.or(shared),
+    }
+}
+
+/// An environment layer: the variable name and its parsed value. A value
+/// that does not parse is an error naming the variable.
+fn env<T>(
+    key: &'static str,
+    parse: fn(&str) -> Result<Option<T>>,
+) -> Result<(ConfigSource, Option<T>)> {
+    Ok((ConfigSource::Env(key), parse(key)?))
 }
 
-fn env_u32(key: &str) -> Option<u32> {
-    env::var(key).ok()?.parse().ok()
+fn env_str(key: &'static str) -> (ConfigSource, Option<String>) {
+    (ConfigSource::Env(key), env_value(key))
 }
 
-fn env_u64(key: &str) -> Option<u64> {
-    env::var(key).ok()?.parse().ok()
+/// Set and not blank.
+fn env_value(key: &str) -> Option<String> {
+    env::var(key).ok().filter(|value| !value.trim().is_empty())
 }
 
-fn env_bool(key: &str) -> Option<bool> {
-    let value = env::var(key).ok()?;
-    match value.to_ascii_lowercase().as_str() {
-        "1" | "true" | "yes" | "on" => Some(true),
-        "0" | "false" | "no" | "off" => Some(false),
-        _ => None,
+fn env_number<T: std::str::FromStr>(key: &str) -> Result<Option<T>> {
+    env_value(key)
+        .map(|value| {
+            value
+                .trim()
+                .parse()
+                .map_err(|_| anyhow!("{key}={value} is not a valid non-negative integer"))
+        })
+        .transpose()
+}
+
+fn env_u32(key: &str) -> Result<Option<u32>> {
+    env_number(key)
+}
+
+fn env_u64(key: &str) -> Result<Option<u64>> {
+    env_number(key)
+}
+
+fn env_usize(key: &str) -> Result<Option<usize>> {
+    env_number(key)
+}
+
+fn env_bool(key: &str) -> Result<Option<bool>> {
+    let Some(value) = env_value(key) else {
+        return Ok(None);
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "1" | "true" | "yes" | "on" => Ok(Some(true)),
+        "0" | "false" | "no" | "off" => Ok(Some(false)),
+        _ => bail!("{key}={value} is not a boolean (expected true/false, 1/0, yes/no or on/off)"),
     }
 }
 
-fn env_csv(key: &str) -> Option<Vec<String>> {
-    let value = env::var(key).ok()?;
+fn env_csv(key: &str) -> Result<Option<Vec<String>>> {
+    let Some(value) = env_value(key) else {
+        return Ok(None);
+    };
     let parts = value
         .split(',')
         .map(str::trim)
         .filter(|s| !s.is_empty())
         .map(ToString::to_string)
         .collect::<Vec<_>>();
-    if parts.is_empty() {
-        None
-    } else {
-        Some(parts)
-    }
+    Ok((!parts.is_empty()).then_some(parts))
 }
 
-fn env_whitespace_args(key: &str) -> Option<Vec<String>> {
-    let value = env::var(key).ok()?;
-    let args = value
-        .split_whitespace()
-        .map(ToString::to_string)
-        .collect::<Vec<_>>();
-    if args.is_empty() {
-        None
-    } else {
-        Some(args)
+/// An argument list split with POSIX shell rules, so quoted arguments may
+/// contain spaces.
+fn env_shell_words(key: &str) -> Result<Option<Vec<String>>> {
+    env_value(key)
+        .map(|value| shell_words::split(&value).map_err(|err| anyhow!("{key}={value}: {err}")))
+        .transpose()
+}
+
+fn env_thinking_mode(key: &str) -> Result<Option<ThinkingMode>> {
+    let Some(value) = env_value(key) else {
+        return Ok(None);
+    };
+    match value.trim().to_ascii_lowercase().as_str() {
+        "off" => Ok(Some(ThinkingMode::Off)),
+        "summary" => Ok(Some(ThinkingMode::Summary)),
+        "raw" => Ok(Some(ThinkingMode::Raw)),
+        _ => bail!("{key}={value} is not a valid thinking mode (expected off, summary or raw)"),
     }
 }
 
-fn env_thinking_mode(key: &str) -> Option<ThinkingMode> {
-    let value = env::var(key).ok()?;
-    match value.to_ascii_lowercase().as_str() {
-        "off" => Some(ThinkingMode::Off),
-        "summary" => Some(ThinkingMode::Summary),
-        "raw" => Some(ThinkingMode::Raw),
-        _ => None,
+fn env_reasoning_effort(key: &str) -> Result<Option<ReasoningEffort>> {
+    let Some(value) = env_value(key) else {
+        return Ok(None);
+    };
+    match ReasoningEffort::from_str_effort(value.trim()) {
+        Some(effort) => Ok(Some(effort)),
+        None => bail!(
+            "{key}={value} is not a valid reasoning effort (expected minimal, low, medium or high)"
+        ),
     }
 }
 
-fn env_engine(key: &str) -> Option<EngineKind> {
-    let value = env::var(key).ok()?;
-    EngineKind::from_str_kind(&value)
+fn env_engine(key: &str) -> Result<Option<EngineKind>> {
+    parse_engine(env_value(key).as_deref().map(str::trim), key)
 }
@@ -0,0 +1,91 @@
+use serde::{Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Where an effective config value came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigSource {
+    Flag,
+    /// `--set KEY=VALUE`.
+    Set,
+    Env(&'static str),
+    /// The `[profile.<name>]` table selected by `--profile`/`FORGE_PROFILE`.
+    Profile,
+    Forgerc,
+    /// `$XDG_CONFIG_HOME/forge/config.toml`.
+    User,
+    Default,
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Flag => f.write_str("flag"),
+            ConfigSource::Set => f.write_str("--set"),
+            ConfigSource::Env(key) => write!(f, "env {key}"),
+            ConfigSource::Profile => f.write_str("profile"),
+            ConfigSource::Forgerc => f.write_str(".forgerc"),
+            ConfigSource::User => f.write_str("user config"),
+            ConfigSource::Default => f.write_str("default"),
+        }
+    }
+}
+
+impl Serialize for ConfigSource {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Source of every field resolved by `load_run_config`, keyed by its
+/// `.forgerc` name.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigSources(BTreeMap<&'static str, ConfigSource>);
+
+impl ConfigSources {
+    pub fn get(&self, key: &str) -> ConfigSource {
+        self.0.get(key).copied().unwrap_or(ConfigSource::Default)
+    }
+
+    /// Every key resolved so far.
+    pub fn keys(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.0.keys().copied()
+    }
+
+    pub(crate) fn set(&mut self, key: &'static str, source: ConfigSource) {
+        self.0.insert(key, source);
+    }
+
+    /// The first layer that has a value, highest precedence first.
+    pub(crate) fn pick_opt<T, const N: usize>(
+        &mut self,
+        key: &'static str,
+        layers: [(ConfigSource, Option<T>); N],
+    ) -> Option<T> {
+        for (source, value) in layers {
+            if value.is_some() {
+                self.set(key, source);
+                return value;
+            }
+        }
+        self.set(key, ConfigSource::Default);
+        None
+    }
+
+    pub(crate) fn pick<T, const N: usize>(
+        &mut self,
+        key: &'static str,
+        layers: [(ConfigSource, Option<T>); N],
+        default: T,
+    ) -> T {
+        self.pick_opt(key, layers).unwrap_or(default)
+    }
+}
+
+/// One line of `forge config show`.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigEntry {
+    pub key: &'static str,
+    pub value: serde_json::Value,
+    pub source: ConfigSource,
+}
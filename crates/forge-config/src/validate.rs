@@ -0,0 +1,196 @@
+use crate::{
+    ConfigSource, CustomEngineConfig, EngineKind, EngineSection, EnginesTable, Forgerc, RunConfig,
+    ThinkingArgs,
+};
+use anyhow::{bail, Result};
+use serde::de::{self, DeserializeOwned, Deserializer, Visitor};
+use serde::forward_to_deserialize_any;
+
+/// Field names serde expects for `T`, taken from its derived `Deserialize`
+/// so the known-key lists cannot drift from the config structs.
+fn struct_fields<T: DeserializeOwned>() -> &'static [&'static str] {
+    struct Fields<'a>(&'a mut &'static [&'static str]);
+
+    impl<'de> Deserializer<'de> for Fields<'_> {
+        type Error = de::value::Error;
+
+        fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Self::Error> {
+            Err(de::Error::custom("not a struct"))
+        }
+
+        fn deserialize_struct<V: Visitor<'de>>(
+            self,
+            _: &'static str,
+            fields: &'static [&'static str],
+            _: V,
+        ) -> Result<V::Value, Self::Error> {
+            *self.0 = fields;
+            Err(de::Error::custom("fields captured"))
+        }
+
+        forward_to_deserialize_any! {
+            bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+            bytes byte_buf option unit unit_struct newtype_struct seq tuple
+            tuple_struct map enum identifier ignored_any
+        }
+    }
+
+    let mut fields: &'static [&'static str] = &[];
+    // Always an error: the deserializer stops once it has seen the fields.
+    let _ = T::deserialize(Fields(&mut fields));
+    fields
+}
+
+const ENGINE_NAMES: &[&str] = &["codex", "opencode", "custom", "mock"];
+
+/// Unknown keys in a parsed config file, each with a suggestion when a
+/// known key is close enough to be a typo.
+pub(crate) fn unknown_keys(table: &toml::Table) -> Vec<String> {
+    let mut problems = Vec::new();
+    check_table(table, "", &mut problems);
+    if let Some(profiles) = table.get("profile").and_then(toml::Value::as_table) {
+        for (name, profile) in profiles {
+            if let Some(profile) = profile.as_table() {
+                check_table(profile, &format!("profile.{name}."), &mut problems);
+            }
+        }
+    }
+    problems
+}
+
+fn check_table(table: &toml::Table, prefix: &str, problems: &mut Vec<String>) {
+    let forgerc_keys = struct_fields::<Forgerc>();
+    for key in table.keys() {
+        if !forgerc_keys.contains(&key.as_str()) {
+            problems.push(unknown_key(&format!("{prefix}{key}"), key, forgerc_keys));
+        }
+    }
+    check_keys(
+        table,
+        prefix,
+        "custom_engine",
+        struct_fields::<CustomEngineConfig>(),
+        problems,
+    );
+    let engine_tables = struct_fields::<EnginesTable>();
+    check_keys(table, prefix, "engines", engine_tables, problems);
+    let Some(engines) = table.get("engines").and_then(toml::Value::as_table) else {
+        return;
+    };
+    for name in engine_tables {
+        check_keys(
+            engines,
+            &format!("{prefix}engines."),
+            name,
+            struct_fields::<EngineSection>(),
+            problems,
+        );
+        if let Some(section) = engines.get(*name).and_then(toml::Value::as_table) {
+            check_keys(
+                section,
+                &format!("{prefix}engines.{name}."),
+                "thinking_args",
+                struct_fields::<ThinkingArgs>(),
+                problems,
+            );
+        }
+    }
+}
+
+/// Keys of the `name` sub-table of `table` that are not in `known`.
+fn check_keys(
+    table: &toml::Table,
+    prefix: &str,
+    name: &str,
+    known: &[&str],
+    problems: &mut Vec<String>,
+) {
+    let Some(inner) = table.get(name).and_then(toml::Value::as_table) else {
+        return;
+    };
+    for key in inner.keys() {
+        if !known.contains(&key.as_str()) {
+            problems.push(unknown_key(&format!("{prefix}{name}.{key}"), key, known));
+        }
+    }
+}
+
+fn unknown_key(path: &str, key: &str, known: &[&str]) -> String {
+    match suggest(key, known) {
+        Some(candidate) => format!("unknown key `{path}` (did you mean `{candidate}`?)"),
+        None => format!("unknown key `{path}`"),
+    }
+}
+
+/// The closest candidate, if it is within a few edits of `input`.
+pub(crate) fn suggest<'a>(input: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let limit = (input.len() / 3).clamp(1, 3);
+    candidates
+        .iter()
+        .map(|candidate| (strsim::levenshtein(input, candidate), *candidate))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// `engine` from a config file; unknown names are an error rather than
+/// falling back to the default engine.
+pub(crate) fn parse_engine(value: Option<&str>, origin: &str) -> Result<Option<EngineKind>> {
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    if let Some(kind) = EngineKind::from_str_kind(value) {
+        return Ok(Some(kind));
+    }
+    match suggest(&value.to_ascii_lowercase(), ENGINE_NAMES) {
+        Some(candidate) => {
+            bail!("{origin}: unknown engine \"{value}\" (did you mean \"{candidate}\"?)")
+        }
+        None => bail!(
+            "{origin}: unknown engine \"{value}\" (expected {})",
+            ENGINE_NAMES.join(", ")
+        ),
+    }
+}
+
+/// Values that parse but cannot work.
+pub(crate) fn out_of_range(cfg: &RunConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+    let mut require_positive = |key: &'static str, value: u64, why: &str| {
+        if value == 0 {
+            let source = match cfg.sources.get(key) {
+                ConfigSource::Default => String::new(),
+                source => format!(" (from {source})"),
+            };
+            problems.push(format!("{key} must be greater than 0{source}{why}"));
+        }
+    };
+    require_positive("max_calls_per_hour", cfg.max_calls_per_hour.into(), "");
+    require_positive(
+        "no_progress_limit",
+        cfg.no_progress_limit.into(),
+        "; 0 would open the circuit breaker on the first loop without progress",
+    );
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn known_keys_come_from_the_config_structs() {
+        let forgerc = struct_fields::<Forgerc>();
+        for key in [
+            "engine",
+            "engine_env",
+            "live_log_retention",
+            "engines",
+            "profile",
+        ] {
+            assert!(forgerc.contains(&key), "{key}");
+        }
+        assert_eq!(struct_fields::<EnginesTable>(), ["codex", "opencode"]);
+        assert_eq!(struct_fields::<ThinkingArgs>(), ["off", "summary", "raw"]);
+    }
+}
@@ -1,4 +1,7 @@
-use forge_config::{load_run_config, CliOverrides, ResumeMode};
+use forge_config::{
+    load_run_config, CliOverrides, ConfigSource, EngineKind, ReasoningEffort, ResumeMode,
+    RuntimeLayout,
+};
 use std::fs;
 use tempfile::tempdir;
 
@@ -21,10 +24,16 @@ fn cli_overrides_file_values() {
             ]),
             engine_exec_args: None,
             thinking_mode: None,
+            model: None,
+            reasoning_effort: None,
             max_calls_per_hour: Some(77),
             timeout_minutes: Some(22),
             resume: None,
             resume_last: true,
+            profile: None,
+            no_user_config: true,
+            set: Vec::new(),
+            analyze: false,
         },
     )
     .expect("load_run_config");
@@ -55,10 +64,16 @@ fn thinking_mode_raw_adds_raw_reasoning_flags() {
             engine_pre_args: None,
             engine_exec_args: None,
             thinking_mode: Some(forge_config::ThinkingMode::Raw),
+            model: None,
+            reasoning_effort: None,
             max_calls_per_hour: None,
             timeout_minutes: None,
             resume: None,
             resume_last: false,
+            profile: None,
+            no_user_config: true,
+            set: Vec::new(),
+            analyze: false,
         },
     )
     .expect("load_run_config");
@@ -69,3 +84,702 @@ fn thinking_mode_raw_adds_raw_reasoning_flags() {
         .windows(2)
         .any(|w| w == ["--config", "show_raw_agent_reasoning=true"]));
 }
+
+#[test]
+fn custom_engine_reads_table_and_requires_it() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join(".forgerc"), "engine = \"custom\"\n").expect("forgerc write");
+    let err = load_run_config(dir.path(), &isolated()).expect_err("missing table");
+    assert!(err.to_string().contains("[custom_engine]"));
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine = \"custom\"\n[custom_engine]\nargs = [\"{prompt}\"]\n",
+    )
+    .expect("forgerc write");
+    let err = load_run_config(dir.path(), &isolated()).expect_err("no command");
+    assert!(
+        err.to_string()
+            .contains("needs [custom_engine] command or engine_cmd"),
+        "{err}"
+    );
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine = \"custom\"\n[custom_engine]\ncommand = \"aider\"\nargs = [\"--message\", \"{prompt}\"]\nprompt_delivery = \"stdin\"\n",
+    )
+    .expect("forgerc write");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("load_run_config");
+
+    assert_eq!(cfg.engine, forge_config::EngineKind::Custom);
+    assert_eq!(cfg.engine_cmd, "aider");
+    let custom = cfg.custom_engine.expect("custom engine");
+    assert_eq!(custom.name, "custom");
+    assert_eq!(custom.prompt_delivery, forge_config::PromptDelivery::Stdin);
+    assert_eq!(custom.output_format, forge_config::OutputFormat::Plain);
+}
+
+#[test]
+fn mock_engine_requires_scenario_and_resolves_it_against_cwd() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join(".forgerc"), "engine = \"mock\"\n").expect("forgerc write");
+    let err = load_run_config(dir.path(), &isolated()).expect_err("no scenario");
+    assert!(err.to_string().contains("mock_scenario"));
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine = \"mock\"\nmock_scenario = \"scenarios/happy.toml\"\n",
+    )
+    .expect("forgerc write");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("load_run_config");
+
+    assert_eq!(cfg.engine, forge_config::EngineKind::Mock);
+    assert_eq!(cfg.engine_cmd, "sh");
+    assert_eq!(
+        cfg.mock_scenario,
+        Some(dir.path().join("scenarios/happy.toml"))
+    );
+}
+
+#[test]
+fn engine_env_settings_load_from_forgerc() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine_env_clear = true\nengine_env_allowlist = [\"PATH\", \"OPENAI_*\"]\nengine_env_denylist = [\"OPENAI_ORG\"]\n[engine_env]\nRUST_LOG = \"warn\"\n",
+    )
+    .expect("forgerc write");
+
+    let cfg = load_run_config(dir.path(), &isolated()).expect("load_run_config");
+
+    assert!(cfg.engine_env_clear);
+    assert_eq!(cfg.engine_env_allowlist, vec!["PATH", "OPENAI_*"]);
+    assert_eq!(cfg.engine_env_denylist, vec!["OPENAI_ORG"]);
+    assert_eq!(
+        cfg.engine_env.get("RUST_LOG").map(String::as_str),
+        Some("warn")
+    );
+}
+
+#[test]
+fn live_log_rotation_defaults_and_forgerc_values() {
+    let dir = tempdir().expect("tempdir");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("load_run_config");
+    assert_eq!(cfg.live_log_max_bytes, 10 * 1024 * 1024);
+    assert_eq!(cfg.live_log_max_age_secs, 7 * 24 * 60 * 60);
+    assert_eq!(cfg.live_log_retention, 5);
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "live_log_max_bytes = 4096\nlive_log_max_age_secs = 0\nlive_log_retention = 2\n",
+    )
+    .expect("forgerc write");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("load_run_config");
+    assert_eq!(cfg.live_log_max_bytes, 4096);
+    assert_eq!(cfg.live_log_max_age_secs, 0);
+    assert_eq!(cfg.live_log_retention, 2);
+}
+
+#[test]
+fn runtime_layout_resolves_every_path_under_runtime_dir() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "runtime_dir = \"state/forge\"\n",
+    )
+    .expect("forgerc write");
+
+    let cfg = load_run_config(dir.path(), &isolated()).expect("config");
+    let layout = RuntimeLayout::from_config(dir.path(), &cfg);
+    let root = dir.path().join("state/forge");
+
+    assert_eq!(layout.root(), root);
+    assert_eq!(
+        RuntimeLayout::discover(dir.path()).expect("discover"),
+        layout
+    );
+    assert_eq!(layout.plan_file(), root.join("plan.md"));
+    assert_eq!(layout.sdd_dir("01-a"), root.join("sdds/01-a"));
+    assert_eq!(
+        layout.analyze_latest_file(),
+        root.join("analyze/latest.json")
+    );
+    assert_eq!(
+        layout.iteration_dir("run-1", 3),
+        root.join("iterations/run-1/loop-3")
+    );
+    assert_eq!(
+        layout.display(dir.path(), &layout.plan_file()),
+        "state/forge/plan.md"
+    );
+    assert!(layout
+        .fresh_run_files()
+        .iter()
+        .all(|p| p.starts_with(&root)));
+
+    // Only runtime_dir is read, so an otherwise invalid config still resolves.
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine = \"custom\"\nruntime_dir = \"state/forge\"\n",
+    )
+    .expect("forgerc write");
+    assert!(load_run_config(dir.path(), &isolated()).is_err());
+    assert_eq!(
+        RuntimeLayout::discover(dir.path()).expect("discover"),
+        layout
+    );
+}
+
+#[test]
+fn sources_record_which_layer_won() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "engine = \"opencode\"\nmax_calls_per_hour = 10\ntimeout_minutes = 2\n",
+    )
+    .expect("forgerc write");
+
+    let cfg = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            timeout_minutes: Some(22),
+            ..isolated()
+        },
+    )
+    .expect("load_run_config");
+
+    assert_eq!(cfg.engine, forge_config::EngineKind::OpenCode);
+    assert_eq!(cfg.sources.get("engine"), ConfigSource::Forgerc);
+    assert_eq!(cfg.sources.get("max_calls_per_hour"), ConfigSource::Forgerc);
+    assert_eq!(cfg.sources.get("timeout_minutes"), ConfigSource::Flag);
+    assert_eq!(cfg.sources.get("no_progress_limit"), ConfigSource::Default);
+    assert_eq!(cfg.sources.get("resume"), ConfigSource::Default);
+
+    let entries = cfg.entries();
+    let engine = entries.iter().find(|e| e.key == "engine").expect("engine");
+    assert_eq!(engine.value, "opencode");
+    assert_eq!(engine.source.to_string(), ".forgerc");
+    let timeout = entries
+        .iter()
+        .find(|e| e.key == "timeout_minutes")
+        .expect("timeout");
+    assert_eq!(timeout.value, 22);
+    assert_eq!(timeout.source.to_string(), "flag");
+    assert_eq!(
+        ConfigSource::Env("FORGE_ENGINE").to_string(),
+        "env FORGE_ENGINE"
+    );
+}
+
+#[test]
+fn selected_profile_overlays_base_values() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        r#"
+timeout_minutes = 15
+max_calls_per_hour = 50
+thinking_mode = "summary"
+
+[profile.quick]
+timeout_minutes = 3
+
+[profile.deep]
+timeout_minutes = 60
+thinking_mode = "raw"
+engine_pre_args = ["--sandbox", "danger-full-access"]
+"#,
+    )
+    .expect("forgerc write");
+
+    let load = |profile: Option<&str>, timeout: Option<u64>| {
+        load_run_config(
+            dir.path(),
+            &CliOverrides {
+                profile: profile.map(str::to_string),
+                timeout_minutes: timeout,
+                ..isolated()
+            },
+        )
+    };
+
+    let base = load(None, None).expect("base");
+    assert_eq!(base.timeout_minutes, 15);
+    assert_eq!(base.profile, None);
+
+    let deep = load(Some("deep"), None).expect("deep");
+    assert_eq!(deep.profile.as_deref(), Some("deep"));
+    assert_eq!(deep.timeout_minutes, 60);
+    assert_eq!(deep.thinking_mode, forge_config::ThinkingMode::Raw);
+    assert_eq!(deep.max_calls_per_hour, 50);
+    assert!(deep
+        .engine_pre_args
+        .starts_with(&["--sandbox".to_string(), "danger-full-access".to_string()]));
+    assert_eq!(deep.sources.get("profile"), ConfigSource::Flag);
+    assert_eq!(deep.sources.get("timeout_minutes"), ConfigSource::Profile);
+    assert_eq!(
+        deep.sources.get("max_calls_per_hour"),
+        ConfigSource::Forgerc
+    );
+
+    let flagged = load(Some("quick"), Some(9)).expect("quick with flag");
+    assert_eq!(flagged.timeout_minutes, 9);
+    assert_eq!(flagged.thinking_mode, forge_config::ThinkingMode::Summary);
+
+    let err = load(Some("missing"), None).expect_err("unknown profile");
+    assert!(err.to_string().contains("available: deep, quick"), "{err}");
+}
+
+#[test]
+fn profile_cannot_move_runtime_dir() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "[profile.other]\nruntime_dir = \"elsewhere\"\n",
+    )
+    .expect("forgerc write");
+
+    let err = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            profile: Some("other".to_string()),
+            ..isolated()
+        },
+    )
+    .expect_err("runtime_dir in profile");
+    assert!(err.to_string().contains("cannot set runtime_dir"), "{err}");
+}
+
+fn isolated() -> CliOverrides {
+    CliOverrides {
+        no_user_config: true,
+        ..CliOverrides::default()
+    }
+}
+
+#[test]
+fn unknown_keys_are_rejected_with_suggestions() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        r#"
+max_call_per_hour = 10
+completely_unrelated = true
+
+[custom_engine]
+args = ["x"]
+probes = ["x", "--version"]
+
+[profile.deep]
+timeout_minute = 60
+"#,
+    )
+    .expect("forgerc write");
+
+    let err = load_run_config(dir.path(), &isolated()).expect_err("unknown keys");
+    let message = err.to_string();
+    assert!(
+        message.contains("unknown key `max_call_per_hour` (did you mean `max_calls_per_hour`?)"),
+        "{message}"
+    );
+    assert!(
+        message.contains("unknown key `completely_unrelated`;"),
+        "{message}"
+    );
+    assert!(
+        message.contains("unknown key `custom_engine.probes` (did you mean `probe`?)"),
+        "{message}"
+    );
+    assert!(
+        message.contains(
+            "unknown key `profile.deep.timeout_minute` (did you mean `timeout_minutes`?)"
+        ),
+        "{message}"
+    );
+
+    let problems = forge_config::validate_config(dir.path(), &isolated());
+    assert_eq!(problems.len(), 4, "{problems:?}");
+}
+
+#[test]
+fn every_documented_key_is_accepted() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join("scenario.toml"), "").expect("scenario write");
+    fs::write(
+        dir.path().join(".forgerc"),
+        r#"
+engine = "mock"
+engine_cmd = "codex"
+engine_pre_args = ["--a"]
+engine_exec_args = ["--b"]
+thinking_mode = "raw"
+max_calls_per_hour = 10
+timeout_minutes = 5
+runtime_dir = ".forge"
+completion_indicators = ["DONE"]
+auto_wait_on_rate_limit = true
+sleep_on_rate_limit_secs = 5
+no_progress_limit = 2
+spec_context_budget_chars = 100
+spec_context_sets = ["api"]
+mock_scenario = "scenario.toml"
+engine_env_allowlist = ["PATH"]
+engine_env_denylist = ["SECRET_*"]
+engine_env_clear = false
+live_log_max_bytes = 1024
+live_log_max_age_secs = 60
+live_log_retention = 2
+
+[engine_env]
+AGENT = "1"
+
+[custom_engine]
+name = "agent"
+command = "agent"
+args = ["{prompt}"]
+resume_args = ["--resume", "{session_id}"]
+resume_last_args = ["--last"]
+prompt_delivery = "stdin"
+output_format = "jsonl"
+session_id_fields = ["session"]
+session_id_markers = ["session:"]
+probe = ["agent", "--version"]
+
+[profile.quick]
+timeout_minutes = 1
+"#,
+    )
+    .expect("forgerc write");
+
+    load_run_config(dir.path(), &isolated()).expect("all keys load");
+    assert!(forge_config::validate_config(dir.path(), &isolated()).is_empty());
+}
+
+#[test]
+fn invalid_values_are_errors_not_defaults() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(dir.path().join(".forgerc"), "engine = \"codx\"\n").expect("forgerc write");
+    let err = load_run_config(dir.path(), &isolated()).expect_err("bad engine");
+    assert!(
+        err.to_string()
+            .contains(".forgerc: unknown engine \"codx\" (did you mean \"codex\"?)"),
+        "{err}"
+    );
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "no_progress_limit = 0\nmax_calls_per_hour = 0\n",
+    )
+    .expect("forgerc write");
+    let problems = forge_config::validate_config(dir.path(), &isolated());
+    assert_eq!(problems.len(), 2, "{problems:?}");
+    assert!(problems[0].starts_with("max_calls_per_hour must be greater than 0 (from .forgerc)"));
+    assert!(problems[1].contains("no_progress_limit must be greater than 0 (from .forgerc)"));
+    assert!(problems[1].contains("circuit breaker"));
+    assert!(load_run_config(dir.path(), &isolated()).is_err());
+
+    // 0 turns the iteration timeout and the no-output watchdog off.
+    fs::write(dir.path().join(".forgerc"), "timeout_minutes = 0\n").expect("forgerc write");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("timeout disabled");
+    assert_eq!(cfg.timeout_minutes, 0);
+}
+
+#[test]
+fn set_entries_are_typed_and_checked_like_forgerc() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "no_progress_limit = 3\ncompletion_indicators = [\"DONE\"]\n",
+    )
+    .expect("forgerc write");
+    let with_set = |entries: &[&str]| {
+        load_run_config(
+            dir.path(),
+            &CliOverrides {
+                set: entries.iter().map(|e| e.to_string()).collect(),
+                timeout_minutes: Some(4),
+                ..isolated()
+            },
+        )
+    };
+
+    let cfg = with_set(&[
+        "no_progress_limit=7",
+        "auto_wait_on_rate_limit=true",
+        "completion_indicators=[\"SHIP IT\", \"DONE\"]",
+        "engine_cmd=/opt/bin/codex",
+        "timeout_minutes=30",
+    ])
+    .expect("set overrides");
+    assert_eq!(cfg.no_progress_limit, 7);
+    assert!(cfg.auto_wait_on_rate_limit);
+    assert_eq!(cfg.completion_indicators, vec!["SHIP IT", "DONE"]);
+    assert_eq!(cfg.engine_cmd, "/opt/bin/codex");
+    assert_eq!(cfg.sources.get("no_progress_limit"), ConfigSource::Set);
+    assert_eq!(ConfigSource::Set.to_string(), "--set");
+    // A dedicated flag still wins over --set.
+    assert_eq!(cfg.timeout_minutes, 4);
+    assert_eq!(cfg.sources.get("timeout_minutes"), ConfigSource::Flag);
+
+    let cfg = with_set(&[
+        "engine=custom",
+        "custom_engine.command=agent",
+        "custom_engine.args=[\"{prompt}\"]",
+    ])
+    .expect("dotted keys");
+    assert_eq!(cfg.engine_cmd, "agent");
+    assert_eq!(
+        cfg.custom_engine.expect("custom engine").args,
+        vec!["{prompt}"]
+    );
+
+    let err = with_set(&["no_progres_limit=2"]).expect_err("unknown key");
+    assert!(
+        err.to_string()
+            .contains("unknown key `no_progres_limit` (did you mean `no_progress_limit`?)"),
+        "{err}"
+    );
+    let err = with_set(&["no_progress_limit=lots"]).expect_err("wrong type");
+    assert!(
+        format!("{err:#}").contains("invalid --set value"),
+        "{err:#}"
+    );
+    let err = with_set(&["no_progress_limit"]).expect_err("missing value");
+    assert!(err.to_string().contains("expected KEY=VALUE"), "{err}");
+    let err = with_set(&["profile.quick.timeout_minutes=1"]).expect_err("profile");
+    assert!(err.to_string().contains("--profile"), "{err}");
+}
+
+#[test]
+fn engine_table_env_merges_over_shared_engine_env() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        r#"
+[engine_env]
+RUST_LOG = "warn"
+OPENCODE_LOG = "info"
+
+[engines.opencode]
+env = { OPENCODE_LOG = "debug" }
+"#,
+    )
+    .expect("forgerc write");
+    let load = |engine: EngineKind| {
+        load_run_config(
+            dir.path(),
+            &CliOverrides {
+                engine: Some(engine),
+                ..isolated()
+            },
+        )
+        .expect("load_run_config")
+    };
+
+    let opencode = load(EngineKind::OpenCode);
+    assert_eq!(opencode.engine_env["RUST_LOG"], "warn");
+    assert_eq!(opencode.engine_env["OPENCODE_LOG"], "debug");
+    assert_eq!(opencode.sources.get("engine_env"), ConfigSource::Forgerc);
+
+    let codex = load(EngineKind::Codex);
+    assert_eq!(codex.engine_env["OPENCODE_LOG"], "info");
+}
+
+#[test]
+fn engine_tables_apply_only_to_the_selected_engine() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        r#"
+engine_pre_args = ["--shared"]
+
+[engines.codex]
+cmd = "/opt/codex/bin/codex"
+pre_args = ["--sandbox", "workspace-write"]
+model = "gpt-5-codex"
+
+[engines.opencode]
+cmd = "opencode-dev"
+exec_args = ["--agent", "build"]
+model = "anthropic/claude-sonnet"
+env = { OPENCODE_LOG = "debug" }
+
+[engines.opencode.thinking_args]
+raw = ["--print-logs"]
+"#,
+    )
+    .expect("forgerc write");
+    let load = |engine: EngineKind, thinking_mode: Option<forge_config::ThinkingMode>| {
+        load_run_config(
+            dir.path(),
+            &CliOverrides {
+                engine: Some(engine),
+                thinking_mode,
+                ..isolated()
+            },
+        )
+        .expect("load_run_config")
+    };
+
+    let codex = load(EngineKind::Codex, None);
+    assert_eq!(codex.engine_cmd, "/opt/codex/bin/codex");
+    assert_eq!(codex.model.as_deref(), Some("gpt-5-codex"));
+    assert!(codex
+        .engine_pre_args
+        .starts_with(&["--sandbox".to_string(), "workspace-write".to_string()]));
+    assert!(codex
+        .engine_pre_args
+        .iter()
+        .any(|a| a == r#"model_reasoning_summary="concise""#));
+    assert_eq!(codex.sources.get("engine_cmd"), ConfigSource::Forgerc);
+
+    let opencode = load(EngineKind::OpenCode, None);
+    assert_eq!(opencode.engine_cmd, "opencode-dev");
+    assert_eq!(opencode.model.as_deref(), Some("anthropic/claude-sonnet"));
+    assert_eq!(opencode.engine_pre_args, vec!["--shared"]);
+    assert_eq!(opencode.engine_exec_args, vec!["--agent", "build"]);
+    assert_eq!(opencode.engine_env["OPENCODE_LOG"], "debug");
+
+    let raw = load(EngineKind::OpenCode, Some(forge_config::ThinkingMode::Raw));
+    assert_eq!(raw.engine_pre_args, vec!["--shared", "--print-logs"]);
+
+    let set = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            engine: Some(EngineKind::OpenCode),
+            set: vec!["engines.opencode.model=openai/gpt-5".to_string()],
+            ..isolated()
+        },
+    )
+    .expect("set engine model");
+    assert_eq!(set.model.as_deref(), Some("openai/gpt-5"));
+    assert_eq!(set.sources.get("model"), ConfigSource::Set);
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "[engines.codx]\ncmd = \"codex\"\n[engines.codex]\npre_arg = []\n",
+    )
+    .expect("forgerc write");
+    let problems = forge_config::validate_config(dir.path(), &isolated());
+    assert_eq!(
+        problems
+            .iter()
+            .map(|p| p.rsplit(": ").next().unwrap_or_default())
+            .collect::<Vec<_>>(),
+        vec![
+            "unknown key `engines.codx` (did you mean `codex`?)",
+            "unknown key `engines.codex.pre_arg` (did you mean `pre_args`?)",
+        ]
+    );
+}
+
+#[test]
+fn spec_context_sets_must_stay_inside_docs_specs() {
+    let dir = tempdir().expect("tempdir");
+    for name in ["../secrets", "a/b", ".."] {
+        fs::write(
+            dir.path().join(".forgerc"),
+            format!("spec_context_sets = [\"monitor\", \"{name}\"]\n"),
+        )
+        .expect("forgerc write");
+        let err = load_run_config(dir.path(), &isolated()).expect_err("invalid set");
+        assert!(
+            err.to_string()
+                .contains(&format!("invalid set name \"{name}\" (from .forgerc)")),
+            "{err}"
+        );
+    }
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "spec_context_sets = [\"monitor\", \"api_v2\"]\n",
+    )
+    .expect("forgerc write");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("valid sets");
+    assert_eq!(cfg.spec_context_sets, vec!["monitor", "api_v2"]);
+}
+
+#[test]
+fn entries_cover_every_resolved_field() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "analyze_model = \"gpt-5-mini\"\nanalyze_reasoning_effort = \"low\"\n",
+    )
+    .expect("forgerc write");
+
+    let cfg = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            resume: Some("thread-1".to_string()),
+            ..isolated()
+        },
+    )
+    .expect("load_run_config");
+    let entries = cfg.entries();
+    let keys = entries.iter().map(|entry| entry.key).collect::<Vec<_>>();
+
+    for key in cfg.sources.keys() {
+        assert!(keys.contains(&key), "{key} is resolved but not listed");
+    }
+    for key in &keys {
+        assert!(
+            cfg.sources.keys().any(|resolved| resolved == *key),
+            "{key} is listed but never resolved"
+        );
+    }
+    let analyze_model = entries
+        .iter()
+        .find(|entry| entry.key == "analyze_model")
+        .expect("analyze_model entry");
+    assert_eq!(analyze_model.value, "gpt-5-mini");
+    assert_eq!(analyze_model.source, ConfigSource::Forgerc);
+    assert_eq!(cfg.analyze_reasoning_effort, Some(ReasoningEffort::Low));
+}
+
+#[test]
+fn model_and_reasoning_effort_resolve_per_command() {
+    let dir = tempdir().expect("tempdir");
+    fs::write(
+        dir.path().join(".forgerc"),
+        "model = \"gpt-5\"\nreasoning_effort = \"medium\"\nanalyze_model = \"gpt-5-mini\"\n\n[engines.codex]\nreasoning_effort = \"high\"\n",
+    )
+    .expect("forgerc write");
+
+    let run = load_run_config(dir.path(), &isolated()).expect("run config");
+    assert_eq!(run.model.as_deref(), Some("gpt-5"));
+    assert_eq!(run.reasoning_effort, Some(ReasoningEffort::High));
+    assert_eq!(run.sources.get("reasoning_effort"), ConfigSource::Forgerc);
+
+    let analyze = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            analyze: true,
+            ..isolated()
+        },
+    )
+    .expect("analyze config");
+    assert_eq!(analyze.model.as_deref(), Some("gpt-5-mini"));
+    assert_eq!(analyze.reasoning_effort, Some(ReasoningEffort::High));
+
+    let flagged = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            analyze: true,
+            model: Some("o4".to_string()),
+            reasoning_effort: Some(ReasoningEffort::Low),
+            ..isolated()
+        },
+    )
+    .expect("flag config");
+    assert_eq!(flagged.model.as_deref(), Some("o4"));
+    assert_eq!(flagged.sources.get("model"), ConfigSource::Flag);
+    assert_eq!(flagged.reasoning_effort, Some(ReasoningEffort::Low));
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        "reasoning_effort = \"extreme\"\n",
+    )
+    .expect("forgerc write");
+    assert!(load_run_config(dir.path(), &isolated()).is_err());
+}
@@ -0,0 +1,525 @@
+use forge_config::EngineKind;
+use forge_engine::opencode_events::ToolPart;
+use forge_engine::{parse_codex_event, parse_opencode_event, CodexEvent, CodexItem, OpenCodeEvent};
+use forge_types::{FailedCommand, LoopCarryOver};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::process::Command;
+
+const MAX_FAILED_COMMANDS: usize = 5;
+const MAX_ERRORS: usize = 5;
+const MAX_AGENT_MESSAGE_CHARS: usize = 1200;
+const MAX_OUTPUT_TAIL_CHARS: usize = 300;
+const MAX_FILES_LISTED: usize = 30;
+
+/// Per-file `git diff --numstat` counts, plus untracked files, taken before and
+/// after a loop so the carry-over only lists what that loop touched.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WorkspaceSnapshot {
+    files: BTreeMap<String, String>,
+}
+
+impl WorkspaceSnapshot {
+    pub fn capture(cwd: &Path, runtime_dir: &Path) -> Self {
+        let runtime_prefix = runtime_dir
+            .strip_prefix(cwd)
+            .ok()
+            .map(|p| p.to_string_lossy().trim_end_matches('/').to_string())
+            .filter(|p| !p.is_empty());
+
+        let mut files = BTreeMap::new();
+        if let Some(numstat) = git_stdout(cwd, &["diff", "--numstat", "HEAD"]) {
+            for line in numstat.lines() {
+                let mut parts = line.splitn(3, '\t');
+                let (Some(added), Some(deleted), Some(path)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    continue;
+                };
+                files.insert(path.to_string(), format!("{added}\t{deleted}"));
+            }
+        }
+        if let Some(untracked) = git_stdout(cwd, &["ls-files", "--others", "--exclude-standard"]) {
+            for path in untracked.lines().filter(|l| !l.trim().is_empty()) {
+                files.insert(path.to_string(), "untracked".to_string());
+            }
+        }
+        if let Some(prefix) = runtime_prefix {
+            files.retain(|path, _| path != &prefix && !path.starts_with(&format!("{prefix}/")));
+        }
+        Self { files }
+    }
+
+    fn changed_since(&self, before: &WorkspaceSnapshot) -> Vec<String> {
+        let mut changed = self
+            .files
+            .iter()
+            .filter(|(path, stat)| before.files.get(*path) != Some(*stat))
+            .map(|(path, _)| path.clone())
+            .collect::<Vec<_>>();
+        changed.extend(
+            before
+                .files
+                .keys()
+                .filter(|path| !self.files.contains_key(*path))
+                .cloned(),
+        );
+        changed.sort();
+        changed
+    }
+
+    /// Lines this loop added and removed in `paths`. Both snapshots count
+    /// against HEAD, so the loop's share is the difference between them: a
+    /// drop in added lines means lines were removed again, and a drop in
+    /// deleted lines means lines were restored.
+    fn diff_stat(&self, before: &WorkspaceSnapshot, paths: &[String]) -> Option<String> {
+        if paths.is_empty() {
+            return None;
+        }
+        let mut added = 0_u64;
+        let mut deleted = 0_u64;
+        for path in paths {
+            let (added_after, deleted_after) = self.numstat(path);
+            let (added_before, deleted_before) = before.numstat(path);
+            added += added_after.saturating_sub(added_before)
+                + deleted_before.saturating_sub(deleted_after);
+            deleted += deleted_after.saturating_sub(deleted_before)
+                + added_before.saturating_sub(added_after);
+        }
+        Some(format!(
+            "{} file(s) changed, +{} -{}",
+            paths.len(),
+            added,
+            deleted
+        ))
+    }
+
+    /// `(added, deleted)` against HEAD; untracked, binary and unchanged
+    /// files count as 0.
+    fn numstat(&self, path: &str) -> (u64, u64) {
+        let Some(stat) = self.files.get(path) else {
+            return (0, 0);
+        };
+        let mut parts = stat.split('\t');
+        let mut count = || parts.next().and_then(|v| v.parse().ok()).unwrap_or(0);
+        (count(), count())
+    }
+}
+
+pub struct CarryOverInput<'a> {
+    /// Selects the event schema stdout is read with; custom engines only
+    /// contribute their exit status.
+    pub engine: EngineKind,
+    pub loop_number: u64,
+    pub exit_ok: bool,
+    pub timed_out: bool,
+    pub before: &'a WorkspaceSnapshot,
+    pub after: &'a WorkspaceSnapshot,
+}
+
+/// Builds the carry-over from the loop's stdout, read one line at a time so
+/// long iterations can be replayed from their output file.
+pub fn build_carry_over<I, S>(input: CarryOverInput<'_>, stdout_lines: I) -> LoopCarryOver
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut carry = LoopCarryOver {
+        loop_number: input.loop_number,
+        ..LoopCarryOver::default()
+    };
+    let mut event_files = Vec::new();
+
+    for line in stdout_lines {
+        let line = line.as_ref();
+        match input.engine {
+            EngineKind::Codex | EngineKind::Mock => {
+                if let Some(event) = parse_codex_event(line) {
+                    collect_codex_event(&mut carry, &mut event_files, event);
+                }
+            }
+            EngineKind::OpenCode => {
+                if let Some(event) = parse_opencode_event(line) {
+                    collect_opencode_event(&mut carry, &mut event_files, event);
+                }
+            }
+            EngineKind::Custom => {}
+        }
+    }
+
+    if input.timed_out {
+        carry.errors.push("iteration timed out".to_string());
+    } else if !input.exit_ok {
+        carry
+            .errors
+            .push("engine exited with a failure status".to_string());
+    }
+
+    let start = carry
+        .failed_commands
+        .len()
+        .saturating_sub(MAX_FAILED_COMMANDS);
+    carry.failed_commands.drain(..start);
+    let start = carry.errors.len().saturating_sub(MAX_ERRORS);
+    carry.errors.drain(..start);
+
+    let changed = input.after.changed_since(input.before);
+    if changed.is_empty() {
+        event_files.sort();
+        event_files.dedup();
+        carry.files_changed = event_files;
+    } else {
+        carry.diff_stat = input.after.diff_stat(input.before, &changed);
+        carry.files_changed = changed;
+    }
+
+    carry
+}
+
+fn collect_codex_event(
+    carry: &mut LoopCarryOver,
+    event_files: &mut Vec<String>,
+    event: CodexEvent,
+) {
+    match event {
+        CodexEvent::Error { message } => push_error(&mut carry.errors, &message),
+        CodexEvent::TurnFailed { error } => push_error(&mut carry.errors, &error.message),
+        CodexEvent::ItemCompleted { item } => match item {
+            CodexItem::AgentMessage { text } => {
+                carry.last_agent_message =
+                    Some(truncate_chars(text.trim(), MAX_AGENT_MESSAGE_CHARS));
+            }
+            CodexItem::CommandExecution { .. } => {
+                if let Some(failed) = failed_command(&item) {
+                    push_failed(&mut carry.failed_commands, failed);
+                }
+            }
+            CodexItem::FileChange { changes, .. } => {
+                event_files.extend(changes.into_iter().map(|change| change.path));
+            }
+            CodexItem::Error { message } => push_error(&mut carry.errors, &message),
+            _ => {}
+        },
+        _ => {}
+    }
+}
+
+fn collect_opencode_event(
+    carry: &mut LoopCarryOver,
+    event_files: &mut Vec<String>,
+    event: OpenCodeEvent,
+) {
+    match event {
+        OpenCodeEvent::Text { part, .. } if !part.text.trim().is_empty() => {
+            carry.last_agent_message =
+                Some(truncate_chars(part.text.trim(), MAX_AGENT_MESSAGE_CHARS));
+        }
+        OpenCodeEvent::ToolUse { part, .. } => {
+            if let Some(path) = part.changed_path() {
+                event_files.push(path);
+            }
+            if let Some(failed) = failed_tool(&part) {
+                push_failed(&mut carry.failed_commands, failed);
+            }
+        }
+        OpenCodeEvent::Error { error, .. } => push_error(&mut carry.errors, &error.message()),
+        _ => {}
+    }
+}
+
+pub fn render_carry_over(carry: &LoopCarryOver) -> String {
+    let mut out = format!("Previous loop carry-over (loop {}):", carry.loop_number);
+
+    if carry.files_changed.is_empty() {
+        out.push_str("\n- files changed: none");
+    } else {
+        let listed = carry
+            .files_changed
+            .iter()
+            .take(MAX_FILES_LISTED)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        let more = carry.files_changed.len().saturating_sub(MAX_FILES_LISTED);
+        out.push_str(&format!("\n- files changed: {listed}"));
+        if more > 0 {
+            out.push_str(&format!(" (+{more} more)"));
+        }
+        if let Some(stat) = &carry.diff_stat {
+            out.push_str(&format!("\n- diff stat: {stat}"));
+        }
+    }
+
+    if carry.failed_commands.is_empty() {
+        out.push_str("\n- failed commands: none");
+    } else {
+        out.push_str("\n- failed commands:");
+        for failed in &carry.failed_commands {
+            let exit = failed
+                .exit_code
+                .map(|code| format!("exit {code}"))
+                .unwrap_or_else(|| "failed".to_string());
+            out.push_str(&format!("\n  - `{}` ({exit})", failed.command));
+            if !failed.output_tail.is_empty() {
+                out.push_str(&format!("\n    output: {}", failed.output_tail));
+            }
+        }
+    }
+
+    if !carry.errors.is_empty() {
+        out.push_str(&format!("\n- errors: {}", carry.errors.join("; ")));
+    }
+
+    if let Some(message) = &carry.last_agent_message {
+        out.push_str(&format!("\n- final agent message: {message}"));
+    }
+
+    out
+}
+
+fn failed_command(item: &CodexItem) -> Option<FailedCommand> {
+    if !item.is_failed_command() {
+        return None;
+    }
+    let CodexItem::CommandExecution {
+        command,
+        aggregated_output,
+        exit_code,
+        ..
+    } = item
+    else {
+        return None;
+    };
+    Some(FailedCommand {
+        command: if command.is_empty() {
+            "-".to_string()
+        } else {
+            command.clone()
+        },
+        exit_code: *exit_code,
+        output_tail: tail_chars(aggregated_output.trim(), MAX_OUTPUT_TAIL_CHARS)
+            .replace('\n', " | "),
+    })
+}
+
+fn failed_tool(part: &ToolPart) -> Option<FailedCommand> {
+    if !part.is_failed() {
+        return None;
+    }
+    let output = part
+        .state
+        .error
+        .as_deref()
+        .or(part.state.output.as_deref())
+        .unwrap_or_default();
+    Some(FailedCommand {
+        command: part.command(),
+        exit_code: part.exit_code(),
+        output_tail: tail_chars(output.trim(), MAX_OUTPUT_TAIL_CHARS).replace('\n', " | "),
+    })
+}
+
+fn push_error(errors: &mut Vec<String>, message: &str) {
+    let text = message.trim();
+    if !text.is_empty() {
+        errors.push(truncate_chars(text, MAX_OUTPUT_TAIL_CHARS));
+        if errors.len() > MAX_ERRORS {
+            errors.remove(0);
+        }
+    }
+}
+
+fn push_failed(failed_commands: &mut Vec<FailedCommand>, failed: FailedCommand) {
+    failed_commands.push(failed);
+    if failed_commands.len() > MAX_FAILED_COMMANDS {
+        failed_commands.remove(0);
+    }
+}
+
+fn git_stdout(cwd: &Path, args: &[&str]) -> Option<String> {
+    let output = Command::new("git")
+        .args(args)
+        .current_dir(cwd)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    Some(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+fn truncate_chars(text: &str, limit: usize) -> String {
+    text.chars().take(limit).collect()
+}
+
+fn tail_chars(text: &str, limit: usize) -> String {
+    let count = text.chars().count();
+    text.chars().skip(count.saturating_sub(limit)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(empty: &WorkspaceSnapshot) -> CarryOverInput<'_> {
+        CarryOverInput {
+            engine: EngineKind::Codex,
+            loop_number: 3,
+            exit_ok: true,
+            timed_out: false,
+            before: empty,
+            after: empty,
+        }
+    }
+
+    #[test]
+    fn collects_failed_commands_and_final_message() {
+        let stdout = r#"{"type":"thread.started","thread_id":"abc"}
+{"type":"item.completed","item":{"type":"command_execution","command":"cargo test","aggregated_output":"running\ntest failed","exit_code":101,"status":"failed"}}
+{"type":"item.completed","item":{"type":"command_execution","command":"ls","aggregated_output":"a","exit_code":0,"status":"completed"}}
+{"type":"item.completed","item":{"type":"agent_message","text":"first"}}
+{"type":"item.completed","item":{"type":"agent_message","text":"fixed parser, tests still red"}}"#;
+        let empty = WorkspaceSnapshot::default();
+
+        let carry = build_carry_over(input(&empty), stdout.lines());
+
+        assert_eq!(carry.loop_number, 3);
+        assert_eq!(carry.failed_commands.len(), 1);
+        assert_eq!(carry.failed_commands[0].command, "cargo test");
+        assert_eq!(carry.failed_commands[0].exit_code, Some(101));
+        assert_eq!(
+            carry.failed_commands[0].output_tail,
+            "running | test failed"
+        );
+        assert_eq!(
+            carry.last_agent_message.as_deref(),
+            Some("fixed parser, tests still red")
+        );
+        assert!(carry.errors.is_empty());
+    }
+
+    #[test]
+    fn collects_error_events_and_file_changes() {
+        let stdout = r#"{"type":"error","message":"stream disconnected"}
+{"type":"turn.failed","error":{"message":"usage limit"}}
+{"type":"item.completed","item":{"type":"file_change","changes":[{"path":"src/b.rs","kind":"update"},{"path":"src/a.rs","kind":"add"}],"status":"completed"}}"#;
+        let empty = WorkspaceSnapshot::default();
+        let mut params = input(&empty);
+        params.timed_out = true;
+
+        let carry = build_carry_over(params, stdout.lines());
+
+        assert_eq!(
+            carry.errors,
+            vec![
+                "stream disconnected".to_string(),
+                "usage limit".to_string(),
+                "iteration timed out".to_string()
+            ]
+        );
+        assert_eq!(carry.files_changed, vec!["src/a.rs", "src/b.rs"]);
+        assert!(carry.diff_stat.is_none());
+    }
+
+    #[test]
+    fn collects_opencode_tools_errors_and_final_text() {
+        let stdout = r#"{"type":"tool_use","sessionID":"ses_1","part":{"tool":"bash","state":{"status":"completed","input":{"command":"cargo test"},"output":"1 failed","metadata":{"exit":101}}}}
+{"type":"tool_use","sessionID":"ses_1","part":{"tool":"write","state":{"status":"completed","input":{"filePath":"src/new.rs"}}}}
+{"type":"error","sessionID":"ses_1","error":{"name":"APIError","data":{"message":"overloaded"}}}
+{"type":"text","sessionID":"ses_1","part":{"text":"wrote new module"}}"#;
+        let empty = WorkspaceSnapshot::default();
+        let params = CarryOverInput {
+            engine: EngineKind::OpenCode,
+            ..input(&empty)
+        };
+
+        let carry = build_carry_over(params, stdout.lines());
+
+        assert_eq!(carry.failed_commands.len(), 1);
+        assert_eq!(carry.failed_commands[0].command, "cargo test");
+        assert_eq!(carry.failed_commands[0].exit_code, Some(101));
+        assert_eq!(carry.failed_commands[0].output_tail, "1 failed");
+        assert_eq!(carry.errors, vec!["overloaded".to_string()]);
+        assert_eq!(carry.files_changed, vec!["src/new.rs"]);
+        assert_eq!(
+            carry.last_agent_message.as_deref(),
+            Some("wrote new module")
+        );
+    }
+
+    #[test]
+    fn events_are_read_with_the_selected_engine_schema() {
+        let stdout =
+            r#"{"type":"item.completed","item":{"type":"agent_message","text":"codex says hi"}}"#;
+        let empty = WorkspaceSnapshot::default();
+
+        for engine in [EngineKind::OpenCode, EngineKind::Custom] {
+            let params = CarryOverInput {
+                engine,
+                ..input(&empty)
+            };
+            let carry = build_carry_over(params, stdout.lines());
+            assert!(carry.last_agent_message.is_none(), "{engine:?}");
+        }
+    }
+
+    #[test]
+    fn workspace_diff_lists_only_files_touched_this_loop() {
+        let before = WorkspaceSnapshot {
+            files: BTreeMap::from([
+                ("a.rs".to_string(), "1\t0".to_string()),
+                ("b.rs".to_string(), "2\t2".to_string()),
+                ("d.rs".to_string(), "4\t1".to_string()),
+            ]),
+        };
+        let after = WorkspaceSnapshot {
+            files: BTreeMap::from([
+                ("a.rs".to_string(), "1\t0".to_string()),
+                ("b.rs".to_string(), "5\t2".to_string()),
+                ("c.rs".to_string(), "untracked".to_string()),
+                ("d.rs".to_string(), "1\t0".to_string()),
+            ]),
+        };
+        let carry = build_carry_over(
+            CarryOverInput {
+                engine: EngineKind::Codex,
+                loop_number: 1,
+                exit_ok: true,
+                timed_out: false,
+                before: &before,
+                after: &after,
+            },
+            std::iter::empty::<&str>(),
+        );
+
+        assert_eq!(carry.files_changed, vec!["b.rs", "c.rs", "d.rs"]);
+        // b.rs gained 3 lines; d.rs dropped 3 added lines and restored 1.
+        assert_eq!(carry.diff_stat.as_deref(), Some("3 file(s) changed, +4 -3"));
+    }
+
+    #[test]
+    fn render_includes_every_section() {
+        let carry = LoopCarryOver {
+            loop_number: 2,
+            files_changed: vec!["src/lib.rs".to_string()],
+            diff_stat: Some("1 file(s) changed, +3 -1".to_string()),
+            failed_commands: vec![FailedCommand {
+                command: "cargo clippy".to_string(),
+                exit_code: Some(1),
+                output_tail: "warning: unused".to_string(),
+            }],
+            last_agent_message: Some("clippy still failing".to_string()),
+            errors: vec!["iteration timed out".to_string()],
+        };
+
+        let rendered = render_carry_over(&carry);
+
+        assert!(rendered.starts_with("Previous loop carry-over (loop 2):"));
+        assert!(rendered.contains("- files changed: src/lib.rs"));
+        assert!(rendered.contains("- diff stat: 1 file(s) changed, +3 -1"));
+        assert!(rendered.contains("`cargo clippy` (exit 1)"));
+        assert!(rendered.contains("output: warning: unused"));
+        assert!(rendered.contains("- errors: iteration timed out"));
+        assert!(rendered.contains("- final agent message: clippy still failing"));
+    }
+}
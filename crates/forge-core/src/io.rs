@@ -35,16 +35,6 @@ pub fn append_history(path: &Path, line: &str) -> Result<()> {
         .with_context(|| format!("failed to append {}", path.display()))
 }
 
-pub fn append_live_activity(path: &Path, text: &str) -> Result<()> {
-    let payload = serde_json::json!({
-        "item": {
-            "type": "agent_message",
-            "text": text,
-        }
-    });
-    append_history(path, &format!("{}\n", serde_json::to_string(&payload)?))
-}
-
 pub fn stamp_lines(input: &str) -> String {
     let ts = Local::now().format("%H:%M:%S").to_string();
     let mut out = String::new();
@@ -154,18 +144,6 @@ mod tests {
         assert!(result.contains("hello"));
     }
 
-    #[test]
-    fn append_live_activity_creates_json() {
-        let dir = tempdir().expect("tempdir");
-        let path = dir.path().join("live.log");
-
-        append_live_activity(&path, "test activity").expect("append");
-
-        let content = fs::read_to_string(&path).expect("read");
-        assert!(content.contains("agent_message"));
-        assert!(content.contains("test activity"));
-    }
-
     #[test]
     fn read_lines_reverse_returns_reversed() {
         let dir = tempdir().expect("tempdir");
@@ -1,36 +1,80 @@
 pub use exit_reason::*;
 pub use request_response::*;
 
+pub mod carry_over;
 pub mod circuit_breaker;
 pub mod io;
+pub mod live_log;
+pub mod plan;
+pub mod plan_history;
 pub mod prompt;
+pub mod queue;
 pub mod rate_limiter;
 pub mod status;
 
+pub use carry_over::{build_carry_over, render_carry_over, CarryOverInput, WorkspaceSnapshot};
 pub use circuit_breaker::{CircuitBreaker, CircuitBreakerAction};
 pub use io::{
-    append_history, append_live_activity, ensure_dir, read_json, read_json_or_default,
-    read_lines_reverse, write_json,
+    append_history, ensure_dir, read_json, read_json_or_default, read_lines_reverse, write_json,
 };
-pub use prompt::{analyze_plan, build_plan_prompt, PlanSummary};
+pub use live_log::{
+    current_run_segment, list_log_archives, new_run_id, rotate_live_log, LiveLogRotation,
+};
+pub use plan::{normalize_item_text, PlanDocument, PlanItem, PlanLintIssue, PlanLintKind};
+pub use plan_history::{
+    read_plan_history, read_plan_items, write_plan_history, BurndownPoint, PlanHistory,
+    PlanItemHistory, PlanProgressSummary, PlanTrend,
+};
+pub use prompt::{
+    analyze_plan, build_plan_prompt, build_plan_prompt_with_options, PlanSummary, PromptOptions,
+};
+pub use queue::{read_queue, write_queue, QueueEntry, QueueEntryState, QueuePolicy, SddQueue};
 pub use rate_limiter::{RateLimitResult, RateLimitState, RateLimiter};
 pub use status::{read_progress, read_status, write_progress, write_status};
 
-use anyhow::{Context, Result};
-use forge_engine::{create_engine, epoch_now, execute_with_engine, EngineExecParams};
-use forge_types::{CircuitState, ProgressSnapshot, RunStatus};
+use anyhow::{bail, Context, Result};
+use forge_config::RuntimeLayout;
+use forge_engine::{
+    check_compatibility, create_engine, epoch_now, execute_with_engine, EngineExecParams, LiveLog,
+};
+use forge_types::{
+    CircuitState, IterationMeta, LogLevel, OutputAnalysis, ProgressSnapshot, RunState, RunStatus,
+    STATE_SCHEMA_VERSION,
+};
 use std::fs;
-use std::path::{Path, PathBuf};
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
 use std::process;
+use std::time::SystemTime;
 
 mod exit_reason {
-    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "snake_case")]
     pub enum ExitReason {
         Completed,
         CircuitOpened,
         RateLimited,
         MaxLoopsReached,
     }
+
+    impl ExitReason {
+        pub fn as_str(self) -> &'static str {
+            match self {
+                ExitReason::Completed => "completed",
+                ExitReason::CircuitOpened => "circuit_opened",
+                ExitReason::RateLimited => "rate_limited",
+                ExitReason::MaxLoopsReached => "max_loops_reached",
+            }
+        }
+    }
+
+    impl std::fmt::Display for ExitReason {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str(self.as_str())
+        }
+    }
 }
 
 mod request_response {
@@ -55,14 +99,49 @@ mod request_response {
 }
 
 pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
-    let runtime_dir = req.cwd.join(&req.config.runtime_dir);
-    ensure_dir(&runtime_dir)?;
-    let _runner_pid_guard = RunnerPidGuard::create(&runtime_dir)?;
+    let layout = RuntimeLayout::from_config(&req.cwd, &req.config);
+    ensure_dir(layout.root())?;
+    let _runner_pid_guard = RunnerPidGuard::create(&layout)?;
+
+    let rotation = LiveLogRotation::from_config(&req.config);
+    rotate_live_log(&layout, &rotation, SystemTime::now())?;
+    let run_id = new_run_id();
+    let live_log = LiveLog::new(layout.live_log()).with_run_id(&run_id);
+    live_log::write_run_started(&live_log)?;
+
+    let outcome = run_iterations(&req, &layout, &rotation, &live_log, &run_id);
+    let finished = match &outcome {
+        Ok(outcome) => outcome.status.state.to_string(),
+        Err(err) => format!("error: {err}"),
+    };
+    // The boundary line is diagnostic; failing to write it must not replace
+    // the run's own outcome.
+    if let Err(err) = live_log::write_run_finished(&live_log, &finished) {
+        eprintln!("warning: failed to record run end in live log: {err:#}");
+    }
+    outcome
+}
 
-    let engine = create_engine(req.config.engine);
-    let previous_status: RunStatus = read_json_or_default(&runtime_dir.join("status.json"));
+fn run_iterations(
+    req: &RunRequest,
+    layout: &RuntimeLayout,
+    rotation: &LiveLogRotation,
+    live_log: &LiveLog,
+    run_id: &str,
+) -> Result<RunOutcome> {
+    let mut engine = create_engine(&req.config)?;
+    engine.detect_version();
+    let compat = check_compatibility(engine.as_ref(), &req.config);
+    if compat.is_refused() {
+        bail!("{}", compat.warnings().join("; "));
+    }
+    for warning in compat.warnings() {
+        live_log.forge(LogLevel::Warn, &warning)?;
+    }
+    let previous_status: RunStatus = read_json_or_default(&layout.status_file());
     let mut status = RunStatus {
-        state: "running".to_string(),
+        schema_version: STATE_SCHEMA_VERSION,
+        state: RunState::Running,
         thinking_mode: req.config.thinking_mode.as_str().to_string(),
         run_started_at_epoch: epoch_now(),
         current_loop: 0,
@@ -71,10 +150,20 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         completion_indicators: 0,
         exit_signal_seen: false,
         session_id: previous_status.session_id,
+        run_id: live_log.run_id().map(str::to_string),
         circuit_state: CircuitState::Closed,
         current_loop_started_at_epoch: 0,
         last_heartbeat_at_epoch: 0,
         updated_at_epoch: epoch_now(),
+        engine: engine.name().to_string(),
+        profile: req.config.profile.clone(),
+        model: req.config.model.clone(),
+        reasoning_effort: req
+            .config
+            .reasoning_effort
+            .map(|effort| effort.as_str().to_string()),
+        last_token_usage: None,
+        extra: Default::default(),
     };
     let mut progress = ProgressSnapshot {
         updated_at_epoch: epoch_now(),
@@ -83,11 +172,12 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
     let mut circuit = CircuitBreaker::new(req.config.no_progress_limit);
 
     status.circuit_state = circuit.state.state.clone();
-    write_json(&runtime_dir.join("status.json"), &status)?;
-    write_json(&runtime_dir.join("progress.json"), &progress)?;
-    write_json(&runtime_dir.join(".circuit_breaker_state"), &circuit.state)?;
+    write_json(&layout.status_file(), &status)?;
+    write_json(&layout.progress_file(), &progress)?;
+    write_json(&layout.circuit_breaker_state_file(), &circuit.state)?;
 
     let rate_limiter = RateLimiter::new(req.config.max_calls_per_hour);
+    let prompt_options = PromptOptions::from_config(&req.config);
     let mut loop_count = 0_u64;
 
     while loop_count < req.max_loops {
@@ -95,19 +185,23 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         status.current_loop = loop_count;
         status.current_loop_started_at_epoch = epoch_now();
         status.updated_at_epoch = epoch_now();
-        write_json(&runtime_dir.join("status.json"), &status)?;
+        write_json(&layout.status_file(), &status)?;
         progress.last_summary = format!("loop {} started: invoking {}", loop_count, engine.name());
         progress.updated_at_epoch = epoch_now();
-        write_json(&runtime_dir.join("progress.json"), &progress)?;
-        append_live_activity(
-            &runtime_dir.join("live.log"),
+        write_json(&layout.progress_file(), &progress)?;
+        if rotate_live_log(layout, rotation, SystemTime::now())? {
+            live_log::write_run_continued(live_log)?;
+        }
+        let loop_log = live_log.for_loop(loop_count);
+        loop_log.forge(
+            LogLevel::Info,
             &format!("loop {}: {} exec started", loop_count, engine.name()),
         )?;
 
-        let rate = rate_limiter.check_and_increment(&runtime_dir, epoch_now())?;
+        let rate = rate_limiter.check_and_increment(layout, epoch_now())?;
         if !rate.allowed {
-            finalize_run_status(&mut status, "rate_limited");
-            write_json(&runtime_dir.join("status.json"), &status)?;
+            finalize_run_status(&mut status, RunState::RateLimited);
+            write_json(&layout.status_file(), &status)?;
             if req.config.auto_wait_on_rate_limit {
                 std::thread::sleep(std::time::Duration::from_secs(
                     req.config.sleep_on_rate_limit_secs,
@@ -122,21 +216,38 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         }
 
         status.last_heartbeat_at_epoch = epoch_now();
-        write_json(&runtime_dir.join("status.json"), &status)?;
+        write_json(&layout.status_file(), &status)?;
+
+        ensure_dir(&layout.iteration_dir(run_id, loop_count))?;
+        write_json(
+            &layout.iteration_meta_file(run_id, loop_count),
+            &IterationMeta {
+                loop_number: loop_count,
+                run_id: status.run_id.clone(),
+                engine: status.engine.clone(),
+                model: status.model.clone(),
+                reasoning_effort: status.reasoning_effort.clone(),
+                thinking_mode: status.thinking_mode.clone(),
+                started_at_epoch: status.current_loop_started_at_epoch,
+            },
+        )?;
 
-        let prompt = build_plan_prompt(&req.cwd);
+        let prompt = build_plan_prompt_with_options(&req.cwd, layout, &prompt_options);
+        let workspace_before = WorkspaceSnapshot::capture(&req.cwd, layout.root());
+        let plan_before = read_plan_items(layout);
         let result = execute_with_engine(
             engine.as_ref(),
             EngineExecParams {
                 cwd: &req.cwd,
                 config: &req.config,
                 prompt,
-                live_log_path: &runtime_dir.join("live.log"),
+                live_log: &loop_log,
+                output_dir: &layout.iteration_dir(run_id, loop_count),
             },
             || {
                 status.last_heartbeat_at_epoch = epoch_now();
                 status.updated_at_epoch = epoch_now();
-                write_json(&runtime_dir.join("status.json"), &status)
+                write_json(&layout.status_file(), &status)
             },
         )?;
 
@@ -147,37 +258,42 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         } else {
             "failed"
         };
-        append_live_activity(
-            &runtime_dir.join("live.log"),
+        let level = if result.exit_ok {
+            LogLevel::Info
+        } else {
+            LogLevel::Error
+        };
+        loop_log.forge(
+            level,
             &format!("loop {}: {} exec {}", loop_count, engine.name(), end_state),
         )?;
 
-        let analysis = engine.parse_output(
-            &result.stdout,
-            &result.stderr,
-            &req.config.completion_indicators,
-        );
+        let analysis = result.analysis.clone();
 
+        status.last_token_usage = analysis.token_usage.clone();
         if let Some(session_id) = analysis.session_id.clone() {
             status.session_id = Some(session_id.clone());
-            fs::write(runtime_dir.join(".session_id"), session_id)
+            fs::write(layout.session_id_file(), session_id)
                 .context("failed to write session id")?;
         }
 
-        let has_progress =
-            analysis.has_progress_hint || (result.exit_ok && (!result.stdout.trim().is_empty()));
+        let mut plan_history = read_plan_history(layout);
+        plan_history.record_loop(
+            &plan_before,
+            &read_plan_items(layout),
+            loop_count,
+            epoch_now(),
+        );
+        write_plan_history(layout, &plan_history)?;
+
+        let has_progress = analysis.has_progress_hint
+            || (result.exit_ok && (!result.stdout_tail.trim().is_empty()));
 
         // Early completion check before mutating circuit state
-        let completed_condition_early = analysis.exit_signal_true
-            && (analysis.completion_indicators > 0
-                || result
-                    .stdout
-                    .to_ascii_lowercase()
-                    .contains("status: complete")
-                || result.stdout.to_ascii_lowercase().contains("task_complete"));
+        let completed_condition_early = is_completed(&analysis, &result.stdout_tail);
         if completed_condition_early {
-            finalize_run_status(&mut status, "completed");
-            write_json(&runtime_dir.join("status.json"), &status)?;
+            finalize_run_status(&mut status, RunState::Completed);
+            write_json(&layout.status_file(), &status)?;
             return Ok(RunOutcome {
                 reason: ExitReason::Completed,
                 loops_executed: loop_count,
@@ -197,7 +313,26 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
             progress.loops_without_progress += 1;
         }
 
-        progress.last_summary = summarize_output(&result.stdout, &result.stderr);
+        let workspace_after = WorkspaceSnapshot::capture(&req.cwd, layout.root());
+        let stdout_file = fs::File::open(&result.stdout_path)
+            .with_context(|| format!("failed to open {}", result.stdout_path.display()))?;
+        let carry_over = build_carry_over(
+            CarryOverInput {
+                engine: req.config.engine,
+                loop_number: loop_count,
+                exit_ok: result.exit_ok,
+                timed_out: result.timed_out,
+                before: &workspace_before,
+                after: &workspace_after,
+            },
+            BufReader::new(stdout_file).lines().map_while(Result::ok),
+        );
+        progress.last_summary = carry_over
+            .last_agent_message
+            .as_deref()
+            .map(|message| message.chars().take(180).collect())
+            .unwrap_or_else(|| summarize_output(&result.stdout_tail, &result.stderr_tail));
+        progress.carry_over = Some(carry_over);
         progress.updated_at_epoch = epoch_now();
 
         status.total_loops_executed += 1;
@@ -213,11 +348,11 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         status.circuit_state = circuit.state.state.clone();
         status.updated_at_epoch = epoch_now();
 
-        write_json(&runtime_dir.join("progress.json"), &progress)?;
-        write_json(&runtime_dir.join("status.json"), &status)?;
-        write_json(&runtime_dir.join(".circuit_breaker_state"), &circuit.state)?;
+        write_json(&layout.progress_file(), &progress)?;
+        write_json(&layout.status_file(), &status)?;
+        write_json(&layout.circuit_breaker_state_file(), &circuit.state)?;
         append_history(
-            &runtime_dir.join(".circuit_breaker_history"),
+            &layout.circuit_breaker_history_file(),
             &format!(
                 "{} loop={} state={:?} no_progress={}\n",
                 epoch_now(),
@@ -227,18 +362,10 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
             ),
         )?;
 
-        // Consider completed when EXIT_SIGNAL is true and we have explicit completion indicators
-        // or when the engine outputs a clear completion marker like "STATUS: COMPLETE".
-        let completed_condition = analysis.exit_signal_true
-            && (analysis.completion_indicators > 0
-                || result
-                    .stdout
-                    .to_ascii_lowercase()
-                    .contains("status: complete")
-                || result.stdout.to_ascii_lowercase().contains("task_complete"));
+        let completed_condition = is_completed(&analysis, &result.stdout_tail);
         if completed_condition {
-            finalize_run_status(&mut status, "completed");
-            write_json(&runtime_dir.join("status.json"), &status)?;
+            finalize_run_status(&mut status, RunState::Completed);
+            write_json(&layout.status_file(), &status)?;
             return Ok(RunOutcome {
                 reason: ExitReason::Completed,
                 loops_executed: loop_count,
@@ -247,8 +374,8 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         }
 
         if circuit_action == CircuitBreakerAction::OpenCircuit {
-            finalize_run_status(&mut status, "circuit_open");
-            write_json(&runtime_dir.join("status.json"), &status)?;
+            finalize_run_status(&mut status, RunState::CircuitOpen);
+            write_json(&layout.status_file(), &status)?;
             return Ok(RunOutcome {
                 reason: ExitReason::CircuitOpened,
                 loops_executed: loop_count,
@@ -257,8 +384,8 @@ pub fn run_loop(req: RunRequest) -> Result<RunOutcome> {
         }
     }
 
-    finalize_run_status(&mut status, "max_loops_reached");
-    write_json(&runtime_dir.join("status.json"), &status)?;
+    finalize_run_status(&mut status, RunState::MaxLoopsReached);
+    write_json(&layout.status_file(), &status)?;
 
     Ok(RunOutcome {
         reason: ExitReason::MaxLoopsReached,
@@ -272,8 +399,8 @@ struct RunnerPidGuard {
 }
 
 impl RunnerPidGuard {
-    fn create(runtime_dir: &Path) -> Result<Self> {
-        let path = runtime_dir.join(".runner_pid");
+    fn create(layout: &RuntimeLayout) -> Result<Self> {
+        let path = layout.runner_pid_file();
         fs::write(&path, process::id().to_string())
             .with_context(|| format!("failed to write {}", path.display()))?;
         Ok(Self { path })
@@ -286,14 +413,29 @@ impl Drop for RunnerPidGuard {
     }
 }
 
-fn finalize_run_status(status: &mut RunStatus, state: &str) {
-    status.state = state.to_string();
+fn finalize_run_status(status: &mut RunStatus, state: RunState) {
+    status.state = state;
     status.current_loop = 0;
     status.current_loop_started_at_epoch = 0;
     status.last_heartbeat_at_epoch = 0;
     status.updated_at_epoch = epoch_now();
 }
 
+/// Consider completed when EXIT_SIGNAL is true and we have explicit completion
+/// indicators, or a clear completion marker in the final agent message (the
+/// whole output when the engine does not expose one).
+fn is_completed(analysis: &OutputAnalysis, stdout: &str) -> bool {
+    let text = analysis
+        .final_message
+        .as_deref()
+        .unwrap_or(stdout)
+        .to_ascii_lowercase();
+    analysis.exit_signal_true
+        && (analysis.completion_indicators > 0
+            || text.contains("status: complete")
+            || text.contains("task_complete"))
+}
+
 fn summarize_output(stdout: &str, stderr: &str) -> String {
     let joined = format!("{} {}", stdout.trim(), stderr.trim());
     let trimmed = joined.trim();
@@ -326,19 +468,35 @@ mod tests {
         assert_eq!(result, "no output");
     }
 
+    #[test]
+    fn completion_marker_only_counts_in_final_message() {
+        let quoted = OutputAnalysis {
+            exit_signal_true: true,
+            final_message: Some("EXIT_SIGNAL: true, still going".to_string()),
+            ..OutputAnalysis::default()
+        };
+        assert!(!is_completed(&quoted, "cat prompt: STATUS: COMPLETE"));
+
+        let plain = OutputAnalysis {
+            exit_signal_true: true,
+            ..OutputAnalysis::default()
+        };
+        assert!(is_completed(&plain, "EXIT_SIGNAL: true\nSTATUS: COMPLETE"));
+    }
+
     #[test]
     fn finalize_run_status_sets_state() {
         let mut status = RunStatus {
-            state: "running".to_string(),
+            state: RunState::Running,
             current_loop: 5,
             current_loop_started_at_epoch: 1000,
             last_heartbeat_at_epoch: 1100,
             ..RunStatus::default()
         };
 
-        finalize_run_status(&mut status, "completed");
+        finalize_run_status(&mut status, RunState::Completed);
 
-        assert_eq!(status.state, "completed");
+        assert_eq!(status.state, RunState::Completed);
         assert_eq!(status.current_loop, 0);
         assert_eq!(status.current_loop_started_at_epoch, 0);
         assert_eq!(status.last_heartbeat_at_epoch, 0);
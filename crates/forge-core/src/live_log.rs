@@ -0,0 +1,344 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use forge_config::{RunConfig, RuntimeLayout};
+use forge_engine::LiveLog;
+use forge_types::{LiveLogEntry, LogLevel, LogSource};
+use serde_json::{json, Value};
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::SystemTime;
+
+/// When `live.log` is archived and how many archives are kept.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LiveLogRotation {
+    pub max_bytes: u64,
+    pub max_age_secs: u64,
+    pub retention: usize,
+}
+
+impl LiveLogRotation {
+    pub fn from_config(config: &RunConfig) -> Self {
+        Self {
+            max_bytes: config.live_log_max_bytes,
+            max_age_secs: config.live_log_max_age_secs,
+            retention: config.live_log_retention,
+        }
+    }
+
+    fn is_due(&self, len: u64, age_secs: u64) -> bool {
+        (self.max_bytes > 0 && len >= self.max_bytes)
+            || (self.max_age_secs > 0 && age_secs >= self.max_age_secs)
+    }
+}
+
+/// `<UTC timestamp>-<pid>`, unique per run on one machine. Later runs in
+/// the same process (`forge queue run`) add `-<n>`, since they can start
+/// within the same second.
+pub fn new_run_id() -> String {
+    static RUNS: AtomicU64 = AtomicU64::new(0);
+    let id = format!("{}-{}", Utc::now().format("%Y%m%dT%H%M%SZ"), process::id());
+    match RUNS.fetch_add(1, Ordering::Relaxed) {
+        0 => id,
+        n => format!("{id}-{n}"),
+    }
+}
+
+pub fn write_run_started(log: &LiveLog) -> Result<()> {
+    write_run_boundary(log, "started", None)
+}
+
+/// First line of a new segment when a run rotates the log mid-way.
+pub fn write_run_continued(log: &LiveLog) -> Result<()> {
+    write_run_boundary(log, "continued", None)
+}
+
+pub fn write_run_finished(log: &LiveLog, outcome: &str) -> Result<()> {
+    write_run_boundary(log, "finished", Some(outcome))
+}
+
+fn write_run_boundary(log: &LiveLog, event: &str, detail: Option<&str>) -> Result<()> {
+    let mut payload = json!({ "type": "forge.run", "event": event });
+    if let Some(detail) = detail {
+        payload["detail"] = Value::String(detail.to_string());
+    }
+    log.append(LogSource::Forge, LogLevel::Info, payload)
+}
+
+/// The part of a live log written by the latest run, from its last
+/// `started` or `continued` boundary. Logs without boundaries are returned
+/// whole.
+pub fn current_run_segment(raw: &str) -> &str {
+    let line_starts = std::iter::once(0)
+        .chain(raw.match_indices('\n').map(|(pos, _)| pos + 1))
+        .filter(|start| *start < raw.len())
+        .collect::<Vec<_>>();
+    line_starts
+        .into_iter()
+        .rev()
+        .find(|start| {
+            let line = raw[*start..].lines().next().unwrap_or_default();
+            line.contains("forge.run")
+                && serde_json::from_str::<LiveLogEntry>(line)
+                    .is_ok_and(|entry| matches!(entry.run_event(), Some("started" | "continued")))
+        })
+        .map_or(raw, |start| &raw[start..])
+}
+
+/// Archives `live.log` into `<runtime>/logs` when it is over the size or
+/// age limit, then prunes old archives. Returns whether it rotated; with a
+/// retention of 0 the segment is dropped instead of archived.
+pub fn rotate_live_log(
+    layout: &RuntimeLayout,
+    rotation: &LiveLogRotation,
+    now: SystemTime,
+) -> Result<bool> {
+    let path = layout.live_log();
+    let Ok(meta) = fs::metadata(&path) else {
+        return Ok(false);
+    };
+    if meta.len() == 0 {
+        return Ok(false);
+    }
+    // Appends refresh the mtime, so the segment's age comes from its first
+    // entry; files without one fall back to the filesystem times.
+    let opened = segment_started(&path)
+        .or_else(|| meta.created().or_else(|_| meta.modified()).ok())
+        .unwrap_or(now);
+    let age_secs = now
+        .duration_since(opened)
+        .map(|age| age.as_secs())
+        .unwrap_or(0);
+    if !rotation.is_due(meta.len(), age_secs) {
+        return Ok(false);
+    }
+
+    let archive_dir = layout.log_archive_dir();
+    fs::create_dir_all(&archive_dir)
+        .with_context(|| format!("failed to create {}", archive_dir.display()))?;
+    if rotation.retention > 0 {
+        compress_into(&path, &unused_archive_path(&archive_dir, now))?;
+    }
+    fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+    prune_archives(&archive_dir, rotation.retention)?;
+    Ok(true)
+}
+
+/// Timestamp of the first entry in the live log at `path`.
+fn segment_started(path: &Path) -> Option<SystemTime> {
+    let mut first = String::new();
+    BufReader::new(File::open(path).ok()?)
+        .read_line(&mut first)
+        .ok()?;
+    let entry = serde_json::from_str::<LiveLogEntry>(&first).ok()?;
+    let ts = DateTime::parse_from_rfc3339(&entry.ts).ok()?;
+    Some(ts.with_timezone(&Utc).into())
+}
+
+/// Archives in `dir`, oldest first.
+pub fn list_log_archives(dir: &Path) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut archives = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter_map(|path| {
+            let key = archive_sort_key(path.file_name()?.to_str()?)?;
+            Some((key, path))
+        })
+        .collect::<Vec<_>>();
+    archives.sort();
+    archives.into_iter().map(|(_, path)| path).collect()
+}
+
+/// `(timestamp, collision suffix)` from `live-<timestamp>[-<n>].log.gz`.
+fn archive_sort_key(name: &str) -> Option<(String, u32)> {
+    let stem = name.strip_prefix("live-")?.strip_suffix(".log.gz")?;
+    match stem.split_once('-') {
+        Some((stamp, suffix)) => Some((stamp.to_string(), suffix.parse().ok()?)),
+        None => Some((stem.to_string(), 0)),
+    }
+}
+
+/// Next archive name for `now`. Archives from the same second get an
+/// increasing suffix so they still sort after the ones already pruned to.
+fn unused_archive_path(dir: &Path, now: SystemTime) -> PathBuf {
+    let stamp = DateTime::<Utc>::from(now)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string();
+    let next = list_log_archives(dir)
+        .iter()
+        .filter_map(|path| archive_sort_key(path.file_name()?.to_str()?))
+        .filter(|(existing, _)| *existing == stamp)
+        .map(|(_, suffix)| suffix + 1)
+        .max();
+    match next {
+        Some(suffix) => dir.join(format!("live-{stamp}-{suffix}.log.gz")),
+        None => dir.join(format!("live-{stamp}.log.gz")),
+    }
+}
+
+fn compress_into(source: &Path, archive: &Path) -> Result<()> {
+    let mut input =
+        File::open(source).with_context(|| format!("failed to open {}", source.display()))?;
+    let output =
+        File::create(archive).with_context(|| format!("failed to create {}", archive.display()))?;
+    let mut encoder = GzEncoder::new(output, Compression::default());
+    io::copy(&mut input, &mut encoder)
+        .and_then(|_| encoder.finish())
+        .with_context(|| format!("failed to write {}", archive.display()))?;
+    Ok(())
+}
+
+fn prune_archives(dir: &Path, retention: usize) -> Result<()> {
+    let archives = list_log_archives(dir);
+    let excess = archives.len().saturating_sub(retention);
+    for path in archives.into_iter().take(excess) {
+        fs::remove_file(&path).with_context(|| format!("failed to remove {}", path.display()))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::GzDecoder;
+    use std::io::Read;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn rotation(max_bytes: u64, max_age_secs: u64, retention: usize) -> LiveLogRotation {
+        LiveLogRotation {
+            max_bytes,
+            max_age_secs,
+            retention,
+        }
+    }
+
+    #[test]
+    fn small_fresh_log_is_left_in_place() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        fs::write(layout.live_log(), "line\n").expect("write");
+
+        let rotated =
+            rotate_live_log(&layout, &rotation(1024, 3600, 3), SystemTime::now()).expect("rotate");
+
+        assert!(!rotated);
+        assert!(layout.live_log().exists());
+    }
+
+    #[test]
+    fn oversized_log_is_compressed_and_removed() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        let body = "x".repeat(2048) + "\n";
+        fs::write(layout.live_log(), &body).expect("write");
+
+        assert!(
+            rotate_live_log(&layout, &rotation(1024, 0, 3), SystemTime::now()).expect("rotate")
+        );
+
+        assert!(!layout.live_log().exists());
+        let archives = list_log_archives(&layout.log_archive_dir());
+        assert_eq!(archives.len(), 1);
+        let archive = &archives[0];
+        let mut restored = String::new();
+        GzDecoder::new(File::open(archive).expect("open"))
+            .read_to_string(&mut restored)
+            .expect("decompress");
+        assert_eq!(restored, body);
+    }
+
+    #[test]
+    fn old_log_rotates_by_age() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        fs::write(layout.live_log(), "line\n").expect("write");
+        let later = SystemTime::now() + Duration::from_secs(7200);
+
+        assert!(rotate_live_log(&layout, &rotation(0, 3600, 3), later).expect("rotate"));
+    }
+
+    #[test]
+    fn age_is_measured_from_the_first_entry_not_the_last_write() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        let started = chrono::Local::now() - chrono::Duration::hours(2);
+        let first = serde_json::to_string(&LiveLogEntry {
+            ts: started.to_rfc3339(),
+            run_id: None,
+            loop_number: None,
+            source: LogSource::Forge,
+            level: LogLevel::Info,
+            payload: json!({ "type": "forge.run", "event": "started" }),
+        })
+        .expect("entry");
+        fs::write(layout.live_log(), first + "\n").expect("write");
+        LiveLog::new(layout.live_log())
+            .forge(LogLevel::Info, "just appended")
+            .expect("append");
+
+        assert!(
+            rotate_live_log(&layout, &rotation(0, 3600, 3), SystemTime::now()).expect("rotate")
+        );
+    }
+
+    #[test]
+    fn retention_keeps_the_newest_archives() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        let archive_dir = layout.log_archive_dir();
+        let mut archived = Vec::new();
+        for _ in 0..4 {
+            fs::write(layout.live_log(), "line\n").expect("write");
+            rotate_live_log(&layout, &rotation(1, 0, 2), SystemTime::now()).expect("rotate");
+            archived.extend(list_log_archives(&archive_dir).pop());
+        }
+
+        assert_eq!(list_log_archives(&archive_dir), archived[2..].to_vec());
+    }
+
+    #[test]
+    fn zero_retention_discards_the_segment() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        fs::write(layout.live_log(), "line\n").expect("write");
+
+        assert!(rotate_live_log(&layout, &rotation(1, 0, 0), SystemTime::now()).expect("rotate"));
+
+        assert!(!layout.live_log().exists());
+        assert!(list_log_archives(&layout.log_archive_dir()).is_empty());
+    }
+
+    #[test]
+    fn current_segment_starts_at_the_last_boundary() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        let path = layout.live_log();
+        let first = LiveLog::new(&path).with_run_id("run-a");
+        write_run_started(&first).expect("start");
+        first
+            .forge(LogLevel::Info, "first run output")
+            .expect("append");
+        write_run_finished(&first, "completed").expect("finish");
+        let second = LiveLog::new(&path).with_run_id("run-b");
+        write_run_started(&second).expect("start");
+        second
+            .forge(LogLevel::Info, "second run output")
+            .expect("append");
+
+        let raw = fs::read_to_string(&path).expect("read");
+        let segment = current_run_segment(&raw);
+
+        assert!(segment.contains(r#""run_id":"run-b""#));
+        assert!(segment.contains("second run output"));
+        assert!(!segment.contains("first run output"));
+        assert_eq!(current_run_segment("no boundaries\n"), "no boundaries\n");
+    }
+}
@@ -0,0 +1,375 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanItem {
+    /// 1-based position among the plan's checklist items.
+    pub id: usize,
+    /// 1-based line number in the plan file.
+    pub line: usize,
+    pub checked: bool,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanLintKind {
+    NoChecklistItems,
+    DuplicateItem,
+    EmptyItem,
+    ItemInCodeBlock,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PlanLintIssue {
+    pub kind: PlanLintKind,
+    pub line: Option<usize>,
+    pub message: String,
+}
+
+/// A markdown plan kept line by line so edits leave everything that is not a
+/// checklist item untouched, line endings included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PlanDocument {
+    lines: Vec<String>,
+    /// `"\r\n"` when the file used CRLF, otherwise `"\n"`.
+    line_ending: &'static str,
+    trailing_newline: bool,
+}
+
+struct ParsedLine {
+    index: usize,
+    checked: bool,
+    indent: String,
+    text: String,
+    in_code_block: bool,
+}
+
+impl PlanDocument {
+    pub fn parse(raw: &str) -> Self {
+        Self {
+            lines: raw.lines().map(ToString::to_string).collect(),
+            line_ending: if raw.contains("\r\n") { "\r\n" } else { "\n" },
+            trailing_newline: raw.ends_with('\n'),
+        }
+    }
+
+    pub fn render(&self) -> String {
+        let mut out = self.lines.join(self.line_ending);
+        if self.trailing_newline {
+            out.push_str(self.line_ending);
+        }
+        out
+    }
+
+    pub fn items(&self) -> Vec<PlanItem> {
+        self.parsed_lines()
+            .into_iter()
+            .filter(|line| !line.in_code_block)
+            .enumerate()
+            .map(|(idx, line)| PlanItem {
+                id: idx + 1,
+                line: line.index + 1,
+                checked: line.checked,
+                text: line.text,
+            })
+            .collect()
+    }
+
+    pub fn add(&mut self, text: &str, after: Option<usize>) -> Result<usize> {
+        let text = text.trim();
+        if text.is_empty() {
+            bail!("plan item text must not be empty");
+        }
+        let items = self.items();
+        let new_line = |indent: &str| format!("{indent}- [ ] {text}");
+
+        let id = match after {
+            Some(after_id) => {
+                let anchor = self.item_line(after_id)?;
+                let indent = self.indent_of(anchor);
+                self.lines.insert(anchor + 1, new_line(&indent));
+                after_id + 1
+            }
+            None => match items.last() {
+                Some(last) => {
+                    let anchor = last.line - 1;
+                    let indent = self.indent_of(anchor);
+                    self.lines.insert(anchor + 1, new_line(&indent));
+                    items.len() + 1
+                }
+                None => {
+                    if self.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                        self.lines.push(String::new());
+                    }
+                    self.lines.push(new_line(""));
+                    1
+                }
+            },
+        };
+        Ok(id)
+    }
+
+    pub fn set_checked(&mut self, id: usize, checked: bool) -> Result<()> {
+        let index = self.item_line(id)?;
+        let parsed = parse_checklist_line(&self.lines[index]).expect("item line is a checklist");
+        let mark = if checked { "x" } else { " " };
+        self.lines[index] = format!("{}- [{mark}] {}", parsed.0, parsed.2);
+        Ok(())
+    }
+
+    /// Moves item `id` so it ends up at checklist position `to`.
+    pub fn move_item(&mut self, id: usize, to: usize) -> Result<()> {
+        let count = self.items().len();
+        if to == 0 || to > count {
+            bail!("target position {} is out of range (1..={})", to, count);
+        }
+        let index = self.item_line(id)?;
+        if id == to {
+            return Ok(());
+        }
+        let line = self.lines.remove(index);
+        let remaining = self.items();
+        if to > remaining.len() {
+            let anchor = remaining.last().map(|item| item.line).unwrap_or(0);
+            self.lines.insert(anchor, line);
+        } else {
+            self.lines.insert(remaining[to - 1].line - 1, line);
+        }
+        Ok(())
+    }
+
+    pub fn lint(&self) -> Vec<PlanLintIssue> {
+        let mut issues = Vec::new();
+        let parsed = self.parsed_lines();
+
+        if !parsed.iter().any(|line| !line.in_code_block) {
+            issues.push(PlanLintIssue {
+                kind: PlanLintKind::NoChecklistItems,
+                line: None,
+                message: "plan has no checklist items (`- [ ] ...`)".to_string(),
+            });
+        }
+
+        let mut seen: Vec<(String, usize)> = Vec::new();
+        for line in &parsed {
+            let line_no = line.index + 1;
+            if line.in_code_block {
+                issues.push(PlanLintIssue {
+                    kind: PlanLintKind::ItemInCodeBlock,
+                    line: Some(line_no),
+                    message: format!("checklist item inside a code block: {}", line.text),
+                });
+                continue;
+            }
+            if line.text.is_empty() {
+                issues.push(PlanLintIssue {
+                    kind: PlanLintKind::EmptyItem,
+                    line: Some(line_no),
+                    message: "checklist item has no text".to_string(),
+                });
+                continue;
+            }
+            let key = normalize_item_text(&line.text);
+            if let Some((_, first_line)) = seen.iter().find(|(k, _)| *k == key) {
+                issues.push(PlanLintIssue {
+                    kind: PlanLintKind::DuplicateItem,
+                    line: Some(line_no),
+                    message: format!("duplicate of item on line {}: {}", first_line, line.text),
+                });
+            } else {
+                seen.push((key, line_no));
+            }
+        }
+        issues
+    }
+
+    fn parsed_lines(&self) -> Vec<ParsedLine> {
+        let mut out = Vec::new();
+        let mut in_code_block = false;
+        for (index, line) in self.lines.iter().enumerate() {
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+                continue;
+            }
+            if let Some((indent, checked, text)) = parse_checklist_line(line) {
+                out.push(ParsedLine {
+                    index,
+                    checked,
+                    indent,
+                    text,
+                    in_code_block,
+                });
+            }
+        }
+        out
+    }
+
+    fn item_line(&self, id: usize) -> Result<usize> {
+        let items = self.items();
+        match items.iter().find(|item| item.id == id) {
+            Some(item) => Ok(item.line - 1),
+            None => bail!("plan item {} not found ({} item(s))", id, items.len()),
+        }
+    }
+
+    fn indent_of(&self, index: usize) -> String {
+        self.parsed_lines()
+            .into_iter()
+            .find(|line| line.index == index)
+            .map(|line| line.indent)
+            .unwrap_or_default()
+    }
+}
+
+/// Lowercased, whitespace-collapsed item text used to match items across edits.
+pub fn normalize_item_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn parse_checklist_line(line: &str) -> Option<(String, bool, String)> {
+    let trimmed = line.trim_start();
+    let indent = line[..line.len() - trimmed.len()].to_string();
+    let rest = trimmed
+        .strip_prefix("- [")
+        .or_else(|| trimmed.strip_prefix("* ["))?;
+    let mut chars = rest.chars();
+    let mark = chars.next()?;
+    let rest = chars.as_str().strip_prefix(']')?;
+    let checked = match mark {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((indent, checked, rest.trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAN: &str =
+        "# Plan\n\n## Tasks\n- [ ] Task A\n- [x] Task B\n  - [ ] Task C\n\nNotes here\n";
+
+    fn texts(doc: &PlanDocument) -> Vec<String> {
+        doc.items().into_iter().map(|item| item.text).collect()
+    }
+
+    #[test]
+    fn items_are_numbered_in_order() {
+        let doc = PlanDocument::parse(PLAN);
+        let items = doc.items();
+
+        assert_eq!(items.len(), 3);
+        assert_eq!(items[0].id, 1);
+        assert_eq!(items[0].line, 4);
+        assert!(!items[0].checked);
+        assert!(items[1].checked);
+        assert_eq!(items[2].text, "Task C");
+    }
+
+    #[test]
+    fn add_appends_after_last_item_or_anchor() {
+        let mut doc = PlanDocument::parse(PLAN);
+        assert_eq!(doc.add("Task D", None).expect("add"), 4);
+        assert_eq!(doc.add("Task A2", Some(1)).expect("add after"), 2);
+
+        assert_eq!(
+            texts(&doc),
+            vec!["Task A", "Task A2", "Task B", "Task C", "Task D"]
+        );
+        assert!(doc.render().contains("  - [ ] Task D\n\nNotes here"));
+    }
+
+    #[test]
+    fn add_to_plan_without_items_appends_at_end() {
+        let mut doc = PlanDocument::parse("# Plan\nGoal");
+        doc.add("First", None).expect("add");
+        assert_eq!(doc.render(), "# Plan\nGoal\n\n- [ ] First");
+    }
+
+    #[test]
+    fn render_keeps_line_endings_and_trailing_newline() {
+        for raw in [
+            "# Plan\r\n- [ ] Task A\r\n",
+            "# Plan\r\n- [ ] Task A",
+            "# Plan\n- [ ] Task A",
+            "",
+        ] {
+            assert_eq!(PlanDocument::parse(raw).render(), raw);
+        }
+
+        let mut doc = PlanDocument::parse("# Plan\r\n- [ ] Task A\r\n");
+        doc.add("Task B", None).expect("add");
+        doc.set_checked(1, true).expect("check");
+        assert_eq!(doc.render(), "# Plan\r\n- [x] Task A\r\n- [ ] Task B\r\n");
+    }
+
+    #[test]
+    fn add_rejects_empty_text_and_unknown_anchor() {
+        let mut doc = PlanDocument::parse(PLAN);
+        assert!(doc.add("   ", None).is_err());
+        assert!(doc.add("x", Some(9)).is_err());
+    }
+
+    #[test]
+    fn check_and_uncheck_toggle_marker() {
+        let mut doc = PlanDocument::parse(PLAN);
+        doc.set_checked(1, true).expect("check");
+        doc.set_checked(2, false).expect("uncheck");
+
+        let rendered = doc.render();
+        assert!(rendered.contains("- [x] Task A"));
+        assert!(rendered.contains("- [ ] Task B"));
+        assert!(doc.set_checked(7, true).is_err());
+    }
+
+    #[test]
+    fn move_item_reorders_checklist() {
+        let mut doc = PlanDocument::parse(PLAN);
+        doc.move_item(3, 1).expect("move up");
+        assert_eq!(texts(&doc), vec!["Task C", "Task A", "Task B"]);
+
+        doc.move_item(1, 3).expect("move down");
+        assert_eq!(texts(&doc), vec!["Task A", "Task B", "Task C"]);
+
+        assert!(doc.move_item(1, 4).is_err());
+    }
+
+    #[test]
+    fn lint_flags_duplicates_empty_and_code_block_items() {
+        let doc = PlanDocument::parse(
+            "- [ ] Ship it\n- [ ]\n- [ ]  ship   IT\n```\n- [ ] example only\n```\n",
+        );
+        let kinds = doc
+            .lint()
+            .into_iter()
+            .map(|issue| (issue.kind, issue.line))
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds,
+            vec![
+                (PlanLintKind::EmptyItem, Some(2)),
+                (PlanLintKind::DuplicateItem, Some(3)),
+                (PlanLintKind::ItemInCodeBlock, Some(5)),
+            ]
+        );
+        assert_eq!(doc.items().len(), 3);
+    }
+
+    #[test]
+    fn lint_flags_plan_without_items() {
+        let doc = PlanDocument::parse("# Plan\n```\n- [ ] code\n```\n");
+        let issues = doc.lint();
+        assert!(issues
+            .iter()
+            .any(|issue| issue.kind == PlanLintKind::NoChecklistItems));
+    }
+}
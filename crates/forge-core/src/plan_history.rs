@@ -0,0 +1,292 @@
+use crate::io::{read_json_or_default, write_json};
+use crate::plan::{normalize_item_text, PlanDocument, PlanItem};
+use anyhow::Result;
+use forge_config::RuntimeLayout;
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+const TREND_WINDOW: usize = 5;
+
+/// Per-item lifecycle and per-loop burndown, persisted across runs in
+/// `<runtime_dir>/plan_history.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PlanHistory {
+    /// Loops recorded so far across every run; item loop numbers refer to it.
+    pub loops_recorded: u64,
+    pub items: Vec<PlanItemHistory>,
+    pub burndown: Vec<BurndownPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct PlanItemHistory {
+    pub key: String,
+    pub text: String,
+    pub first_seen_at_epoch: u64,
+    pub first_seen_loop: u64,
+    pub checked_at_epoch: Option<u64>,
+    pub checked_in_loop: Option<u64>,
+    pub loops_spent: u64,
+    pub reopened: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct BurndownPoint {
+    pub loop_number: u64,
+    pub run_loop: u64,
+    pub at_epoch: u64,
+    pub total: usize,
+    pub remaining: usize,
+    pub checked_this_loop: usize,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum PlanTrend {
+    Unknown,
+    Converging,
+    Stalled,
+    Thrashing,
+}
+
+impl PlanTrend {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            PlanTrend::Unknown => "unknown",
+            PlanTrend::Converging => "converging",
+            PlanTrend::Stalled => "stalled",
+            PlanTrend::Thrashing => "thrashing",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PlanProgressSummary {
+    pub total: usize,
+    pub remaining: usize,
+    pub loops_recorded: u64,
+    pub trend: PlanTrend,
+    pub burndown: Vec<BurndownPoint>,
+}
+
+pub fn read_plan_history(layout: &RuntimeLayout) -> PlanHistory {
+    read_json_or_default(&layout.plan_history_file())
+}
+
+pub fn write_plan_history(layout: &RuntimeLayout, history: &PlanHistory) -> Result<()> {
+    write_json(&layout.plan_history_file(), history)
+}
+
+/// Checklist items of the runtime `plan.md`, or none when the plan is missing.
+pub fn read_plan_items(layout: &RuntimeLayout) -> Vec<PlanItem> {
+    fs::read_to_string(layout.plan_file())
+        .map(|raw| PlanDocument::parse(&raw).items())
+        .unwrap_or_default()
+}
+
+impl PlanHistory {
+    /// Records one loop given the plan items before and after it ran.
+    ///
+    /// The loop is attributed to every item checked during it or, when none
+    /// was, to the first item that was still pending when the loop started.
+    pub fn record_loop(
+        &mut self,
+        before: &[PlanItem],
+        after: &[PlanItem],
+        run_loop: u64,
+        now: u64,
+    ) {
+        self.loops_recorded += 1;
+        let loop_number = self.loops_recorded;
+
+        for item in before.iter().chain(after) {
+            self.entry(item, loop_number, now);
+        }
+
+        let was_checked = |key: &str| {
+            before
+                .iter()
+                .any(|item| item.checked && normalize_item_text(&item.text) == key)
+        };
+
+        let mut checked_this_loop = 0;
+        let mut worked_on = Vec::new();
+        for item in after {
+            let key = normalize_item_text(&item.text);
+            let entry = self.entry(item, loop_number, now);
+            if item.checked && entry.checked_in_loop.is_none() {
+                entry.checked_in_loop = Some(loop_number);
+                entry.checked_at_epoch = Some(now);
+                checked_this_loop += 1;
+                worked_on.push(key);
+            } else if !item.checked && entry.checked_in_loop.is_some() {
+                entry.checked_in_loop = None;
+                entry.checked_at_epoch = None;
+                entry.reopened += 1;
+            } else if item.checked && !was_checked(&key) && entry.checked_in_loop.is_some() {
+                checked_this_loop += 1;
+                worked_on.push(key);
+            }
+        }
+
+        if worked_on.is_empty() {
+            if let Some(head) = before.iter().find(|item| !item.checked) {
+                worked_on.push(normalize_item_text(&head.text));
+            }
+        }
+        for key in worked_on {
+            if let Some(entry) = self.items.iter_mut().find(|entry| entry.key == key) {
+                entry.loops_spent += 1;
+            }
+        }
+
+        self.burndown.push(BurndownPoint {
+            loop_number,
+            run_loop,
+            at_epoch: now,
+            total: after.len(),
+            remaining: after.iter().filter(|item| !item.checked).count(),
+            checked_this_loop,
+        });
+    }
+
+    pub fn summary(&self) -> PlanProgressSummary {
+        let last = self.burndown.last();
+        PlanProgressSummary {
+            total: last.map(|p| p.total).unwrap_or(0),
+            remaining: last.map(|p| p.remaining).unwrap_or(0),
+            loops_recorded: self.loops_recorded,
+            trend: self.trend(),
+            burndown: self.burndown.clone(),
+        }
+    }
+
+    /// Classifies the last few loops: remaining items going down is converging,
+    /// flat is stalled, and going up or reopening items is thrashing.
+    pub fn trend(&self) -> PlanTrend {
+        if self.burndown.len() < 2 {
+            return PlanTrend::Unknown;
+        }
+        let start = self.burndown.len().saturating_sub(TREND_WINDOW + 1);
+        let window = &self.burndown[start..];
+        let reopened_recently = window
+            .windows(2)
+            .any(|pair| pair[1].remaining > pair[0].remaining);
+        let first = window[0].remaining;
+        let last = window[window.len() - 1].remaining;
+
+        if reopened_recently || last > first {
+            PlanTrend::Thrashing
+        } else if last < first || last == 0 {
+            PlanTrend::Converging
+        } else {
+            PlanTrend::Stalled
+        }
+    }
+
+    fn entry(&mut self, item: &PlanItem, loop_number: u64, now: u64) -> &mut PlanItemHistory {
+        let key = normalize_item_text(&item.text);
+        let position = match self.items.iter().position(|entry| entry.key == key) {
+            Some(position) => position,
+            None => {
+                self.items.push(PlanItemHistory {
+                    key,
+                    text: item.text.clone(),
+                    first_seen_at_epoch: now,
+                    first_seen_loop: loop_number,
+                    ..PlanItemHistory::default()
+                });
+                self.items.len() - 1
+            }
+        };
+        &mut self.items[position]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn items(spec: &[(&str, bool)]) -> Vec<PlanItem> {
+        spec.iter()
+            .enumerate()
+            .map(|(idx, (text, checked))| PlanItem {
+                id: idx + 1,
+                line: idx + 1,
+                checked: *checked,
+                text: text.to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn records_first_seen_checked_loop_and_loops_spent() {
+        let mut history = PlanHistory::default();
+        let start = items(&[("A", false), ("B", false)]);
+        let after_one = items(&[("A", false), ("B", false)]);
+        let after_two = items(&[("A", true), ("B", false)]);
+
+        history.record_loop(&start, &after_one, 1, 100);
+        history.record_loop(&after_one, &after_two, 2, 200);
+
+        let a = &history.items[0];
+        assert_eq!(a.first_seen_loop, 1);
+        assert_eq!(a.first_seen_at_epoch, 100);
+        assert_eq!(a.checked_in_loop, Some(2));
+        assert_eq!(a.checked_at_epoch, Some(200));
+        assert_eq!(a.loops_spent, 2);
+        assert_eq!(history.items[1].loops_spent, 0);
+        assert_eq!(history.burndown.len(), 2);
+        assert_eq!(history.burndown[1].remaining, 1);
+        assert_eq!(history.burndown[1].checked_this_loop, 1);
+    }
+
+    #[test]
+    fn reopening_an_item_counts_as_thrashing() {
+        let mut history = PlanHistory::default();
+        let open = items(&[("A", false)]);
+        let done = items(&[("A", true)]);
+
+        history.record_loop(&open, &done, 1, 1);
+        history.record_loop(&done, &open, 2, 2);
+
+        assert_eq!(history.items[0].reopened, 1);
+        assert_eq!(history.items[0].checked_in_loop, None);
+        assert_eq!(history.trend(), PlanTrend::Thrashing);
+    }
+
+    #[test]
+    fn trend_reports_converging_and_stalled() {
+        let mut history = PlanHistory::default();
+        let three = items(&[("A", false), ("B", false), ("C", false)]);
+        let one_done = items(&[("A", true), ("B", false), ("C", false)]);
+        assert_eq!(history.trend(), PlanTrend::Unknown);
+
+        history.record_loop(&three, &three, 1, 1);
+        history.record_loop(&three, &one_done, 2, 2);
+        assert_eq!(history.trend(), PlanTrend::Converging);
+
+        for n in 3..10 {
+            history.record_loop(&one_done, &one_done, n, n);
+        }
+        assert_eq!(history.trend(), PlanTrend::Stalled);
+        assert_eq!(history.items[1].loops_spent, 7);
+    }
+
+    #[test]
+    fn history_round_trips_through_runtime_dir() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        let mut history = PlanHistory::default();
+        history.record_loop(&items(&[("A", false)]), &items(&[("A", true)]), 1, 10);
+
+        write_plan_history(&layout, &history).expect("write");
+        let read = read_plan_history(&layout);
+
+        assert_eq!(read, history);
+        assert_eq!(read.summary().remaining, 0);
+    }
+}
@@ -1,12 +1,53 @@
 use std::fs;
 use std::path::Path;
 
+use crate::carry_over::render_carry_over;
 use crate::status::read_json_or_default;
+use forge_config::{is_spec_set_name, RunConfig, RuntimeLayout};
 use forge_types::ProgressSnapshot;
 
-pub fn build_plan_prompt(cwd: &Path) -> Option<String> {
-    let plan_file = cwd.join(".forge/plan.md");
-    let plan = fs::read_to_string(plan_file).ok()?;
+const SPEC_DOCUMENTS: [&str; 3] = ["acceptance.md", "scenarios.md", "spec.md"];
+const SESSION_SPEC_SET: &str = "session";
+const TRUNCATION_MARKER: &str = "[... truncated to fit prompt budget]";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptOptions {
+    /// Character budget shared by every spec document included in the prompt.
+    /// Roughly four characters per token; `0` disables spec context.
+    pub spec_budget_chars: usize,
+    /// Extra `docs/specs/<name>/` sets to include after the session specs.
+    pub spec_sets: Vec<String>,
+}
+
+impl Default for PromptOptions {
+    fn default() -> Self {
+        Self {
+            spec_budget_chars: 8000,
+            spec_sets: Vec::new(),
+        }
+    }
+}
+
+impl PromptOptions {
+    pub fn from_config(config: &RunConfig) -> Self {
+        Self {
+            spec_budget_chars: config.spec_context_budget_chars,
+            spec_sets: config.spec_context_sets.clone(),
+        }
+    }
+}
+
+pub fn build_plan_prompt(cwd: &Path, layout: &RuntimeLayout) -> Option<String> {
+    build_plan_prompt_with_options(cwd, layout, &PromptOptions::default())
+}
+
+pub fn build_plan_prompt_with_options(
+    cwd: &Path,
+    layout: &RuntimeLayout,
+    options: &PromptOptions,
+) -> Option<String> {
+    let plan_file = layout.plan_file();
+    let plan = fs::read_to_string(&plan_file).ok()?;
     let trimmed = plan.trim();
     if trimmed.is_empty() {
         return None;
@@ -28,14 +69,22 @@ pub fn build_plan_prompt(cwd: &Path) -> Option<String> {
         )
     };
 
-    let last_summary =
-        read_json_or_default::<ProgressSnapshot>(&cwd.join(".forge/progress.json")).last_summary;
-    let continuity = if last_summary.trim().is_empty() {
+    let progress = read_json_or_default::<ProgressSnapshot>(&layout.progress_file());
+    let mut continuity = if progress.last_summary.trim().is_empty() {
         "Last loop summary: (none)".to_string()
     } else {
-        format!("Last loop summary: {}", last_summary.trim())
+        format!("Last loop summary: {}", progress.last_summary.trim())
     };
+    if let Some(carry_over) = &progress.carry_over {
+        continuity.push_str("\n\n");
+        continuity.push_str(&render_carry_over(carry_over));
+    }
 
+    let spec_block = build_spec_context(cwd, options)
+        .map(|block| format!("\n\n{block}"))
+        .unwrap_or_default();
+
+    let plan_source = layout.display(cwd, &plan_file);
     Some(format!(
         "You are continuing an iterative execution loop.\n\
 Continue from current workspace state. Do NOT redo completed checklist items.\n\
@@ -44,10 +93,162 @@ Apply small, verifiable steps and run only targeted validations per step.\n\
 Emit `EXIT_SIGNAL: true` only when all pending checklist items are complete.\n\n\
 {continuity}\n\n\
 {pending_block}\n\n\
-Plan source: .forge/plan.md"
+Plan source: {plan_source}{spec_block}"
     ))
 }
 
+struct SpecDocument {
+    label: String,
+    body: String,
+}
+
+fn build_spec_context(cwd: &Path, options: &PromptOptions) -> Option<String> {
+    if options.spec_budget_chars == 0 {
+        return None;
+    }
+
+    let documents = collect_spec_documents(cwd, &options.spec_sets);
+    if documents.is_empty() {
+        return None;
+    }
+
+    let lengths = documents
+        .iter()
+        .map(|doc| char_len(&doc.body))
+        .collect::<Vec<_>>();
+    let allocations = allocate_budget(&lengths, options.spec_budget_chars);
+
+    let mut out =
+        String::from("Spec context (acceptance criteria and scenarios the work must satisfy):");
+    for (doc, limit) in documents.iter().zip(allocations) {
+        let body = summarize_document(&doc.body, limit);
+        if body.trim().is_empty() {
+            continue;
+        }
+        out.push_str(&format!("\n\n--- {} ---\n{}", doc.label, body.trim_end()));
+    }
+    Some(out)
+}
+
+fn collect_spec_documents(cwd: &Path, extra_sets: &[String]) -> Vec<SpecDocument> {
+    let mut sets = vec![SESSION_SPEC_SET.to_string()];
+    for name in extra_sets {
+        let name = name.trim();
+        if !name.is_empty() && is_spec_set_name(name) && !sets.iter().any(|s| s == name) {
+            sets.push(name.to_string());
+        }
+    }
+
+    let mut documents = Vec::new();
+    for set in sets {
+        for file in SPEC_DOCUMENTS {
+            let label = format!("docs/specs/{set}/{file}");
+            let Ok(body) = fs::read_to_string(cwd.join(&label)) else {
+                continue;
+            };
+            if body.trim().is_empty() {
+                continue;
+            }
+            documents.push(SpecDocument { label, body });
+        }
+    }
+    documents
+}
+
+/// Splits `budget` across documents so short ones are kept whole and the
+/// remainder is shared evenly by the longer ones.
+fn allocate_budget(lengths: &[usize], budget: usize) -> Vec<usize> {
+    let mut allocations = vec![0; lengths.len()];
+    let mut order = (0..lengths.len()).collect::<Vec<_>>();
+    order.sort_by_key(|&idx| lengths[idx]);
+
+    let mut remaining = budget;
+    for (position, &idx) in order.iter().enumerate() {
+        let share = remaining / (order.len() - position);
+        let granted = lengths[idx].min(share);
+        allocations[idx] = granted;
+        remaining -= granted;
+    }
+    allocations
+}
+
+/// Keeps a document within `limit` characters by dropping section bodies
+/// from the end first, preserving headings so the agent still sees the
+/// outline.
+fn summarize_document(body: &str, limit: usize) -> String {
+    if char_len(body) <= limit {
+        return body.to_string();
+    }
+    let reserve = char_len(TRUNCATION_MARKER) + 1;
+    if limit <= reserve {
+        return String::new();
+    }
+    let limit = limit - reserve;
+
+    let sections = split_sections(body);
+    let headings_cost = sections
+        .iter()
+        .filter_map(|section| section.heading)
+        .map(|heading| char_len(heading) + 1)
+        .sum::<usize>();
+
+    let mut out = String::new();
+    let mut used = 0;
+    let mut body_budget = limit.saturating_sub(headings_cost);
+    for section in &sections {
+        if let Some(heading) = section.heading {
+            let cost = char_len(heading) + 1;
+            if used + cost > limit {
+                break;
+            }
+            used += cost;
+            out.push_str(heading);
+            out.push('\n');
+        }
+        for line in &section.lines {
+            let cost = char_len(line) + 1;
+            if cost > body_budget {
+                body_budget = 0;
+                break;
+            }
+            body_budget -= cost;
+            used += cost;
+            out.push_str(line);
+            out.push('\n');
+        }
+    }
+    out.push_str(TRUNCATION_MARKER);
+    out.push('\n');
+    out
+}
+
+fn char_len(text: &str) -> usize {
+    text.chars().count()
+}
+
+struct Section<'a> {
+    heading: Option<&'a str>,
+    lines: Vec<&'a str>,
+}
+
+fn split_sections(body: &str) -> Vec<Section<'_>> {
+    let mut sections = vec![Section {
+        heading: None,
+        lines: Vec::new(),
+    }];
+    for line in body.lines() {
+        if line.trim_start().starts_with('#') {
+            sections.push(Section {
+                heading: Some(line),
+                lines: Vec::new(),
+            });
+        } else if let Some(current) = sections.last_mut() {
+            current.lines.push(line);
+        }
+    }
+    sections
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PlanSummary {
     pub total_items: usize,
@@ -55,9 +256,8 @@ pub struct PlanSummary {
     pub checked_items: usize,
 }
 
-pub fn analyze_plan(cwd: &Path) -> Option<PlanSummary> {
-    let plan_file = cwd.join(".forge/plan.md");
-    let plan = fs::read_to_string(plan_file).ok()?;
+pub fn analyze_plan(layout: &RuntimeLayout) -> Option<PlanSummary> {
+    let plan = fs::read_to_string(layout.plan_file()).ok()?;
     let trimmed = plan.trim();
     if trimmed.is_empty() {
         return None;
@@ -79,10 +279,14 @@ mod tests {
     use super::*;
     use tempfile::tempdir;
 
+    fn layout(cwd: &Path) -> RuntimeLayout {
+        RuntimeLayout::new(cwd.join(".forge"))
+    }
+
     #[test]
     fn returns_none_when_no_plan() {
         let dir = tempdir().expect("tempdir");
-        let result = build_plan_prompt(dir.path());
+        let result = build_plan_prompt(dir.path(), &layout(dir.path()));
         assert!(result.is_none());
     }
 
@@ -93,7 +297,7 @@ mod tests {
         fs::create_dir_all(&forge_dir).expect("create .forge");
         fs::write(forge_dir.join("plan.md"), "   \n").expect("write empty plan");
 
-        let result = build_plan_prompt(dir.path());
+        let result = build_plan_prompt(dir.path(), &layout(dir.path()));
         assert!(result.is_none());
     }
 
@@ -113,7 +317,7 @@ mod tests {
         )
         .expect("write progress");
 
-        let prompt = build_plan_prompt(dir.path()).expect("prompt");
+        let prompt = build_plan_prompt(dir.path(), &layout(dir.path())).expect("prompt");
 
         assert!(prompt.contains("continuing an iterative execution loop"));
         assert!(prompt.contains("Do NOT redo completed checklist items"));
@@ -122,6 +326,23 @@ mod tests {
         assert!(prompt.contains("finished task B"));
     }
 
+    #[test]
+    fn reads_plan_and_progress_from_custom_runtime_dir() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path().join("state/forge"));
+        fs::create_dir_all(layout.root()).expect("create runtime dir");
+        fs::write(layout.plan_file(), "- [ ] Task A\n").expect("write plan");
+        fs::write(layout.progress_file(), r#"{"last_summary":"set up"}"#).expect("progress");
+
+        let prompt = build_plan_prompt(dir.path(), &layout).expect("prompt");
+
+        assert!(prompt.contains("Task A"));
+        assert!(prompt.contains("set up"));
+        assert!(prompt.contains("Plan source: state/forge/plan.md"));
+        assert!(build_plan_prompt(dir.path(), &self::layout(dir.path())).is_none());
+        assert_eq!(analyze_plan(&layout).expect("summary").unchecked_items, 1);
+    }
+
     #[test]
     fn includes_continuity_message_when_no_progress() {
         let dir = tempdir().expect("tempdir");
@@ -129,7 +350,7 @@ mod tests {
         fs::create_dir_all(&forge_dir).expect("create .forge");
         fs::write(forge_dir.join("plan.md"), "# Plan\n- [ ] Task A\n").expect("write plan");
 
-        let prompt = build_plan_prompt(dir.path()).expect("prompt");
+        let prompt = build_plan_prompt(dir.path(), &layout(dir.path())).expect("prompt");
 
         assert!(prompt.contains("Last loop summary: (none)"));
     }
@@ -145,7 +366,7 @@ mod tests {
         )
         .expect("write plan");
 
-        let summary = analyze_plan(dir.path()).expect("summary");
+        let summary = analyze_plan(&layout(dir.path())).expect("summary");
 
         assert_eq!(summary.total_items, 3);
         assert_eq!(summary.unchecked_items, 2);
@@ -155,7 +376,7 @@ mod tests {
     #[test]
     fn analyze_plan_returns_none_when_no_plan() {
         let dir = tempdir().expect("tempdir");
-        let result = analyze_plan(dir.path());
+        let result = analyze_plan(&layout(dir.path()));
         assert!(result.is_none());
     }
 
@@ -171,7 +392,7 @@ mod tests {
         }
         fs::write(forge_dir.join("plan.md"), &plan).expect("write plan");
 
-        let prompt = build_plan_prompt(dir.path()).expect("prompt");
+        let prompt = build_plan_prompt(dir.path(), &layout(dir.path())).expect("prompt");
 
         assert!(prompt.contains("Task 0"));
         assert!(prompt.contains("Task 79"));
@@ -185,7 +406,7 @@ mod tests {
         fs::create_dir_all(&forge_dir).expect("create .forge");
         fs::write(forge_dir.join("plan.md"), "   \n\t\n").expect("write whitespace");
 
-        let result = build_plan_prompt(dir.path());
+        let result = build_plan_prompt(dir.path(), &layout(dir.path()));
         assert!(result.is_none());
     }
 
@@ -200,7 +421,7 @@ mod tests {
         )
         .expect("write plan");
 
-        let summary = analyze_plan(dir.path()).expect("summary");
+        let summary = analyze_plan(&layout(dir.path())).expect("summary");
         assert_eq!(summary.checked_items, 1);
         assert_eq!(summary.unchecked_items, 1);
     }
@@ -212,10 +433,114 @@ mod tests {
         fs::create_dir_all(&forge_dir).expect("create .forge");
         fs::write(forge_dir.join("plan.md"), "# Plan\n").expect("write empty plan");
 
-        let summary = analyze_plan(dir.path()).expect("summary");
+        let summary = analyze_plan(&layout(dir.path())).expect("summary");
         assert_eq!(summary.total_items, 0);
     }
 
+    #[test]
+    fn includes_session_specs_in_prompt() {
+        let dir = tempdir().expect("tempdir");
+        let forge_dir = dir.path().join(".forge");
+        let specs_dir = dir.path().join("docs/specs/session");
+        fs::create_dir_all(&forge_dir).expect("create .forge");
+        fs::create_dir_all(&specs_dir).expect("create specs");
+        fs::write(forge_dir.join("plan.md"), "- [ ] Task A\n").expect("write plan");
+        fs::write(
+            specs_dir.join("acceptance.md"),
+            "# Session Acceptance Criteria\n\n- [ ] status is consistent\n",
+        )
+        .expect("write acceptance");
+        fs::write(
+            specs_dir.join("scenarios.md"),
+            "# Session Scenarios\n\n## Scenario 1\nGiven a plan When run Then finish\n",
+        )
+        .expect("write scenarios");
+
+        let prompt = build_plan_prompt(dir.path(), &layout(dir.path())).expect("prompt");
+
+        assert!(prompt.contains("--- docs/specs/session/acceptance.md ---"));
+        assert!(prompt.contains("status is consistent"));
+        assert!(prompt.contains("Given a plan When run Then finish"));
+    }
+
+    #[test]
+    fn includes_named_spec_sets_and_skips_when_budget_is_zero() {
+        let dir = tempdir().expect("tempdir");
+        let forge_dir = dir.path().join(".forge");
+        let specs_dir = dir.path().join("docs/specs/monitor");
+        fs::create_dir_all(&forge_dir).expect("create .forge");
+        fs::create_dir_all(&specs_dir).expect("create specs");
+        fs::write(forge_dir.join("plan.md"), "- [ ] Task A\n").expect("write plan");
+        fs::write(specs_dir.join("spec.md"), "# Monitor\nshows heartbeat\n").expect("write spec");
+
+        let options = PromptOptions {
+            spec_budget_chars: 4000,
+            spec_sets: vec!["monitor".to_string()],
+        };
+        let prompt = build_plan_prompt_with_options(dir.path(), &layout(dir.path()), &options)
+            .expect("prompt");
+        assert!(prompt.contains("--- docs/specs/monitor/spec.md ---"));
+        assert!(prompt.contains("shows heartbeat"));
+
+        let disabled = PromptOptions {
+            spec_budget_chars: 0,
+            ..options
+        };
+        let prompt = build_plan_prompt_with_options(dir.path(), &layout(dir.path()), &disabled)
+            .expect("prompt");
+        assert!(!prompt.contains("Spec context"));
+    }
+
+    #[test]
+    fn allocate_budget_keeps_short_documents_whole() {
+        let allocations = allocate_budget(&[100, 5000, 5000], 3100);
+        assert_eq!(allocations, vec![100, 1500, 1500]);
+    }
+
+    #[test]
+    fn summarize_document_truncates_by_section_keeping_headings() {
+        let mut doc = String::from("# Spec\n\n## Goal\n");
+        doc.push_str(&"goal line\n".repeat(40));
+        doc.push_str("## Constraints\nrust only\n");
+
+        let summary = summarize_document(&doc, 200);
+
+        assert!(summary.len() <= 200);
+        assert!(summary.contains("## Goal"));
+        assert!(summary.contains("## Constraints"));
+        assert!(summary.ends_with(&format!("{TRUNCATION_MARKER}\n")));
+    }
+
+    #[test]
+    fn summarize_document_counts_characters_not_bytes() {
+        let doc = "## Ziel\n".to_string() + &"größe ändern\n".repeat(10);
+        let chars = char_len(&doc);
+        assert!(doc.len() > chars);
+        assert_eq!(summarize_document(&doc, chars), doc);
+
+        let summary = summarize_document(&doc, 100);
+        assert!(char_len(&summary) <= 100);
+        assert!(summary.contains("größe ändern"));
+    }
+
+    #[test]
+    fn renders_carry_over_from_progress() {
+        let dir = tempdir().expect("tempdir");
+        let forge_dir = dir.path().join(".forge");
+        fs::create_dir_all(&forge_dir).expect("create .forge");
+        fs::write(forge_dir.join("plan.md"), "- [ ] Test\n").expect("write plan");
+        fs::write(
+            forge_dir.join("progress.json"),
+            r#"{"last_summary":"tests red","carry_over":{"loop_number":4,"files_changed":["src/lib.rs"],"failed_commands":[{"command":"cargo test","exit_code":101}],"last_agent_message":"tests red"}}"#,
+        )
+        .expect("write progress");
+
+        let prompt = build_plan_prompt(dir.path(), &layout(dir.path())).expect("prompt");
+        assert!(prompt.contains("Previous loop carry-over (loop 4):"));
+        assert!(prompt.contains("- files changed: src/lib.rs"));
+        assert!(prompt.contains("`cargo test` (exit 101)"));
+    }
+
     #[test]
     fn build_plan_prompt_includes_continuity_message() {
         let dir = tempdir().expect("tempdir");
@@ -228,7 +553,7 @@ mod tests {
         )
         .expect("write progress");
 
-        let prompt = build_plan_prompt(dir.path()).expect("prompt");
+        let prompt = build_plan_prompt(dir.path(), &layout(dir.path())).expect("prompt");
         assert!(prompt.contains("Last loop summary:"));
         assert!(prompt.contains("completed step 1"));
     }
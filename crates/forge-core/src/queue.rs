@@ -0,0 +1,184 @@
+use crate::io::{read_json_or_default, write_json};
+use crate::ExitReason;
+use anyhow::{bail, Result};
+use forge_config::RuntimeLayout;
+use serde::{Deserialize, Serialize};
+
+/// SDDs waiting to be run back-to-back, persisted in `<runtime_dir>/queue.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct SddQueue {
+    pub entries: Vec<QueueEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct QueueEntry {
+    pub sdd_id: String,
+    /// Loop budget for this SDD; the runner's default applies when unset.
+    pub max_loops: Option<u64>,
+    pub state: QueueEntryState,
+    pub exit_reason: Option<ExitReason>,
+    pub loops_executed: u64,
+    pub error: Option<String>,
+    pub added_at_epoch: u64,
+    pub started_at_epoch: Option<u64>,
+    pub finished_at_epoch: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum QueueEntryState {
+    #[default]
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+impl QueueEntryState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            QueueEntryState::Pending => "pending",
+            QueueEntryState::Running => "running",
+            QueueEntryState::Completed => "completed",
+            QueueEntryState::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QueuePolicy {
+    StopOnFailure,
+    Continue,
+}
+
+pub fn read_queue(layout: &RuntimeLayout) -> SddQueue {
+    read_json_or_default(&layout.queue_file())
+}
+
+pub fn write_queue(layout: &RuntimeLayout, queue: &SddQueue) -> Result<()> {
+    write_json(&layout.queue_file(), queue)
+}
+
+impl SddQueue {
+    pub fn add(&mut self, sdd_id: &str, max_loops: Option<u64>, now: u64) -> Result<()> {
+        if self
+            .entries
+            .iter()
+            .any(|entry| entry.sdd_id == sdd_id && entry.state == QueueEntryState::Pending)
+        {
+            bail!("sdd {} is already queued", sdd_id);
+        }
+        if max_loops == Some(0) {
+            bail!("max loops must be at least 1");
+        }
+        self.entries.push(QueueEntry {
+            sdd_id: sdd_id.to_string(),
+            max_loops,
+            added_at_epoch: now,
+            ..QueueEntry::default()
+        });
+        Ok(())
+    }
+
+    pub fn next_pending(&self) -> Option<usize> {
+        self.entries
+            .iter()
+            .position(|entry| entry.state == QueueEntryState::Pending)
+    }
+
+    /// Puts entries left `running` by an interrupted runner back in line.
+    pub fn recover_interrupted(&mut self) -> usize {
+        let mut recovered = 0;
+        for entry in &mut self.entries {
+            if entry.state == QueueEntryState::Running {
+                entry.state = QueueEntryState::Pending;
+                entry.started_at_epoch = None;
+                recovered += 1;
+            }
+        }
+        recovered
+    }
+
+    pub fn start(&mut self, index: usize, now: u64) {
+        let entry = &mut self.entries[index];
+        entry.state = QueueEntryState::Running;
+        entry.started_at_epoch = Some(now);
+        entry.finished_at_epoch = None;
+        entry.exit_reason = None;
+        entry.error = None;
+        entry.loops_executed = 0;
+    }
+
+    pub fn finish(
+        &mut self,
+        index: usize,
+        succeeded: bool,
+        exit_reason: Option<ExitReason>,
+        loops_executed: u64,
+        error: Option<String>,
+        now: u64,
+    ) {
+        let entry = &mut self.entries[index];
+        entry.state = if succeeded {
+            QueueEntryState::Completed
+        } else {
+            QueueEntryState::Failed
+        };
+        entry.exit_reason = exit_reason;
+        entry.loops_executed = loops_executed;
+        entry.error = error;
+        entry.finished_at_epoch = Some(now);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn add_rejects_duplicate_pending_entries_and_zero_budget() {
+        let mut queue = SddQueue::default();
+        queue.add("01-a", None, 1).expect("add");
+
+        assert!(queue.add("01-a", Some(3), 2).is_err());
+        assert!(queue.add("02-b", Some(0), 2).is_err());
+
+        queue.finish(0, true, Some(ExitReason::Completed), 2, None, 3);
+        queue
+            .add("01-a", Some(3), 4)
+            .expect("re-queue after finishing");
+        assert_eq!(queue.entries.len(), 2);
+    }
+
+    #[test]
+    fn next_pending_skips_finished_and_recovers_interrupted() {
+        let mut queue = SddQueue::default();
+        queue.add("01-a", None, 1).expect("add a");
+        queue.add("02-b", None, 1).expect("add b");
+
+        queue.start(0, 5);
+        assert_eq!(queue.next_pending(), Some(1));
+        assert_eq!(queue.recover_interrupted(), 1);
+        assert_eq!(queue.next_pending(), Some(0));
+
+        queue.start(0, 6);
+        queue.finish(0, false, Some(ExitReason::CircuitOpened), 4, None, 7);
+        assert_eq!(queue.entries[0].state, QueueEntryState::Failed);
+        assert_eq!(queue.next_pending(), Some(1));
+    }
+
+    #[test]
+    fn queue_round_trips_through_runtime_dir() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        let mut queue = SddQueue::default();
+        queue.add("01-a", Some(10), 1).expect("add");
+
+        write_queue(&layout, &queue).expect("write");
+
+        assert_eq!(read_queue(&layout), queue);
+    }
+}
@@ -1,5 +1,5 @@
+use forge_config::RuntimeLayout;
 use std::fs;
-use std::path::Path;
 
 pub struct RateLimiter {
     max_calls_per_hour: u32,
@@ -25,14 +25,14 @@ impl RateLimiter {
 
     pub fn check_and_increment(
         &self,
-        runtime_dir: &Path,
+        layout: &RuntimeLayout,
         now_epoch: u64,
     ) -> anyhow::Result<RateLimitResult> {
-        let state = self.load_state(runtime_dir)?;
+        let state = self.load_state(layout)?;
         let (count, last_reset) = self.maybe_reset(state, now_epoch);
 
         if count >= self.max_calls_per_hour {
-            self.persist_state(runtime_dir, count, last_reset)?;
+            self.persist_state(layout, count, last_reset)?;
             return Ok(RateLimitResult {
                 allowed: false,
                 current_count: count,
@@ -41,7 +41,7 @@ impl RateLimiter {
         }
 
         let new_count = count + 1;
-        self.persist_state(runtime_dir, new_count, last_reset)?;
+        self.persist_state(layout, new_count, last_reset)?;
 
         Ok(RateLimitResult {
             allowed: true,
@@ -50,17 +50,17 @@ impl RateLimiter {
         })
     }
 
-    pub fn get_state(&self, runtime_dir: &Path) -> anyhow::Result<RateLimitState> {
-        self.load_state(runtime_dir)
+    pub fn get_state(&self, layout: &RuntimeLayout) -> anyhow::Result<RateLimitState> {
+        self.load_state(layout)
     }
 
-    pub fn reset(&self, runtime_dir: &Path, now_epoch: u64) -> anyhow::Result<()> {
-        self.persist_state(runtime_dir, 0, now_epoch)
+    pub fn reset(&self, layout: &RuntimeLayout, now_epoch: u64) -> anyhow::Result<()> {
+        self.persist_state(layout, 0, now_epoch)
     }
 
-    fn load_state(&self, runtime_dir: &Path) -> anyhow::Result<RateLimitState> {
-        let count_path = runtime_dir.join(".call_count");
-        let reset_path = runtime_dir.join(".last_reset");
+    fn load_state(&self, layout: &RuntimeLayout) -> anyhow::Result<RateLimitState> {
+        let count_path = layout.call_count_file();
+        let reset_path = layout.last_reset_file();
 
         let count = fs::read_to_string(&count_path)
             .ok()
@@ -86,9 +86,14 @@ impl RateLimiter {
         }
     }
 
-    fn persist_state(&self, runtime_dir: &Path, count: u32, last_reset: u64) -> anyhow::Result<()> {
-        let count_path = runtime_dir.join(".call_count");
-        let reset_path = runtime_dir.join(".last_reset");
+    fn persist_state(
+        &self,
+        layout: &RuntimeLayout,
+        count: u32,
+        last_reset: u64,
+    ) -> anyhow::Result<()> {
+        let count_path = layout.call_count_file();
+        let reset_path = layout.last_reset_file();
 
         fs::write(&count_path, count.to_string())?;
         fs::write(&reset_path, last_reset.to_string())?;
@@ -105,11 +110,10 @@ mod tests {
     #[test]
     fn allows_first_call() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(100);
 
-        let result = limiter
-            .check_and_increment(dir.path(), 1000)
-            .expect("check");
+        let result = limiter.check_and_increment(&layout, 1000).expect("check");
 
         assert!(result.allowed);
         assert_eq!(result.current_count, 1);
@@ -119,12 +123,13 @@ mod tests {
     #[test]
     fn blocks_when_limit_reached() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(3);
 
-        limiter.check_and_increment(dir.path(), 1000).expect("1");
-        limiter.check_and_increment(dir.path(), 1001).expect("2");
-        limiter.check_and_increment(dir.path(), 1002).expect("3");
-        let result = limiter.check_and_increment(dir.path(), 1003).expect("4");
+        limiter.check_and_increment(&layout, 1000).expect("1");
+        limiter.check_and_increment(&layout, 1001).expect("2");
+        limiter.check_and_increment(&layout, 1002).expect("3");
+        let result = limiter.check_and_increment(&layout, 1003).expect("4");
 
         assert!(!result.allowed);
         assert_eq!(result.current_count, 3);
@@ -134,17 +139,16 @@ mod tests {
     #[test]
     fn resets_after_one_hour() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(2);
 
-        limiter.check_and_increment(dir.path(), 1000).expect("1");
-        limiter.check_and_increment(dir.path(), 1001).expect("2");
-        let blocked = limiter
-            .check_and_increment(dir.path(), 1002)
-            .expect("blocked");
+        limiter.check_and_increment(&layout, 1000).expect("1");
+        limiter.check_and_increment(&layout, 1001).expect("2");
+        let blocked = limiter.check_and_increment(&layout, 1002).expect("blocked");
         assert!(!blocked.allowed);
 
         let after_reset = limiter
-            .check_and_increment(dir.path(), 5000)
+            .check_and_increment(&layout, 5000)
             .expect("after reset");
 
         assert!(after_reset.allowed);
@@ -154,14 +158,15 @@ mod tests {
     #[test]
     fn reset_clears_count() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(100);
 
-        limiter.check_and_increment(dir.path(), 1000).expect("1");
-        limiter.check_and_increment(dir.path(), 1001).expect("2");
+        limiter.check_and_increment(&layout, 1000).expect("1");
+        limiter.check_and_increment(&layout, 1001).expect("2");
 
-        limiter.reset(dir.path(), 2000).expect("reset");
+        limiter.reset(&layout, 2000).expect("reset");
 
-        let state = limiter.get_state(dir.path()).expect("state");
+        let state = limiter.get_state(&layout).expect("state");
         assert_eq!(state.count, 0);
         assert_eq!(state.last_reset_epoch, 2000);
     }
@@ -169,25 +174,26 @@ mod tests {
     #[test]
     fn reset_at_exactly_one_hour() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(2);
 
-        limiter.check_and_increment(dir.path(), 1000).expect("1");
-        limiter.check_and_increment(dir.path(), 1001).expect("2");
+        limiter.check_and_increment(&layout, 1000).expect("1");
+        limiter.check_and_increment(&layout, 1001).expect("2");
 
         let at_one_hour = limiter
-            .check_and_increment(dir.path(), 4600)
+            .check_and_increment(&layout, 4600)
             .expect("at one hour");
         assert!(at_one_hour.allowed);
         assert_eq!(at_one_hour.current_count, 1);
 
         let after_one_hour = limiter
-            .check_and_increment(dir.path(), 5000)
+            .check_and_increment(&layout, 5000)
             .expect("after hour");
         assert!(after_one_hour.allowed);
         assert_eq!(after_one_hour.current_count, 2);
 
         let over_limit = limiter
-            .check_and_increment(dir.path(), 5001)
+            .check_and_increment(&layout, 5001)
             .expect("over limit");
         assert!(!over_limit.allowed);
     }
@@ -195,13 +201,14 @@ mod tests {
     #[test]
     fn persists_state_between_instances() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
 
         let limiter1 = RateLimiter::new(5);
-        limiter1.check_and_increment(dir.path(), 1000).expect("1");
-        limiter1.check_and_increment(dir.path(), 1001).expect("2");
+        limiter1.check_and_increment(&layout, 1000).expect("1");
+        limiter1.check_and_increment(&layout, 1001).expect("2");
 
         let limiter2 = RateLimiter::new(5);
-        let state = limiter2.get_state(dir.path()).expect("state");
+        let state = limiter2.get_state(&layout).expect("state");
 
         assert_eq!(state.count, 2);
     }
@@ -209,11 +216,10 @@ mod tests {
     #[test]
     fn zero_limit_always_blocks() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(0);
 
-        let result = limiter
-            .check_and_increment(dir.path(), 1000)
-            .expect("check");
+        let result = limiter.check_and_increment(&layout, 1000).expect("check");
 
         assert!(!result.allowed);
         assert_eq!(result.current_count, 0);
@@ -223,20 +229,22 @@ mod tests {
     #[test]
     fn saturating_sub_prevents_underflow() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(1);
 
-        limiter.check_and_increment(dir.path(), 1000).expect("1");
+        limiter.check_and_increment(&layout, 1000).expect("1");
 
-        let result = limiter.check_and_increment(dir.path(), 1001).expect("2");
+        let result = limiter.check_and_increment(&layout, 1001).expect("2");
         assert_eq!(result.remaining, 0);
     }
 
     #[test]
     fn get_state_returns_default_for_missing_files() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let limiter = RateLimiter::new(100);
 
-        let state = limiter.get_state(dir.path()).expect("state");
+        let state = limiter.get_state(&layout).expect("state");
 
         assert_eq!(state.count, 0);
         assert_eq!(state.last_reset_epoch, 0);
@@ -1,5 +1,6 @@
 use anyhow::{bail, Context, Result};
-use forge_types::{ProgressSnapshot, RunStatus};
+use forge_config::RuntimeLayout;
+use forge_types::{ProgressSnapshot, RunState, RunStatus, Versioned};
 use serde::de::DeserializeOwned;
 use std::fs;
 use std::path::Path;
@@ -12,17 +13,18 @@ fn epoch_now() -> u64 {
         .as_secs()
 }
 
-pub fn read_status(runtime_dir: &Path) -> Result<RunStatus> {
-    let path = runtime_dir.join("status.json");
+pub fn read_status(layout: &RuntimeLayout) -> Result<RunStatus> {
+    let path = layout.status_file();
     if !path.exists() {
         bail!("status file not found at {}", path.display());
     }
     let body =
         fs::read_to_string(&path).with_context(|| format!("failed to read {}", path.display()))?;
-    let mut status: RunStatus = serde_json::from_str(&body)
-        .with_context(|| format!("invalid json in {}", path.display()))?;
-    if is_stale_running_status(runtime_dir, &status) {
-        status.state = "stale_runner".to_string();
+    let mut status = serde_json::from_str::<RunStatus>(&body)
+        .with_context(|| format!("invalid json in {}", path.display()))?
+        .upgrade();
+    if is_stale_running_status(layout, &status) {
+        status.state = RunState::StaleRunner;
         status.current_loop = 0;
         status.current_loop_started_at_epoch = 0;
         status.last_heartbeat_at_epoch = 0;
@@ -31,21 +33,21 @@ pub fn read_status(runtime_dir: &Path) -> Result<RunStatus> {
         }
         status.updated_at_epoch = epoch_now();
         let _ = write_json(&path, &status);
-        let _ = fs::remove_file(runtime_dir.join(".runner_pid"));
+        let _ = fs::remove_file(layout.runner_pid_file());
     }
     Ok(status)
 }
 
-pub fn read_progress(runtime_dir: &Path) -> ProgressSnapshot {
-    read_json_or_default(&runtime_dir.join("progress.json"))
+pub fn read_progress(layout: &RuntimeLayout) -> ProgressSnapshot {
+    read_json_or_default::<ProgressSnapshot>(&layout.progress_file()).upgrade()
 }
 
-pub fn write_status(runtime_dir: &Path, status: &RunStatus) -> Result<()> {
-    write_json(&runtime_dir.join("status.json"), status)
+pub fn write_status(layout: &RuntimeLayout, status: &RunStatus) -> Result<()> {
+    write_json(&layout.status_file(), status)
 }
 
-pub fn write_progress(runtime_dir: &Path, progress: &ProgressSnapshot) -> Result<()> {
-    write_json(&runtime_dir.join("progress.json"), progress)
+pub fn write_progress(layout: &RuntimeLayout, progress: &ProgressSnapshot) -> Result<()> {
+    write_json(&layout.progress_file(), progress)
 }
 
 pub fn write_json<T: serde::Serialize>(path: &Path, value: &T) -> Result<()> {
@@ -60,11 +62,11 @@ pub fn read_json_or_default<T: DeserializeOwned + Default>(path: &Path) -> T {
     }
 }
 
-fn is_stale_running_status(runtime_dir: &Path, status: &RunStatus) -> bool {
-    if status.state != "running" {
+fn is_stale_running_status(layout: &RuntimeLayout, status: &RunStatus) -> bool {
+    if status.state != RunState::Running {
         return false;
     }
-    let pid_path = runtime_dir.join(".runner_pid");
+    let pid_path = layout.runner_pid_file();
     let Ok(raw_pid) = fs::read_to_string(pid_path) else {
         return true;
     };
@@ -100,9 +102,10 @@ mod tests {
     use forge_types::CircuitState;
     use tempfile::tempdir;
 
-    fn make_status(state: &str) -> RunStatus {
+    fn make_status(state: RunState) -> RunStatus {
         RunStatus {
-            state: state.to_string(),
+            schema_version: forge_types::STATE_SCHEMA_VERSION,
+            state,
             thinking_mode: "summary".to_string(),
             run_started_at_epoch: 1000,
             current_loop: 1,
@@ -111,10 +114,17 @@ mod tests {
             completion_indicators: 0,
             exit_signal_seen: false,
             session_id: Some("test-session".to_string()),
+            run_id: None,
             circuit_state: CircuitState::Closed,
             current_loop_started_at_epoch: 1100,
             last_heartbeat_at_epoch: 1150,
             updated_at_epoch: 1200,
+            engine: "codex".to_string(),
+            profile: None,
+            model: None,
+            reasoning_effort: None,
+            last_token_usage: None,
+            extra: Default::default(),
         }
     }
 
@@ -122,20 +132,77 @@ mod tests {
     #[allow(clippy::zombie_processes, unused_mut)]
     fn write_and_read_status() {
         let dir = tempdir().expect("tempdir");
-        let status = make_status("idle"); // Use "idle" to avoid stale check
+        let layout = RuntimeLayout::new(dir.path());
+        let status = make_status(RunState::Idle); // Use Idle to avoid stale check
 
-        write_status(dir.path(), &status).expect("write");
+        write_status(&layout, &status).expect("write");
 
-        let read = read_status(dir.path()).expect("read");
-        assert_eq!(read.state, "idle");
+        let read = read_status(&layout).expect("read");
+        assert_eq!(read.state, RunState::Idle);
         assert_eq!(read.current_loop, 1);
         assert_eq!(read.session_id, Some("test-session".to_string()));
     }
 
+    #[test]
+    fn read_status_upgrades_legacy_and_tolerates_newer_files() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+
+        fs::write(
+            layout.status_file(),
+            r#"{"state":"max_loops_reached","engine":"codex"}"#,
+        )
+        .expect("write legacy");
+        let legacy = read_status(&layout).expect("read legacy");
+        assert_eq!(legacy.state, RunState::MaxLoopsReached);
+        assert_eq!(legacy.schema_version, forge_types::STATE_SCHEMA_VERSION);
+
+        fs::write(
+            layout.status_file(),
+            r#"{"schema_version":7,"state":"paused","engine":"codex","new_field":true}"#,
+        )
+        .expect("write newer");
+        let newer = read_status(&layout).expect("read newer");
+        assert_eq!(newer.state, RunState::Other("paused".to_string()));
+        assert_eq!(newer.schema_version, 7);
+        assert_eq!(newer.engine, "codex");
+        let rewritten = serde_json::to_value(&newer).expect("serialize");
+        assert_eq!(rewritten["state"], "paused");
+
+        fs::write(layout.progress_file(), r#"{"loops_with_progress":3}"#)
+            .expect("write legacy progress");
+        let progress = read_progress(&layout);
+        assert_eq!(progress.loops_with_progress, 3);
+        assert_eq!(progress.schema_version, forge_types::STATE_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn stale_rewrite_keeps_fields_from_a_newer_version() {
+        let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
+        fs::write(
+            layout.status_file(),
+            r#"{"schema_version":2,"state":"running","engine":"codex","new_field":{"a":1}}"#,
+        )
+        .expect("write v2");
+
+        let status = read_status(&layout).expect("read v2");
+        assert_eq!(status.state, RunState::StaleRunner);
+        assert_eq!(status.schema_version, 2);
+
+        let raw = fs::read_to_string(layout.status_file()).expect("reread");
+        let rewritten: serde_json::Value = serde_json::from_str(&raw).expect("json");
+        assert_eq!(rewritten["schema_version"], 2);
+        assert_eq!(rewritten["state"], "stale_runner");
+        assert_eq!(rewritten["new_field"]["a"], 1);
+        assert_eq!(read_status(&layout).expect("read again").extra.len(), 1);
+    }
+
     #[test]
     fn read_status_fails_when_missing() {
         let dir = tempdir().expect("tempdir");
-        let result = read_status(dir.path());
+        let layout = RuntimeLayout::new(dir.path());
+        let result = read_status(&layout);
         assert!(result.is_err());
     }
 
@@ -162,16 +229,20 @@ mod tests {
     #[test]
     fn write_and_read_progress() {
         let dir = tempdir().expect("tempdir");
+        let layout = RuntimeLayout::new(dir.path());
         let progress = ProgressSnapshot {
+            schema_version: forge_types::STATE_SCHEMA_VERSION,
             loops_with_progress: 10,
             loops_without_progress: 2,
             last_summary: "completed task".to_string(),
             updated_at_epoch: 5000,
+            carry_over: None,
+            extra: Default::default(),
         };
 
-        write_progress(dir.path(), &progress).expect("write");
+        write_progress(&layout, &progress).expect("write");
 
-        let read = read_progress(dir.path());
+        let read = read_progress(&layout);
         assert_eq!(read.loops_with_progress, 10);
         assert_eq!(read.loops_without_progress, 2);
         assert_eq!(read.last_summary, "completed task");
@@ -1,5 +1,6 @@
-use forge_config::{load_run_config, CliOverrides};
-use forge_core::{run_loop, ExitReason, RunRequest};
+use forge_config::{load_run_config, CliOverrides, RuntimeLayout};
+use forge_core::{list_log_archives, read_plan_history, run_loop, ExitReason, RunRequest};
+use forge_types::{LiveLogEntry, LogSource, RunState};
 use std::fs;
 use tempfile::tempdir;
 
@@ -26,7 +27,7 @@ fn run_completes_when_dual_gate_is_satisfied() {
     )
     .expect("forgerc write");
 
-    let cfg = load_run_config(dir.path(), &CliOverrides::default()).expect("config");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("config");
     let outcome = run_loop(RunRequest {
         cwd: dir.path().to_path_buf(),
         config: cfg,
@@ -35,7 +36,7 @@ fn run_completes_when_dual_gate_is_satisfied() {
     .expect("run_loop");
 
     assert_eq!(outcome.reason, ExitReason::Completed);
-    assert_eq!(outcome.status.state, "completed");
+    assert_eq!(outcome.status.state, RunState::Completed);
 }
 
 #[cfg(unix)]
@@ -57,7 +58,7 @@ fn run_with_fixed_max_loops_resets_counters_per_execution() {
     )
     .expect("forgerc write");
 
-    let cfg = load_run_config(dir.path(), &CliOverrides::default()).expect("config");
+    let cfg = load_run_config(dir.path(), &isolated()).expect("config");
     let first = run_loop(RunRequest {
         cwd: dir.path().to_path_buf(),
         config: cfg.clone(),
@@ -66,19 +67,233 @@ fn run_with_fixed_max_loops_resets_counters_per_execution() {
     .expect("first run");
 
     assert_eq!(first.reason, ExitReason::MaxLoopsReached);
-    assert_eq!(first.status.state, "max_loops_reached");
+    assert_eq!(first.status.state, RunState::MaxLoopsReached);
     assert_eq!(first.status.total_loops_executed, 1);
     assert_eq!(first.status.current_loop, 0);
 
     let second = run_loop(RunRequest {
         cwd: dir.path().to_path_buf(),
-        config: cfg,
+        config: cfg.clone(),
         max_loops: 1,
     })
     .expect("second run");
 
     assert_eq!(second.reason, ExitReason::MaxLoopsReached);
-    assert_eq!(second.status.state, "max_loops_reached");
+    assert_eq!(second.status.state, RunState::MaxLoopsReached);
     assert_eq!(second.status.total_loops_executed, 1);
     assert_eq!(second.status.current_loop, 0);
+
+    // Each run keeps its own captures; the second must not wipe the first.
+    let layout = RuntimeLayout::from_config(dir.path(), &cfg);
+    let first_id = first.status.run_id.as_deref().expect("first run id");
+    let second_id = second.status.run_id.as_deref().expect("second run id");
+    assert_ne!(first_id, second_id);
+    for run_id in [first_id, second_id] {
+        assert!(layout.iteration_meta_file(run_id, 1).exists(), "{run_id}");
+        assert!(layout.iteration_dir(run_id, 1).join("stdout.log").exists());
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn each_run_is_bounded_in_live_log_and_rotated_when_large() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    let script_path = dir.path().join("fake-codex-chatty.sh");
+    fs::write(
+        &script_path,
+        "#!/usr/bin/env bash
+echo 'still working'
+",
+    )
+    .expect("script write");
+
+    let mut perms = fs::metadata(&script_path).expect("metadata").permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&script_path, perms).expect("chmod");
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        format!("engine_cmd = \"{}\"\n", script_path.display()),
+    )
+    .expect("forgerc write");
+
+    let cfg = load_run_config(dir.path(), &isolated()).expect("config");
+    let first = run_loop(RunRequest {
+        cwd: dir.path().to_path_buf(),
+        config: cfg.clone(),
+        max_loops: 1,
+    })
+    .expect("first run");
+    let run_id = first.status.run_id.clone().expect("run id");
+    let layout = RuntimeLayout::from_config(dir.path(), &cfg);
+    let live_log = layout.live_log();
+    let live = fs::read_to_string(&live_log).expect("live log");
+    let entries = live
+        .lines()
+        .map(|line| serde_json::from_str::<LiveLogEntry>(line).expect("envelope"))
+        .collect::<Vec<_>>();
+    assert!(entries
+        .iter()
+        .all(|entry| entry.run_id.as_deref() == Some(run_id.as_str())));
+    assert_eq!(entries[0].run_event(), Some("started"));
+    let last = entries.last().expect("entries");
+    assert_eq!(last.run_event(), Some("finished"));
+    assert_eq!(last.payload["detail"], "max_loops_reached");
+    let engine_line = entries
+        .iter()
+        .find(|entry| entry.source == LogSource::EngineStdout)
+        .expect("engine output");
+    assert_eq!(engine_line.loop_number, Some(1));
+    assert_eq!(engine_line.text(), Some("still working"));
+
+    // A limit of exactly one run's output rotates the log before the second
+    // run but not again at its first loop.
+    let mut cfg = cfg;
+    cfg.live_log_max_bytes = live.len() as u64;
+    cfg.live_log_retention = 1;
+    run_loop(RunRequest {
+        cwd: dir.path().to_path_buf(),
+        config: cfg,
+        max_loops: 1,
+    })
+    .expect("second run");
+
+    let archives = list_log_archives(&layout.log_archive_dir());
+    assert_eq!(archives.len(), 1);
+    let live = fs::read_to_string(&live_log).expect("live log");
+    assert_eq!(live.matches(r#""event":"started""#).count(), 1);
+}
+
+#[cfg(unix)]
+#[test]
+fn custom_runtime_dir_holds_all_run_state() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    let script_path = dir.path().join("fake-codex-plan.sh");
+    let prompt_log = dir.path().join("prompt.log");
+    fs::write(
+        &script_path,
+        format!(
+            "#!/usr/bin/env bash\necho \"$*\" > '{}'\nsed -i 's/- \\[ \\]/- [x]/' state/plan.md\necho 'STATUS: COMPLETE'\necho 'EXIT_SIGNAL: true'\n",
+            prompt_log.display()
+        ),
+    )
+    .expect("script write");
+
+    let mut perms = fs::metadata(&script_path).expect("metadata").permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&script_path, perms).expect("chmod");
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        format!(
+            "engine_cmd = \"{}\"\nruntime_dir = \"state\"\n",
+            script_path.display()
+        ),
+    )
+    .expect("forgerc write");
+    fs::create_dir_all(dir.path().join("state")).expect("runtime dir");
+    fs::write(dir.path().join("state/plan.md"), "- [ ] ship it\n").expect("plan");
+
+    let cfg = load_run_config(dir.path(), &isolated()).expect("config");
+    let layout = RuntimeLayout::from_config(dir.path(), &cfg);
+    let outcome = run_loop(RunRequest {
+        cwd: dir.path().to_path_buf(),
+        config: cfg,
+        max_loops: 1,
+    })
+    .expect("run_loop");
+
+    assert_eq!(outcome.reason, ExitReason::Completed);
+    let prompt = fs::read_to_string(&prompt_log).expect("prompt log");
+    assert!(prompt.contains("- [ ] ship it"));
+    assert!(prompt.contains("Plan source: state/plan.md"));
+    for path in [
+        layout.status_file(),
+        layout.progress_file(),
+        layout.live_log(),
+        layout.call_count_file(),
+        layout.plan_history_file(),
+        layout
+            .iteration_dir(outcome.status.run_id.as_deref().expect("run id"), 1)
+            .join("stdout.log"),
+    ] {
+        assert!(path.starts_with(dir.path().join("state")));
+        assert!(path.exists(), "{} missing", path.display());
+    }
+    assert_eq!(read_plan_history(&layout).summary().remaining, 0);
+    assert!(!dir.path().join(".forge").exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn run_adapts_flags_to_the_detected_engine_version() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = tempdir().expect("tempdir");
+    let script_path = dir.path().join("fake-old-codex.sh");
+    let args_log = dir.path().join("args.log");
+    fs::write(
+        &script_path,
+        format!(
+            "#!/usr/bin/env bash\nif [[ \"$1\" == \"--version\" ]]; then echo 'codex-cli 0.33.0'; exit 0; fi\necho \"$*\" >> '{}'\necho 'STATUS: COMPLETE'\necho 'EXIT_SIGNAL: true'\n",
+            args_log.display()
+        ),
+    )
+    .expect("script write");
+
+    let mut perms = fs::metadata(&script_path).expect("metadata").permissions();
+    perms.set_mode(0o755);
+    fs::set_permissions(&script_path, perms).expect("chmod");
+
+    fs::write(
+        dir.path().join(".forgerc"),
+        format!(
+            "engine_cmd = \"{}\"\nengine_exec_args = [\"--ephemeral\"]\n",
+            script_path.display()
+        ),
+    )
+    .expect("forgerc write");
+
+    let cfg = load_run_config(dir.path(), &isolated()).expect("config");
+    let outcome = run_loop(RunRequest {
+        cwd: dir.path().to_path_buf(),
+        config: cfg,
+        max_loops: 1,
+    })
+    .expect("run_loop");
+
+    assert_eq!(outcome.reason, ExitReason::Completed);
+    let args = fs::read_to_string(&args_log).expect("args log");
+    assert!(args.starts_with("--config"));
+    assert!(!args.contains("--ephemeral"));
+    let live = fs::read_to_string(dir.path().join(".forge/live.log")).expect("live log");
+    assert!(live.contains("codex 0.33.0 does not support `--ephemeral`; omitting it"));
+
+    let cfg = load_run_config(
+        dir.path(),
+        &CliOverrides {
+            resume_last: true,
+            ..isolated()
+        },
+    )
+    .expect("config");
+    let err = run_loop(RunRequest {
+        cwd: dir.path().to_path_buf(),
+        config: cfg,
+        max_loops: 1,
+    })
+    .expect_err("resume --last needs codex 0.36");
+
+    assert!(err.to_string().contains("exec resume --last"));
+}
+
+fn isolated() -> CliOverrides {
+    CliOverrides {
+        no_user_config: true,
+        ..CliOverrides::default()
+    }
 }
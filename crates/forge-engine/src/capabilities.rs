@@ -0,0 +1,251 @@
+use crate::Engine;
+use forge_config::{ResumeMode, RunConfig};
+use serde::Serialize;
+use std::fmt;
+use std::process::Command;
+
+/// `major.minor.patch` as printed by `<engine> --version`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct EngineVersion {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+impl EngineVersion {
+    pub const fn new(major: u64, minor: u64, patch: u64) -> Self {
+        Self {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// First `x.y[.z]` in the text, e.g. `codex-cli 0.46.0` or `v0.5.1`.
+    pub fn parse(text: &str) -> Option<Self> {
+        text.split(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .find_map(|word| {
+                let mut parts = word.split('.').filter(|p| !p.is_empty());
+                let major = parts.next()?.parse().ok()?;
+                let minor = parts.next()?.parse().ok()?;
+                let patch = parts.next().and_then(|p| p.parse().ok()).unwrap_or(0);
+                Some(Self::new(major, minor, patch))
+            })
+    }
+}
+
+impl fmt::Display for EngineVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// A CLI feature forge relies on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    ExecJson,
+    ExecResume,
+    ResumeLast,
+    Ephemeral,
+    ReasoningSummaryConfig,
+    RunFormatJson,
+    RunSession,
+}
+
+impl Capability {
+    pub fn flag(self) -> &'static str {
+        match self {
+            Capability::ExecJson => "exec --json",
+            Capability::ExecResume => "exec resume <id>",
+            Capability::ResumeLast => "exec resume --last",
+            Capability::Ephemeral => "--ephemeral",
+            Capability::ReasoningSummaryConfig => "--config model_reasoning_summary",
+            Capability::RunFormatJson => "run --format json",
+            Capability::RunSession => "run --session / --continue",
+        }
+    }
+
+    /// Optional flags are dropped from argv on older CLIs; the rest refuse
+    /// the run when forge would need them.
+    pub fn is_optional(self) -> bool {
+        matches!(
+            self,
+            Capability::Ephemeral | Capability::ReasoningSummaryConfig
+        )
+    }
+}
+
+/// Oldest release known to accept each flag.
+pub const CODEX_CAPABILITIES: &[(Capability, EngineVersion)] = &[
+    (Capability::ExecJson, EngineVersion::new(0, 30, 0)),
+    (Capability::ExecResume, EngineVersion::new(0, 36, 0)),
+    (Capability::ResumeLast, EngineVersion::new(0, 36, 0)),
+    (
+        Capability::ReasoningSummaryConfig,
+        EngineVersion::new(0, 23, 0),
+    ),
+    (Capability::Ephemeral, EngineVersion::new(0, 50, 0)),
+];
+
+pub const OPENCODE_CAPABILITIES: &[(Capability, EngineVersion)] = &[
+    (Capability::RunFormatJson, EngineVersion::new(0, 4, 0)),
+    (Capability::RunSession, EngineVersion::new(0, 4, 0)),
+];
+
+/// Runs `<command> --version` and parses the result.
+pub fn probe_version(mut command: Command) -> Option<EngineVersion> {
+    let output = command.arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    EngineVersion::parse(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// True when the detected version has the capability, or when the engine
+/// does not list it or its version is unknown.
+pub fn supports(engine: &dyn Engine, capability: Capability) -> bool {
+    let Some(version) = engine.version() else {
+        return true;
+    };
+    engine
+        .capabilities()
+        .iter()
+        .find(|(cap, _)| *cap == capability)
+        .is_none_or(|(_, min)| version >= *min)
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct EngineCompatibility {
+    pub engine: String,
+    pub version: Option<EngineVersion>,
+    /// Optional flags that will be left out of argv.
+    pub dropped: Vec<Capability>,
+    /// Flags this run needs that the installed CLI lacks.
+    pub unsupported: Vec<Capability>,
+}
+
+impl EngineCompatibility {
+    pub fn is_refused(&self) -> bool {
+        !self.unsupported.is_empty()
+    }
+
+    /// Human-readable warnings, empty when everything is supported.
+    pub fn warnings(&self) -> Vec<String> {
+        let version = self
+            .version
+            .map(|v| v.to_string())
+            .unwrap_or_else(|| "unknown".to_string());
+        let mut out = Vec::new();
+        for cap in &self.unsupported {
+            out.push(format!(
+                "{} {} does not support `{}`",
+                self.engine,
+                version,
+                cap.flag()
+            ));
+        }
+        for cap in &self.dropped {
+            out.push(format!(
+                "{} {} does not support `{}`; omitting it",
+                self.engine,
+                version,
+                cap.flag()
+            ));
+        }
+        out
+    }
+}
+
+/// Checks the capabilities this run would use against the detected version.
+pub fn check_compatibility(engine: &dyn Engine, config: &RunConfig) -> EngineCompatibility {
+    let mut compat = EngineCompatibility {
+        engine: engine.name().to_string(),
+        version: engine.version(),
+        dropped: Vec::new(),
+        unsupported: Vec::new(),
+    };
+    for (capability, _) in engine.capabilities() {
+        if supports(engine, *capability) || !is_used(*capability, config) {
+            continue;
+        }
+        if capability.is_optional() {
+            compat.dropped.push(*capability);
+        } else {
+            compat.unsupported.push(*capability);
+        }
+    }
+    compat
+}
+
+fn is_used(capability: Capability, config: &RunConfig) -> bool {
+    match capability {
+        Capability::ExecJson | Capability::RunFormatJson => true,
+        Capability::ExecResume => matches!(config.resume_mode, ResumeMode::Explicit(_)),
+        Capability::ResumeLast => config.resume_mode == ResumeMode::Last,
+        Capability::RunSession => config.resume_mode != ResumeMode::New,
+        Capability::Ephemeral => config.engine_exec_args.iter().any(|a| a == "--ephemeral"),
+        Capability::ReasoningSummaryConfig => config
+            .engine_pre_args
+            .iter()
+            .any(|a| a.starts_with("model_reasoning_summary=")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::isolated;
+    use crate::CodexEngine;
+    use forge_config::{load_run_config, CliOverrides, EngineKind};
+
+    fn codex_config(exec_args: Vec<String>, resume_last: bool) -> RunConfig {
+        let dir = tempfile::tempdir().expect("tempdir");
+        load_run_config(
+            dir.path(),
+            &CliOverrides {
+                engine: Some(EngineKind::Codex),
+                engine_exec_args: Some(exec_args),
+                resume_last,
+                ..isolated()
+            },
+        )
+        .expect("config")
+    }
+
+    #[test]
+    fn parses_versions_from_cli_output() {
+        assert_eq!(
+            EngineVersion::parse("codex-cli 0.46.0\n"),
+            Some(EngineVersion::new(0, 46, 0))
+        );
+        assert_eq!(
+            EngineVersion::parse("v1.2"),
+            Some(EngineVersion::new(1, 2, 0))
+        );
+        assert_eq!(EngineVersion::parse("opencode dev build"), None);
+        assert!(EngineVersion::new(0, 36, 0) > EngineVersion::new(0, 9, 9));
+    }
+
+    #[test]
+    fn old_codex_drops_optional_flags_and_refuses_missing_resume() {
+        let config = codex_config(vec!["--ephemeral".into()], true);
+        let engine = CodexEngine::default().with_version(Some(EngineVersion::new(0, 33, 0)));
+
+        let compat = check_compatibility(&engine, &config);
+
+        assert_eq!(compat.dropped, vec![Capability::Ephemeral]);
+        assert_eq!(compat.unsupported, vec![Capability::ResumeLast]);
+        assert!(compat.is_refused());
+        assert!(compat.warnings()[0].contains("exec resume --last"));
+    }
+
+    #[test]
+    fn unknown_version_assumes_support() {
+        let config = codex_config(vec!["--ephemeral".into()], true);
+        let compat = check_compatibility(&CodexEngine::default(), &config);
+
+        assert!(compat.dropped.is_empty());
+        assert!(!compat.is_refused());
+    }
+}
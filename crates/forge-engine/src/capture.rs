@@ -0,0 +1,121 @@
+use anyhow::{Context, Result};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// Bytes of each stream kept in memory for summaries and completion checks.
+pub const OUTPUT_TAIL_BYTES: usize = 64 * 1024;
+
+pub const STDOUT_FILE: &str = "stdout.log";
+pub const STDERR_FILE: &str = "stderr.log";
+
+/// The last `limit` bytes of a stream, trimmed to a char boundary.
+#[derive(Debug, Clone)]
+pub struct OutputTail {
+    text: String,
+    limit: usize,
+}
+
+impl OutputTail {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            text: String::new(),
+            limit,
+        }
+    }
+
+    pub fn push(&mut self, chunk: &str) {
+        self.text.push_str(chunk);
+        // Trim lazily so a stream of short lines does not shift the buffer
+        // on every push.
+        if self.text.len() > self.limit.saturating_mul(2) {
+            self.trim();
+        }
+    }
+
+    pub fn into_string(mut self) -> String {
+        self.trim();
+        self.text
+    }
+
+    fn trim(&mut self) {
+        if self.text.len() <= self.limit {
+            return;
+        }
+        let mut start = self.text.len() - self.limit;
+        while !self.text.is_char_boundary(start) {
+            start += 1;
+        }
+        self.text.drain(..start);
+    }
+}
+
+/// One output stream written through to its iteration file, with a bounded
+/// tail kept in memory.
+pub(crate) struct StreamCapture {
+    path: PathBuf,
+    file: BufWriter<File>,
+    tail: OutputTail,
+}
+
+impl StreamCapture {
+    pub(crate) fn create(dir: &Path, name: &str) -> Result<Self> {
+        fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir.display()))?;
+        let path = dir.join(name);
+        let file =
+            File::create(&path).with_context(|| format!("failed to create {}", path.display()))?;
+        Ok(Self {
+            path,
+            file: BufWriter::new(file),
+            tail: OutputTail::new(OUTPUT_TAIL_BYTES),
+        })
+    }
+
+    pub(crate) fn push(&mut self, chunk: &str) -> Result<()> {
+        self.tail.push(chunk);
+        self.file
+            .write_all(chunk.as_bytes())
+            .with_context(|| format!("failed to write {}", self.path.display()))
+    }
+
+    pub(crate) fn finish(mut self) -> Result<(PathBuf, String)> {
+        self.file
+            .flush()
+            .with_context(|| format!("failed to write {}", self.path.display()))?;
+        Ok((self.path, self.tail.into_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tail_keeps_only_the_last_bytes_on_char_boundaries() {
+        let mut tail = OutputTail::new(8);
+        for _ in 0..100 {
+            tail.push("line é\n");
+        }
+        let text = tail.into_string();
+        assert!(text.len() <= 8);
+        assert!(text.ends_with("é\n"));
+    }
+
+    #[test]
+    fn capture_writes_the_whole_stream_to_disk() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let mut capture =
+            StreamCapture::create(&dir.path().join("loop-1"), STDOUT_FILE).expect("capture");
+        let line = "x".repeat(1024) + "\n";
+        for _ in 0..(OUTPUT_TAIL_BYTES / 1024 * 3) {
+            capture.push(&line).expect("push");
+        }
+        let (path, tail) = capture.finish().expect("finish");
+
+        assert_eq!(tail.len(), OUTPUT_TAIL_BYTES);
+        assert_eq!(
+            fs::metadata(path).expect("metadata").len() as usize,
+            OUTPUT_TAIL_BYTES * 3 + OUTPUT_TAIL_BYTES / 1024 * 3
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::output_parser::{OutputAnalyzer, TextAnalyzer};
+use forge_types::{OutputAnalysis, TokenUsage};
+use serde::Deserialize;
+
+/// One line of `codex exec --json` output.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type")]
+pub enum CodexEvent {
+    #[serde(rename = "thread.started")]
+    ThreadStarted { thread_id: String },
+    #[serde(rename = "turn.started")]
+    TurnStarted,
+    #[serde(rename = "turn.completed")]
+    TurnCompleted {
+        #[serde(default)]
+        usage: Option<TokenUsage>,
+    },
+    #[serde(rename = "turn.failed")]
+    TurnFailed { error: ErrorPayload },
+    #[serde(rename = "item.started")]
+    ItemStarted { item: CodexItem },
+    #[serde(rename = "item.updated")]
+    ItemUpdated { item: CodexItem },
+    #[serde(rename = "item.completed")]
+    ItemCompleted { item: CodexItem },
+    #[serde(rename = "token_count")]
+    TokenCount(TokenUsage),
+    #[serde(rename = "error")]
+    Error { message: String },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CodexItem {
+    AgentMessage {
+        #[serde(default)]
+        text: String,
+    },
+    Reasoning {
+        #[serde(default)]
+        text: String,
+    },
+    CommandExecution {
+        #[serde(default)]
+        command: String,
+        #[serde(default)]
+        aggregated_output: String,
+        #[serde(default)]
+        exit_code: Option<i64>,
+        #[serde(default)]
+        status: Option<String>,
+    },
+    FileChange {
+        #[serde(default)]
+        changes: Vec<FileChange>,
+        #[serde(default)]
+        status: Option<String>,
+    },
+    Error {
+        #[serde(default)]
+        message: String,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct FileChange {
+    pub path: String,
+    #[serde(default)]
+    pub kind: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ErrorPayload {
+    #[serde(default)]
+    pub message: String,
+}
+
+impl CodexItem {
+    /// A completed command that exited non-zero or reported `failed`.
+    pub fn is_failed_command(&self) -> bool {
+        match self {
+            CodexItem::CommandExecution {
+                exit_code, status, ..
+            } => status.as_deref() == Some("failed") || exit_code.is_some_and(|code| code != 0),
+            _ => false,
+        }
+    }
+}
+
+/// Parses every Codex event in `stdout`, skipping lines that are not events.
+pub fn parse_codex_events(stdout: &str) -> Vec<CodexEvent> {
+    stdout.lines().filter_map(parse_codex_event).collect()
+}
+
+/// One line of output as a Codex event, if it is one.
+pub fn parse_codex_event(line: &str) -> Option<CodexEvent> {
+    serde_json::from_str::<CodexEvent>(line.trim()).ok()
+}
+
+/// Text of the last completed agent message.
+pub fn final_agent_message(events: &[CodexEvent]) -> Option<&str> {
+    events.iter().rev().find_map(|event| match event {
+        CodexEvent::ItemCompleted {
+            item: CodexItem::AgentMessage { text },
+        } => Some(text.as_str()),
+        _ => None,
+    })
+}
+
+/// Messages of `error` events, failed turns and error items, in order.
+pub fn error_messages(events: &[CodexEvent]) -> Vec<&str> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            CodexEvent::Error { message } => Some(message.as_str()),
+            CodexEvent::TurnFailed { error } => Some(error.message.as_str()),
+            CodexEvent::ItemCompleted {
+                item: CodexItem::Error { message },
+            } => Some(message.as_str()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Builds the loop analysis from typed events: the exit gate and completion
+/// indicators only look at the final agent message, and errors only come from
+/// error events, so quoted protocol text or `error:` in a diff is ignored.
+pub fn analyze_codex_events(events: &[CodexEvent], indicators: &[String]) -> OutputAnalysis {
+    let mut analyzer = CodexAnalyzer::new(indicators);
+    for event in events {
+        analyzer.push_event(event);
+    }
+    analyzer.event_analysis()
+}
+
+/// Streaming form of `analyze_codex_events`. Output without any Codex event
+/// (older CLIs, wrappers) falls back to the text scanner.
+pub struct CodexAnalyzer {
+    indicators: Vec<String>,
+    saw_event: bool,
+    session_id: Option<String>,
+    final_message: Option<String>,
+    has_error: bool,
+    has_progress_hint: bool,
+    token_usage: Option<TokenUsage>,
+    text: TextAnalyzer,
+}
+
+impl CodexAnalyzer {
+    pub fn new(indicators: &[String]) -> Self {
+        Self {
+            indicators: indicators.to_vec(),
+            saw_event: false,
+            session_id: None,
+            final_message: None,
+            has_error: false,
+            has_progress_hint: false,
+            token_usage: None,
+            text: TextAnalyzer::new(indicators),
+        }
+    }
+
+    pub fn push_event(&mut self, event: &CodexEvent) {
+        self.saw_event = true;
+        match event {
+            CodexEvent::ThreadStarted { thread_id } => {
+                self.session_id.get_or_insert_with(|| thread_id.clone());
+            }
+            CodexEvent::TurnCompleted { usage: Some(usage) } => {
+                self.token_usage
+                    .get_or_insert_with(TokenUsage::default)
+                    .add(usage);
+            }
+            CodexEvent::Error { .. } | CodexEvent::TurnFailed { .. } => self.has_error = true,
+            CodexEvent::ItemCompleted { item } => match item {
+                CodexItem::AgentMessage { text } => self.final_message = Some(text.clone()),
+                CodexItem::FileChange { .. } => self.has_progress_hint = true,
+                CodexItem::Error { .. } => self.has_error = true,
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    fn event_analysis(self) -> OutputAnalysis {
+        let message = self.final_message.as_deref().unwrap_or_default();
+        OutputAnalysis {
+            exit_signal_true: has_exit_signal(message),
+            completion_indicators: self
+                .indicators
+                .iter()
+                .filter(|indicator| message.contains(indicator.as_str()))
+                .count() as u32,
+            has_error: self.has_error,
+            has_progress_hint: self.has_progress_hint,
+            session_id: self.session_id,
+            final_message: self.final_message,
+            token_usage: self.token_usage,
+        }
+    }
+}
+
+impl OutputAnalyzer for CodexAnalyzer {
+    fn push_stdout_line(&mut self, line: &str) {
+        if let Some(event) = parse_codex_event(line) {
+            self.push_event(&event);
+        }
+        self.text.push_stdout_line(line);
+    }
+
+    fn push_stderr_line(&mut self, line: &str) {
+        self.text.push_stderr_line(line);
+    }
+
+    fn finish(self: Box<Self>) -> OutputAnalysis {
+        if self.saw_event {
+            self.event_analysis()
+        } else {
+            Box::new(self.text).finish()
+        }
+    }
+}
+
+/// True when `message` carries `EXIT_SIGNAL: true` outside inline code spans,
+/// so an agent explaining the protocol does not end the run.
+pub(crate) fn has_exit_signal(message: &str) -> bool {
+    message
+        .split('`')
+        .step_by(2)
+        .any(|text| text.to_ascii_lowercase().contains("exit_signal: true"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = r#"{"type":"thread.started","thread_id":"th-1"}
+{"type":"turn.started"}
+{"type":"item.started","item":{"id":"i0","type":"command_execution","command":"cargo test","aggregated_output":"","status":"in_progress"}}
+{"type":"item.completed","item":{"id":"i0","type":"command_execution","command":"cargo test","aggregated_output":"error: expected `;`","exit_code":101,"status":"failed"}}
+{"type":"item.completed","item":{"id":"i1","type":"reasoning","text":"I should print EXIT_SIGNAL: true once done"}}
+{"type":"item.completed","item":{"id":"i2","type":"file_change","changes":[{"path":"src/lib.rs","kind":"update"}],"status":"completed"}}
+{"type":"item.completed","item":{"id":"i3","type":"agent_message","text":"Fixed the parser.\nSTATUS: COMPLETE\nEXIT_SIGNAL: true"}}
+{"type":"token_count","input_tokens":120,"output_tokens":30}
+{"type":"turn.completed","usage":{"input_tokens":120,"cached_input_tokens":10,"output_tokens":30}}
+{"type":"some.future_event","payload":1}
+not json"#;
+
+    #[test]
+    fn parses_known_and_unknown_events() {
+        let events = parse_codex_events(TRANSCRIPT);
+
+        assert_eq!(events.len(), 10);
+        assert_eq!(
+            events[0],
+            CodexEvent::ThreadStarted {
+                thread_id: "th-1".into()
+            }
+        );
+        assert!(matches!(
+            &events[3],
+            CodexEvent::ItemCompleted { item } if item.is_failed_command()
+        ));
+        assert_eq!(
+            events[7],
+            CodexEvent::TokenCount(TokenUsage {
+                input_tokens: 120,
+                output_tokens: 30,
+                ..TokenUsage::default()
+            })
+        );
+        assert_eq!(events[9], CodexEvent::Unknown);
+    }
+
+    #[test]
+    fn analysis_uses_final_message_and_error_events_only() {
+        let events = parse_codex_events(TRANSCRIPT);
+        let analysis = analyze_codex_events(&events, &["STATUS: COMPLETE".into()]);
+
+        assert!(analysis.exit_signal_true);
+        assert_eq!(analysis.completion_indicators, 1);
+        assert!(!analysis.has_error);
+        assert!(analysis.has_progress_hint);
+        assert_eq!(analysis.session_id.as_deref(), Some("th-1"));
+        assert_eq!(
+            analysis.token_usage,
+            Some(TokenUsage {
+                input_tokens: 120,
+                cached_input_tokens: 10,
+                output_tokens: 30,
+                reasoning_tokens: 0,
+            })
+        );
+    }
+
+    #[test]
+    fn quoted_protocol_does_not_trigger_exit_gate() {
+        let stdout = r#"{"type":"item.completed","item":{"type":"command_execution","command":"cat PROMPT.md","aggregated_output":"print EXIT_SIGNAL: true and STATUS: COMPLETE","exit_code":0,"status":"completed"}}
+{"type":"item.completed","item":{"type":"agent_message","text":"Still working; I will report `EXIT_SIGNAL: true` when done."}}"#;
+        let analysis =
+            analyze_codex_events(&parse_codex_events(stdout), &["STATUS: COMPLETE".into()]);
+
+        assert!(!analysis.exit_signal_true);
+        assert_eq!(analysis.completion_indicators, 0);
+    }
+
+    #[test]
+    fn collects_real_error_events() {
+        let stdout = r#"{"type":"error","message":"stream disconnected"}
+{"type":"turn.failed","error":{"message":"usage limit reached"}}"#;
+        let events = parse_codex_events(stdout);
+
+        assert_eq!(
+            error_messages(&events),
+            vec!["stream disconnected", "usage limit reached"]
+        );
+        assert!(analyze_codex_events(&events, &[]).has_error);
+    }
+}
@@ -0,0 +1,430 @@
+use crate::output_parser::{OutputAnalyzer, TextAnalyzer};
+use crate::{engine_command, prompt_file_path, Engine, EngineExecParams};
+use forge_config::{CustomEngineConfig, OutputFormat, PromptDelivery, ResumeMode};
+use forge_types::OutputAnalysis;
+use serde_json::Value;
+use std::env;
+use std::path::Path;
+
+/// Engine described entirely by the `[custom_engine]` table in `.forgerc`.
+pub struct CustomEngine {
+    config: CustomEngineConfig,
+    command: String,
+    env: Option<Vec<(String, String)>>,
+}
+
+impl CustomEngine {
+    pub fn new(config: CustomEngineConfig, command: String) -> Self {
+        Self {
+            config,
+            command,
+            env: None,
+        }
+    }
+
+    /// Environment for the availability probe; `None` inherits.
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    fn expand(&self, params: &EngineExecParams) -> Vec<String> {
+        let mut out = Vec::new();
+        for arg in &self.config.args {
+            match arg.as_str() {
+                "{resume}" => {
+                    let resume = match &params.config.resume_mode {
+                        ResumeMode::New => &[][..],
+                        ResumeMode::Explicit(_) => &self.config.resume_args[..],
+                        ResumeMode::Last => &self.config.resume_last_args[..],
+                    };
+                    out.extend(resume.iter().map(|arg| substitute(arg, params)));
+                }
+                "{exec_args}" => out.extend(params.config.engine_exec_args.iter().cloned()),
+                _ => out.push(substitute(arg, params)),
+            }
+        }
+        out
+    }
+}
+
+fn substitute(arg: &str, params: &EngineExecParams) -> String {
+    let session_id = match &params.config.resume_mode {
+        ResumeMode::Explicit(id) => id.as_str(),
+        _ => "",
+    };
+    let prompt_file = prompt_file_path(params).display().to_string();
+    let placeholders = [
+        ("{prompt_file}", prompt_file.as_str()),
+        ("{prompt}", params.prompt.as_deref().unwrap_or_default()),
+        ("{session_id}", session_id),
+    ];
+    // One pass, so placeholder text inside a substituted value (a prompt
+    // that mentions `{session_id}`) is left as written.
+    let mut out = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find('{') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        match placeholders.iter().find(|(name, _)| rest.starts_with(name)) {
+            Some((name, value)) => {
+                out.push_str(value);
+                rest = &rest[name.len()..];
+            }
+            None => {
+                out.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+impl Engine for CustomEngine {
+    fn name(&self) -> &str {
+        &self.config.name
+    }
+
+    /// The transport is fixed by `prompt_delivery` in the table, so the
+    /// selected delivery is always that one.
+    fn build_args(&self, params: &EngineExecParams, _delivery: PromptDelivery) -> Vec<String> {
+        let mut args = params.config.engine_pre_args.clone();
+        if let Some(model) = &params.config.model {
+            args.extend(
+                self.config
+                    .model_args
+                    .iter()
+                    .map(|arg| arg.replace("{model}", model)),
+            );
+        }
+        if let Some(effort) = params.config.reasoning_effort {
+            args.extend(
+                self.config
+                    .reasoning_effort_args
+                    .iter()
+                    .map(|arg| arg.replace("{reasoning_effort}", effort.as_str())),
+            );
+        }
+        args.extend(self.expand(params));
+
+        let mentions_prompt = self
+            .config
+            .args
+            .iter()
+            .chain(&self.config.resume_args)
+            .chain(&self.config.resume_last_args)
+            .any(|arg| arg.contains("{prompt}"));
+        if self.config.prompt_delivery == PromptDelivery::Argv && !mentions_prompt {
+            if let Some(prompt) = &params.prompt {
+                args.push(prompt.clone());
+            }
+        }
+        args
+    }
+
+    fn output_analyzer(&self, indicators: &[String]) -> Box<dyn OutputAnalyzer> {
+        Box::new(CustomAnalyzer {
+            text: TextAnalyzer::new(indicators),
+            output_format: self.config.output_format,
+            session_id_fields: self.config.session_id_fields.clone(),
+            session_id_markers: self.config.session_id_markers.clone(),
+            session_id: None,
+            previous_line: String::new(),
+        })
+    }
+
+    fn is_available(&self) -> bool {
+        match self.config.probe.split_first() {
+            Some((program, args)) => engine_command(program, self.env.as_deref())
+                .args(args)
+                .output()
+                .map(|o| o.status.success())
+                .unwrap_or(false),
+            None => command_on_path(&self.command),
+        }
+    }
+
+    fn prompt_deliveries(&self) -> Vec<PromptDelivery> {
+        vec![self.config.prompt_delivery]
+    }
+}
+
+/// Text scan plus the session id rules from the `[custom_engine]` table.
+struct CustomAnalyzer {
+    text: TextAnalyzer,
+    output_format: OutputFormat,
+    session_id_fields: Vec<String>,
+    session_id_markers: Vec<String>,
+    session_id: Option<String>,
+    /// A marker at the end of one line takes its word from the next.
+    previous_line: String,
+}
+
+impl CustomAnalyzer {
+    fn find_marker(&mut self, line: &str) {
+        if self.output_format == OutputFormat::Plain && self.session_id.is_none() {
+            let text = format!("{}\n{line}", self.previous_line);
+            self.session_id = find_marked_word(&text, &self.session_id_markers);
+            self.previous_line = line.to_string();
+        }
+    }
+}
+
+impl OutputAnalyzer for CustomAnalyzer {
+    fn push_stdout_line(&mut self, line: &str) {
+        self.text.push_stdout_line(line);
+        if self.output_format == OutputFormat::Jsonl
+            && !self.session_id_fields.is_empty()
+            && self.session_id.is_none()
+        {
+            self.session_id = serde_json::from_str::<Value>(line)
+                .ok()
+                .and_then(|value| find_json_field(&value, &self.session_id_fields));
+        }
+        self.find_marker(line);
+    }
+
+    fn push_stderr_line(&mut self, line: &str) {
+        self.text.push_stderr_line(line);
+        self.find_marker(line);
+    }
+
+    fn finish(self: Box<Self>) -> OutputAnalysis {
+        let mut analysis = Box::new(self.text).finish();
+        if self.output_format == OutputFormat::Plain || !self.session_id_fields.is_empty() {
+            analysis.session_id = self.session_id;
+        }
+        analysis
+    }
+}
+
+fn find_json_field(value: &Value, fields: &[String]) -> Option<String> {
+    match value {
+        Value::Object(map) => fields
+            .iter()
+            .find_map(|field| match map.get(field) {
+                Some(Value::String(v)) if !v.is_empty() => Some(v.clone()),
+                _ => None,
+            })
+            .or_else(|| map.values().find_map(|v| find_json_field(v, fields))),
+        Value::Array(items) => items.iter().find_map(|v| find_json_field(v, fields)),
+        _ => None,
+    }
+}
+
+fn find_marked_word(text: &str, markers: &[String]) -> Option<String> {
+    markers.iter().find_map(|marker| {
+        let (_, rest) = text.split_once(marker.as_str())?;
+        rest.split_whitespace()
+            .next()
+            .map(|word| word.trim_matches(|c: char| "\"'`,.;".contains(c)))
+            .filter(|word| !word.is_empty())
+            .map(ToString::to_string)
+    })
+}
+
+fn command_on_path(command: &str) -> bool {
+    let path = Path::new(command);
+    if path.components().count() > 1 {
+        return path.is_file();
+    }
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| dir.join(command).is_file()))
+        .unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::isolated;
+    use crate::LiveLog;
+    use forge_config::load_run_config;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn engine_with(forgerc: &str) -> (CustomEngine, forge_config::RunConfig, tempfile::TempDir) {
+        let dir = tempdir().expect("tempdir");
+        fs::write(dir.path().join(".forgerc"), forgerc).expect("write forgerc");
+        let config = load_run_config(dir.path(), &isolated()).expect("config");
+        let engine = CustomEngine::new(
+            config.custom_engine.clone().expect("custom engine"),
+            config.engine_cmd.clone(),
+        );
+        (engine, config, dir)
+    }
+
+    #[test]
+    fn expands_prompt_and_resume_placeholders() {
+        let (engine, mut config, dir) = engine_with(
+            r#"
+engine = "custom"
+[custom_engine]
+name = "aider"
+command = "aider"
+args = ["--yes", "{resume}", "--message", "{prompt}"]
+resume_args = ["--session", "{session_id}"]
+resume_last_args = ["--continue"]
+"#,
+        );
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let params = |config: &forge_config::RunConfig| {
+            engine.build_args(
+                &EngineExecParams {
+                    cwd: dir.path(),
+                    config,
+                    prompt: Some("do it".into()),
+                    live_log: &live_log,
+                    output_dir: dir.path(),
+                },
+                PromptDelivery::Argv,
+            )
+        };
+
+        assert_eq!(engine.name(), "aider");
+        assert_eq!(config.engine_cmd, "aider");
+        assert_eq!(params(&config), vec!["--yes", "--message", "do it"]);
+
+        config.resume_mode = ResumeMode::Explicit("s-1".into());
+        assert_eq!(
+            params(&config),
+            vec!["--yes", "--session", "s-1", "--message", "do it"]
+        );
+
+        config.resume_mode = ResumeMode::Last;
+        assert_eq!(
+            params(&config),
+            vec!["--yes", "--continue", "--message", "do it"]
+        );
+    }
+
+    #[test]
+    fn placeholders_inside_the_prompt_are_left_alone() {
+        let (engine, mut config, dir) = engine_with(
+            r#"
+engine = "custom"
+[custom_engine]
+command = "agent"
+args = ["{resume}", "--message={prompt}"]
+resume_args = ["--session", "{session_id}"]
+"#,
+        );
+        config.resume_mode = ResumeMode::Explicit("s-1".into());
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let args = engine.build_args(
+            &EngineExecParams {
+                cwd: dir.path(),
+                config: &config,
+                prompt: Some("explain {session_id} and {prompt_file} {".into()),
+                live_log: &live_log,
+                output_dir: dir.path(),
+            },
+            PromptDelivery::Argv,
+        );
+        assert_eq!(
+            args,
+            vec![
+                "--session",
+                "s-1",
+                "--message=explain {session_id} and {prompt_file} {"
+            ]
+        );
+    }
+
+    #[test]
+    fn prompt_is_appended_or_referenced_by_file() {
+        let (engine, config, dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"agent\"\nargs = [\"run\"]\n",
+        );
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let args = engine.build_args(
+            &EngineExecParams {
+                cwd: dir.path(),
+                config: &config,
+                prompt: Some("hello".into()),
+                live_log: &live_log,
+                output_dir: dir.path(),
+            },
+            PromptDelivery::Argv,
+        );
+        assert_eq!(args, vec!["run", "hello"]);
+
+        let (engine, config, dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"agent\"\nargs = [\"--input={prompt_file}\"]\nprompt_delivery = \"file\"\n",
+        );
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let params = EngineExecParams {
+            cwd: dir.path(),
+            config: &config,
+            prompt: Some("hello".into()),
+            live_log: &live_log,
+            output_dir: dir.path(),
+        };
+        assert_eq!(
+            engine.build_args(&params, PromptDelivery::File),
+            vec![format!("--input={}", prompt_file_path(&params).display())]
+        );
+        assert_eq!(engine.prompt_deliveries(), vec![PromptDelivery::File]);
+    }
+
+    #[test]
+    fn model_and_effort_use_their_arg_templates() {
+        let (engine, mut config, dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"agent\"\nargs = [\"run\"]\nmodel_args = [\"--model={model}\"]\nreasoning_effort_args = [\"--think\", \"{reasoning_effort}\"]\n",
+        );
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let args = |config: &forge_config::RunConfig| {
+            engine.build_args(
+                &EngineExecParams {
+                    cwd: dir.path(),
+                    config,
+                    prompt: Some("hello".into()),
+                    live_log: &live_log,
+                    output_dir: dir.path(),
+                },
+                PromptDelivery::Argv,
+            )
+        };
+        assert_eq!(args(&config), vec!["run", "hello"]);
+
+        config.model = Some("qwen3".into());
+        config.reasoning_effort = Some(forge_config::ReasoningEffort::Medium);
+        assert_eq!(
+            args(&config),
+            vec!["--model=qwen3", "--think", "medium", "run", "hello"]
+        );
+    }
+
+    #[test]
+    fn extracts_session_id_from_plain_and_jsonl_output() {
+        let (plain, _, _dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"agent\"\nargs = [\"run\"]\nsession_id_markers = [\"Session:\"]\n",
+        );
+        let analysis = plain.parse_output("started\nSession: abc-42.\ndone", "", &[]);
+        assert_eq!(analysis.session_id.as_deref(), Some("abc-42"));
+
+        let (jsonl, _, _dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"agent\"\nargs = [\"run\"]\noutput_format = \"jsonl\"\nsession_id_fields = [\"chat_id\"]\n",
+        );
+        let analysis = jsonl.parse_output(
+            "{\"type\":\"start\",\"id\":\"msg-1\",\"meta\":{\"chat_id\":\"c-7\"}}\n",
+            "",
+            &[],
+        );
+        assert_eq!(analysis.session_id.as_deref(), Some("c-7"));
+    }
+
+    #[test]
+    fn availability_uses_probe_then_path() {
+        let (engine, _, _dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"agent\"\nargs = [\"run\"]\nprobe = [\"false\"]\n",
+        );
+        assert!(!engine.is_available());
+
+        let (engine, _, _dir) = engine_with(
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"definitely-not-installed-agent\"\nargs = [\"run\"]\n",
+        );
+        assert!(!engine.is_available());
+        assert!(command_on_path("sh"));
+    }
+}
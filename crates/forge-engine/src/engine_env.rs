@@ -0,0 +1,165 @@
+use forge_config::RunConfig;
+use serde::Serialize;
+use std::env;
+use std::process::Command;
+
+/// Where a variable in the engine's environment comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EnvSource {
+    Inherited,
+    Explicit,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EngineEnvVar {
+    pub name: String,
+    pub value: String,
+    pub source: EnvSource,
+}
+
+impl EngineEnvVar {
+    /// `NAME=****` line for diagnostics; values are never printed.
+    pub fn masked(&self) -> String {
+        format!("{}={}", self.name, mask_env_value(&self.value))
+    }
+}
+
+/// Hides a value while still showing whether it is set.
+pub fn mask_env_value(value: &str) -> String {
+    if value.is_empty() {
+        String::new()
+    } else {
+        "****".to_string()
+    }
+}
+
+/// Exact name, or a prefix when the pattern ends in `*`.
+fn matches_pattern(name: &str, pattern: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => name.starts_with(prefix),
+        None => name == pattern,
+    }
+}
+
+/// Applies `engine_env_clear`, the allow- and denylists, then `engine_env`
+/// to the inherited variables. The result is sorted by name.
+pub fn resolve_engine_env(
+    config: &RunConfig,
+    inherited: impl IntoIterator<Item = (String, String)>,
+) -> Vec<EngineEnvVar> {
+    let allow = &config.engine_env_allowlist;
+    let restrict = config.engine_env_clear || !allow.is_empty();
+    let mut vars = inherited
+        .into_iter()
+        .filter(|(name, _)| !restrict || allow.iter().any(|p| matches_pattern(name, p)))
+        .filter(|(name, _)| {
+            !config
+                .engine_env_denylist
+                .iter()
+                .any(|p| matches_pattern(name, p))
+        })
+        .filter(|(name, _)| !config.engine_env.contains_key(name))
+        .map(|(name, value)| EngineEnvVar {
+            name,
+            value,
+            source: EnvSource::Inherited,
+        })
+        .collect::<Vec<_>>();
+    vars.extend(config.engine_env.iter().map(|(name, value)| EngineEnvVar {
+        name: name.clone(),
+        value: value.clone(),
+        source: EnvSource::Explicit,
+    }));
+    vars.sort_by(|a, b| a.name.cmp(&b.name));
+    vars
+}
+
+/// The engine environment built from this process's variables. Variables
+/// that are not valid UTF-8 are not passed on.
+pub fn effective_engine_env(config: &RunConfig) -> Vec<EngineEnvVar> {
+    let inherited = env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value.into_string().ok()?)));
+    resolve_engine_env(config, inherited)
+}
+
+/// `effective_engine_env` as name/value pairs for `Command::envs`.
+pub fn engine_env(config: &RunConfig) -> Vec<(String, String)> {
+    effective_engine_env(config)
+        .into_iter()
+        .map(|var| (var.name, var.value))
+        .collect()
+}
+
+/// `Command` for the engine binary with exactly the given environment, or
+/// the inherited one when `env` is `None`.
+pub fn engine_command(program: &str, env: Option<&[(String, String)]>) -> Command {
+    let mut command = Command::new(program);
+    if let Some(vars) = env {
+        command.env_clear().envs(vars.iter().cloned());
+    }
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::isolated;
+    use forge_config::load_run_config;
+
+    fn config_with(forgerc: &str) -> RunConfig {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join(".forgerc"), forgerc).expect("forgerc write");
+        load_run_config(dir.path(), &isolated()).expect("config")
+    }
+
+    fn inherited() -> Vec<(String, String)> {
+        [
+            ("PATH", "/usr/bin"),
+            ("HOME", "/home/dev"),
+            ("OPENAI_API_KEY", "sk-secret"),
+            ("GITHUB_TOKEN", "ghp_secret"),
+        ]
+        .into_iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+    }
+
+    fn names(vars: &[EngineEnvVar]) -> Vec<&str> {
+        vars.iter().map(|v| v.name.as_str()).collect()
+    }
+
+    #[test]
+    fn inherits_everything_by_default() {
+        let vars = resolve_engine_env(&config_with(""), inherited());
+        assert_eq!(
+            names(&vars),
+            vec!["GITHUB_TOKEN", "HOME", "OPENAI_API_KEY", "PATH"]
+        );
+    }
+
+    #[test]
+    fn denylist_and_explicit_vars_apply_after_inheritance() {
+        let config = config_with(
+            "engine_env_denylist = [\"GITHUB_*\"]\n[engine_env]\nHOME = \"/tmp/agent\"\n",
+        );
+        let vars = resolve_engine_env(&config, inherited());
+
+        assert_eq!(names(&vars), vec!["HOME", "OPENAI_API_KEY", "PATH"]);
+        assert_eq!(vars[0].value, "/tmp/agent");
+        assert_eq!(vars[0].source, EnvSource::Explicit);
+    }
+
+    #[test]
+    fn clear_keeps_only_allowlisted_vars() {
+        let config = config_with("engine_env_clear = true\n");
+        assert!(resolve_engine_env(&config, inherited()).is_empty());
+
+        let config = config_with(
+            "engine_env_clear = true\nengine_env_allowlist = [\"PATH\", \"OPENAI_*\"]\n",
+        );
+        let vars = resolve_engine_env(&config, inherited());
+        assert_eq!(names(&vars), vec!["OPENAI_API_KEY", "PATH"]);
+        assert_eq!(vars[0].masked(), "OPENAI_API_KEY=****");
+    }
+}
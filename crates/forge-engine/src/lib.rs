@@ -1,24 +1,65 @@
+mod capabilities;
+mod capture;
+pub mod codex_events;
+mod custom;
+mod engine_env;
+mod live_log;
+mod mock;
+pub mod opencode_events;
 mod output_parser;
-
-use anyhow::{Context, Result};
-use chrono::Local;
-use forge_config::{EngineKind, ResumeMode, RunConfig, ThinkingMode};
-use forge_types::OutputAnalysis;
-use output_parser::OutputParser;
-use std::fs;
-use std::io::{BufRead, BufReader, Write};
-use std::path::Path;
-use std::process::{Command, Stdio};
+mod prompt_transport;
+
+use anyhow::{bail, Context, Result};
+use capture::StreamCapture;
+use forge_config::{EngineKind, PromptDelivery, ResumeMode, RunConfig, ThinkingMode};
+use forge_types::{LogLevel, LogSource, OutputAnalysis};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::mpsc;
 use std::thread;
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
+pub use capabilities::{
+    check_compatibility, probe_version, supports, Capability, EngineCompatibility, EngineVersion,
+    CODEX_CAPABILITIES, OPENCODE_CAPABILITIES,
+};
+pub use capture::{OutputTail, OUTPUT_TAIL_BYTES, STDERR_FILE, STDOUT_FILE};
+pub use codex_events::{
+    analyze_codex_events, error_messages, final_agent_message, parse_codex_event,
+    parse_codex_events, CodexAnalyzer, CodexEvent, CodexItem,
+};
+pub use custom::CustomEngine;
+pub use engine_env::{
+    effective_engine_env, engine_command, engine_env, mask_env_value, resolve_engine_env,
+    EngineEnvVar, EnvSource,
+};
+pub use live_log::LiveLog;
+pub use mock::{MockEngine, MockScenario};
+pub use opencode_events::{
+    analyze_opencode_events, opencode_error_messages, opencode_final_message, parse_opencode_event,
+    parse_opencode_events, OpenCodeAnalyzer, OpenCodeEvent,
+};
+pub use output_parser::{analyze_output, OutputAnalyzer, TextAnalyzer};
+pub use prompt_transport::{
+    describe_prompt_delivery, file_reference_prompt, prompt_file_path, select_prompt_delivery,
+    spawn_engine, ARGV_PROMPT_LIMIT_BYTES,
+};
+
 const NO_OUTPUT_WATCHDOG_SECS: u64 = 120;
 
+/// Outcome of one engine call. The full output stays on disk; only bounded
+/// tails are held in memory.
 #[derive(Debug)]
 pub struct EngineRunResult {
-    pub stdout: String,
-    pub stderr: String,
+    pub stdout_path: PathBuf,
+    pub stderr_path: PathBuf,
+    /// Last `OUTPUT_TAIL_BYTES` of stdout.
+    pub stdout_tail: String,
+    /// Last `OUTPUT_TAIL_BYTES` of stderr.
+    pub stderr_tail: String,
+    /// The engine's analyzer, run over every line as it arrived.
+    pub analysis: OutputAnalysis,
     pub exit_ok: bool,
     pub timed_out: bool,
 }
@@ -28,87 +69,303 @@ pub struct EngineExecParams<'a> {
     pub cwd: &'a Path,
     pub config: &'a RunConfig,
     pub prompt: Option<String>,
-    pub live_log_path: &'a Path,
+    pub live_log: &'a LiveLog,
+    /// Receives `stdout.log` and `stderr.log` for this call.
+    pub output_dir: &'a Path,
 }
 
 pub trait Engine {
-    fn name(&self) -> &'static str;
-    fn build_args(&self, params: &EngineExecParams) -> Vec<String>;
+    fn name(&self) -> &str;
+    fn build_args(&self, params: &EngineExecParams, delivery: PromptDelivery) -> Vec<String>;
+    /// Incremental parser fed each output line while the engine runs.
+    fn output_analyzer(&self, indicators: &[String]) -> Box<dyn OutputAnalyzer> {
+        Box::new(TextAnalyzer::new(indicators))
+    }
+    /// Analysis of output that was captured in full.
     fn parse_output(&self, stdout: &str, stderr: &str, indicators: &[String]) -> OutputAnalysis {
-        OutputParser::parse(stdout, stderr, indicators)
+        analyze_output(self.output_analyzer(indicators), stdout, stderr)
     }
     fn is_available(&self) -> bool;
+    /// Prompt transports the engine accepts, in order of preference.
+    fn prompt_deliveries(&self) -> Vec<PromptDelivery> {
+        vec![PromptDelivery::Argv]
+    }
+    /// Rewrites one stdout line for the live log according to the thinking
+    /// mode; `None` drops it. The captured stdout is never altered.
+    fn live_log_line(&self, line: &str, _mode: ThinkingMode) -> Option<String> {
+        Some(line.to_string())
+    }
+    /// Version found by the last `detect_version`, if any.
+    fn version(&self) -> Option<EngineVersion> {
+        None
+    }
+    /// Asks the installed CLI for its version and remembers it, so
+    /// `build_args` can leave out flags it does not understand.
+    fn detect_version(&mut self) {}
+    /// Minimum versions for the flags forge passes.
+    fn capabilities(&self) -> &'static [(Capability, EngineVersion)] {
+        &[]
+    }
+    /// Called by `spawn_engine` once the process is running, so per-call
+    /// state moves on here rather than in `build_args`.
+    fn spawned(&self) {}
+}
+
+#[derive(Debug, Clone)]
+pub struct CodexEngine {
+    command: String,
+    version: Option<EngineVersion>,
+    env: Option<Vec<(String, String)>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct OpenCodeEngine {
+    command: String,
+    version: Option<EngineVersion>,
+    env: Option<Vec<(String, String)>>,
+}
+
+impl CodexEngine {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            version: None,
+            env: None,
+        }
+    }
+
+    pub fn with_version(mut self, version: Option<EngineVersion>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Environment for version and availability probes; `None` inherits.
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    fn probe_command(&self) -> Command {
+        engine_command(&self.command, self.env.as_deref())
+    }
+}
+
+impl Default for CodexEngine {
+    fn default() -> Self {
+        Self::new(
+            EngineKind::Codex
+                .default_cmd()
+                .expect("codex has a default command"),
+        )
+    }
+}
+
+impl OpenCodeEngine {
+    pub fn new(command: impl Into<String>) -> Self {
+        Self {
+            command: command.into(),
+            version: None,
+            env: None,
+        }
+    }
+
+    pub fn with_version(mut self, version: Option<EngineVersion>) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Environment for version and availability probes; `None` inherits.
+    pub fn with_env(mut self, env: Vec<(String, String)>) -> Self {
+        self.env = Some(env);
+        self
+    }
+
+    fn probe_command(&self) -> Command {
+        engine_command(&self.command, self.env.as_deref())
+    }
 }
 
-pub struct CodexEngine;
+impl Default for OpenCodeEngine {
+    fn default() -> Self {
+        Self::new(
+            EngineKind::OpenCode
+                .default_cmd()
+                .expect("opencode has a default command"),
+        )
+    }
+}
 
-pub struct OpenCodeEngine;
+fn command_succeeds(mut command: Command) -> bool {
+    command
+        .arg("--version")
+        .output()
+        .map(|o| o.status.success())
+        .unwrap_or(false)
+}
 
 impl Engine for CodexEngine {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "codex"
     }
 
-    fn build_args(&self, params: &EngineExecParams) -> Vec<String> {
-        let mut args = params.config.engine_pre_args.clone();
-        args.extend(self.build_exec_args(params));
+    fn build_args(&self, params: &EngineExecParams, delivery: PromptDelivery) -> Vec<String> {
+        let mut args = self.pre_args(params.config);
+        if let Some(effort) = params.config.reasoning_effort {
+            args.extend([
+                "--config".into(),
+                format!(r#"model_reasoning_effort="{}""#, effort.as_str()),
+            ]);
+        }
+        args.extend(self.build_exec_args(params, delivery));
         args
     }
 
+    /// Uses the typed `--json` event stream; plain-text output (older CLIs,
+    /// wrappers) still goes through the text scanner.
+    fn output_analyzer(&self, indicators: &[String]) -> Box<dyn OutputAnalyzer> {
+        Box::new(CodexAnalyzer::new(indicators))
+    }
+
+    /// `codex exec -` reads the prompt from stdin.
+    fn prompt_deliveries(&self) -> Vec<PromptDelivery> {
+        vec![
+            PromptDelivery::Argv,
+            PromptDelivery::Stdin,
+            PromptDelivery::File,
+        ]
+    }
+
     fn is_available(&self) -> bool {
-        Command::new("codex")
-            .arg("--version")
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+        command_succeeds(self.probe_command())
+    }
+
+    fn version(&self) -> Option<EngineVersion> {
+        self.version
+    }
+
+    fn detect_version(&mut self) {
+        self.version = probe_version(self.probe_command());
+    }
+
+    fn capabilities(&self) -> &'static [(Capability, EngineVersion)] {
+        CODEX_CAPABILITIES
     }
 }
 
 impl Engine for OpenCodeEngine {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
         "opencode"
     }
 
-    fn build_args(&self, params: &EngineExecParams) -> Vec<String> {
+    fn build_args(&self, params: &EngineExecParams, delivery: PromptDelivery) -> Vec<String> {
         let mut args = params.config.engine_pre_args.clone();
-        args.extend(self.build_exec_args(params));
+        args.extend(self.build_exec_args(params, delivery));
         args
     }
 
+    fn output_analyzer(&self, indicators: &[String]) -> Box<dyn OutputAnalyzer> {
+        Box::new(OpenCodeAnalyzer::new(indicators))
+    }
+
+    fn live_log_line(&self, line: &str, mode: ThinkingMode) -> Option<String> {
+        opencode_events::opencode_live_line(line, mode)
+    }
+
+    fn prompt_deliveries(&self) -> Vec<PromptDelivery> {
+        vec![PromptDelivery::Argv, PromptDelivery::File]
+    }
+
     fn is_available(&self) -> bool {
-        Command::new("opencode")
-            .arg("--version")
-            .output()
-            .map(|o| o.status.success())
-            .unwrap_or(false)
+        command_succeeds(self.probe_command())
+    }
+
+    fn version(&self) -> Option<EngineVersion> {
+        self.version
+    }
+
+    fn detect_version(&mut self) {
+        self.version = probe_version(self.probe_command());
+    }
+
+    fn capabilities(&self) -> &'static [(Capability, EngineVersion)] {
+        OPENCODE_CAPABILITIES
     }
 }
 
+/// The prompt argument for a transport: the prompt itself, `-` for stdin, or a
+/// pointer to the prompt file.
+fn prompt_arg(params: &EngineExecParams, delivery: PromptDelivery) -> Option<String> {
+    params.prompt.as_ref()?;
+    Some(match delivery {
+        PromptDelivery::Argv => params.prompt.clone().unwrap_or_default(),
+        PromptDelivery::Stdin => "-".to_string(),
+        PromptDelivery::File => file_reference_prompt(params),
+    })
+}
+
 impl CodexEngine {
-    fn build_exec_args(&self, params: &EngineExecParams) -> Vec<String> {
+    /// `engine_pre_args` minus the reasoning-summary override on CLIs that
+    /// predate it.
+    fn pre_args(&self, config: &RunConfig) -> Vec<String> {
+        let mut args = Vec::with_capacity(config.engine_pre_args.len());
+        let keep_summary = supports(self, Capability::ReasoningSummaryConfig);
+        let mut iter = config.engine_pre_args.iter().peekable();
+        while let Some(arg) = iter.next() {
+            let is_summary = |value: &String| value.starts_with("model_reasoning_summary=");
+            if !keep_summary
+                && (arg == "--config" || arg == "-c")
+                && iter.peek().is_some_and(|v| is_summary(v))
+            {
+                iter.next();
+                continue;
+            }
+            if !keep_summary && is_summary(arg) {
+                continue;
+            }
+            args.push(arg.clone());
+        }
+        args
+    }
+
+    /// `engine_exec_args` minus flags the installed CLI does not know.
+    fn exec_args(&self, config: &RunConfig) -> Vec<String> {
+        let keep_ephemeral = supports(self, Capability::Ephemeral);
+        config
+            .engine_exec_args
+            .iter()
+            .filter(|arg| keep_ephemeral || *arg != "--ephemeral")
+            .cloned()
+            .collect()
+    }
+
+    fn build_exec_args(&self, params: &EngineExecParams, delivery: PromptDelivery) -> Vec<String> {
+        let mut exec_args = self.exec_args(params.config);
+        if let Some(model) = &params.config.model {
+            exec_args.extend(["--model".into(), model.clone()]);
+        }
         let mut args = match &params.config.resume_mode {
             ResumeMode::New => {
                 let mut v = vec!["exec".into()];
-                v.extend(params.config.engine_exec_args.iter().cloned());
+                v.extend(exec_args.iter().cloned());
                 v.push("--json".into());
                 v
             }
             ResumeMode::Explicit(id) => {
                 let mut v = vec!["exec".into()];
-                v.extend(params.config.engine_exec_args.iter().cloned());
+                v.extend(exec_args.iter().cloned());
                 v.extend(vec!["resume".into(), id.clone(), "--json".into()]);
                 v
             }
             ResumeMode::Last => {
                 let mut v = vec!["exec".into()];
-                v.extend(params.config.engine_exec_args.iter().cloned());
+                v.extend(exec_args.iter().cloned());
                 v.extend(vec!["resume".into(), "--last".into(), "--json".into()]);
                 v
             }
         };
 
-        if let Some(prompt) = &params.prompt {
-            args.push(prompt.clone());
+        if let Some(prompt) = prompt_arg(params, delivery) {
+            args.push(prompt);
         }
 
         args
@@ -116,30 +373,50 @@ impl CodexEngine {
 }
 
 impl OpenCodeEngine {
-    fn build_exec_args(&self, params: &EngineExecParams) -> Vec<String> {
+    fn build_exec_args(&self, params: &EngineExecParams, delivery: PromptDelivery) -> Vec<String> {
         let mut args = vec!["run".into()];
         args.extend(params.config.engine_exec_args.iter().cloned());
-        args.push("--json".into());
+        if let Some(model) = &params.config.model {
+            args.extend(["--model".into(), model.clone()]);
+        }
+        if let Some(effort) = params.config.reasoning_effort {
+            args.extend(["--variant".into(), effort.as_str().into()]);
+        }
+        args.extend(["--format".into(), "json".into()]);
 
-        if params.config.thinking_mode == ThinkingMode::Off {
-            args.push("--config".into());
-            args.push("hide_agent_reasoning=true".into());
+        match &params.config.resume_mode {
+            ResumeMode::New => {}
+            ResumeMode::Explicit(id) => args.extend(["--session".into(), id.clone()]),
+            ResumeMode::Last => args.push("--continue".into()),
         }
 
-        if let Some(prompt) = &params.prompt {
+        if let Some(prompt) = prompt_arg(params, delivery) {
             args.push("--prompt".into());
-            args.push(prompt.clone());
+            args.push(prompt);
         }
 
         args
     }
 }
 
-pub fn create_engine(kind: EngineKind) -> Box<dyn Engine> {
-    match kind {
-        EngineKind::Codex => Box::new(CodexEngine),
-        EngineKind::OpenCode => Box::new(OpenCodeEngine),
-    }
+pub fn create_engine(config: &RunConfig) -> Result<Box<dyn Engine>> {
+    let env = engine_env(config);
+    Ok(match config.engine {
+        EngineKind::Codex => Box::new(CodexEngine::new(config.engine_cmd.clone()).with_env(env)),
+        EngineKind::OpenCode => {
+            Box::new(OpenCodeEngine::new(config.engine_cmd.clone()).with_env(env))
+        }
+        EngineKind::Custom => match &config.custom_engine {
+            Some(custom) => {
+                Box::new(CustomEngine::new(custom.clone(), config.engine_cmd.clone()).with_env(env))
+            }
+            None => bail!("engine \"custom\" requires a [custom_engine] table in .forgerc"),
+        },
+        EngineKind::Mock => match &config.mock_scenario {
+            Some(path) => Box::new(MockEngine::from_file(path)?),
+            None => bail!("engine \"mock\" requires mock_scenario in .forgerc"),
+        },
+    })
 }
 
 pub fn execute_with_engine<F>(
@@ -150,7 +427,6 @@ pub fn execute_with_engine<F>(
 where
     F: FnMut() -> Result<()>,
 {
-    let args = engine.build_args(&params);
     let config = params.config;
     let timeout = if config.timeout_minutes == 0 {
         None
@@ -165,13 +441,17 @@ where
         Some(Duration::from_secs(NO_OUTPUT_WATCHDOG_SECS))
     };
 
-    let mut child = Command::new(&config.engine_cmd)
-        .args(&args)
-        .current_dir(params.cwd)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .with_context(|| format!("failed to execute {}", config.engine_cmd))?;
+    let delivery = select_prompt_delivery(engine, params.prompt.as_deref());
+    params.live_log.forge(
+        LogLevel::Info,
+        &describe_prompt_delivery(delivery, params.prompt.as_deref()),
+    )?;
+    // Capture files first: an error after the spawn would leave the engine
+    // running with nobody waiting on it.
+    let mut stdout_capture = StreamCapture::create(params.output_dir, STDOUT_FILE)?;
+    let mut stderr_capture = StreamCapture::create(params.output_dir, STDERR_FILE)?;
+    let mut child = spawn_engine(engine, &params, delivery)?;
+    let mut analyzer = engine.output_analyzer(&config.completion_indicators);
 
     let stdout = child.stdout.take().context("failed to capture stdout")?;
     let stderr = child.stderr.take().context("failed to capture stderr")?;
@@ -185,8 +465,6 @@ where
     let mut finished = false;
     let mut exit_ok = false;
     let mut open_streams = 2_u8;
-    let mut stdout_buf = String::new();
-    let mut stderr_buf = String::new();
     let mut last_output_at = Instant::now();
 
     loop {
@@ -196,12 +474,20 @@ where
                 last_output_at = Instant::now();
                 match source {
                     StreamSource::Stdout => {
-                        stdout_buf.push_str(&chunk);
-                        append_history(params.live_log_path, &chunk)?;
+                        stdout_capture.push(&chunk)?;
+                        analyzer.push_stdout_line(chunk.trim_end_matches(['\r', '\n']));
+                        if let Some(line) = engine.live_log_line(&chunk, config.thinking_mode) {
+                            params
+                                .live_log
+                                .engine_line(LogSource::EngineStdout, &line)?;
+                        }
                     }
                     StreamSource::Stderr => {
-                        stderr_buf.push_str(&chunk);
-                        append_history(params.live_log_path, &format!("[stderr] {chunk}"))?;
+                        stderr_capture.push(&chunk)?;
+                        analyzer.push_stderr_line(chunk.trim_end_matches(['\r', '\n']));
+                        params
+                            .live_log
+                            .engine_line(LogSource::EngineStderr, &chunk)?;
                     }
                 }
             }
@@ -241,10 +527,10 @@ where
                         .with_context(|| format!("failed waiting for {}", config.engine_cmd))?;
                     finished = true;
                     exit_ok = status.success();
-                    append_history(
-                        params.live_log_path,
+                    params.live_log.forge(
+                        LogLevel::Warn,
                         &format!(
-                            "[forge] no output watchdog triggered after {}s; iteration killed\n",
+                            "no output watchdog triggered after {}s; iteration killed",
                             limit.as_secs()
                         ),
                     )?;
@@ -261,9 +547,14 @@ where
         let _ = handle.join();
     }
 
+    let (stdout_path, stdout_tail) = stdout_capture.finish()?;
+    let (stderr_path, stderr_tail) = stderr_capture.finish()?;
     Ok(EngineRunResult {
-        stdout: stdout_buf,
-        stderr: stderr_buf,
+        stdout_path,
+        stderr_path,
+        stdout_tail,
+        stderr_tail,
+        analysis: analyzer.finish(),
         exit_ok,
         timed_out,
     })
@@ -314,37 +605,6 @@ where
     })
 }
 
-fn append_history(path: &Path, line: &str) -> Result<()> {
-    let mut file = fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(path)
-        .with_context(|| format!("failed to open {}", path.display()))?;
-    let stamped = stamp_lines(line);
-    file.write_all(stamped.as_bytes())
-        .with_context(|| format!("failed to append {}", path.display()))
-}
-
-fn stamp_lines(input: &str) -> String {
-    let ts = Local::now().format("%H:%M:%S").to_string();
-    let mut out = String::new();
-    for segment in input.split_inclusive('\n') {
-        let has_newline = segment.ends_with('\n');
-        let content = segment.trim_end_matches('\n');
-        if content.is_empty() {
-            continue;
-        }
-        out.push_str(&format!("[{}] {}", ts, content));
-        if has_newline {
-            out.push('\n');
-        }
-    }
-    if out.is_empty() && !input.trim().is_empty() {
-        out.push_str(&format!("[{}] {}", ts, input.trim()));
-    }
-    out
-}
-
 pub fn epoch_now() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -355,11 +615,21 @@ pub fn epoch_now() -> u64 {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use forge_config::{CliOverrides, ReasoningEffort};
+    use std::fs;
+
+    /// Overrides that leave the user's XDG config out of test configs.
+    pub(crate) fn isolated() -> CliOverrides {
+        CliOverrides {
+            no_user_config: true,
+            ..CliOverrides::default()
+        }
+    }
 
     #[test]
     #[allow(clippy::overly_complex_bool_expr)]
     fn codex_engine_is_available_check() {
-        let engine = CodexEngine;
+        let engine = CodexEngine::default();
         let result = engine.is_available();
         assert!(result || !result);
     }
@@ -367,7 +637,7 @@ mod tests {
     #[test]
     #[allow(clippy::overly_complex_bool_expr)]
     fn opencode_engine_is_available_check() {
-        let engine = OpenCodeEngine;
+        let engine = OpenCodeEngine::default();
         let result = engine.is_available();
         assert!(result || !result);
     }
@@ -375,7 +645,8 @@ mod tests {
     #[test]
     fn parse_codex_output_detects_exit_signal() {
         let output = "EXIT_SIGNAL: true\nSTATUS: COMPLETE";
-        let analysis = CodexEngine.parse_output(output, "", &["STATUS: COMPLETE".into()]);
+        let analysis =
+            CodexEngine::default().parse_output(output, "", &["STATUS: COMPLETE".into()]);
         assert!(analysis.exit_signal_true);
         assert_eq!(analysis.completion_indicators, 1);
     }
@@ -383,31 +654,278 @@ mod tests {
     #[test]
     fn parse_opencode_output_detects_session_id() {
         let output = r#"{"type":"thread.started","thread_id":"abc123"}"#;
-        let analysis = OpenCodeEngine.parse_output(output, "", &[]);
+        let analysis = OpenCodeEngine::default().parse_output(output, "", &[]);
         assert_eq!(analysis.session_id, Some("abc123".to_string()));
     }
 
+    #[test]
+    fn parse_opencode_events_output() {
+        let output = r#"{"type":"step_start","sessionID":"ses_9"}
+{"type":"text","sessionID":"ses_9","part":{"text":"EXIT_SIGNAL: true"}}"#;
+        let analysis = OpenCodeEngine::default().parse_output(output, "", &[]);
+        assert_eq!(analysis.session_id.as_deref(), Some("ses_9"));
+        assert!(analysis.exit_signal_true);
+    }
+
+    #[test]
+    fn opencode_args_use_json_format_and_resume_flags() {
+        let mut config = config_for(EngineKind::OpenCode);
+        let dir = tempfile::tempdir().expect("tempdir");
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let args = |config: &RunConfig| {
+            OpenCodeEngine::default().build_args(
+                &EngineExecParams {
+                    cwd: dir.path(),
+                    config,
+                    prompt: Some("go".into()),
+                    live_log: &live_log,
+                    output_dir: dir.path(),
+                },
+                PromptDelivery::Argv,
+            )
+        };
+
+        let new = args(&config);
+        assert_eq!(new[0], "run");
+        assert!(new.windows(2).any(|w| w == ["--format", "json"]));
+        assert!(new.ends_with(&["--prompt".to_string(), "go".to_string()]));
+
+        config.resume_mode = ResumeMode::Explicit("ses_1".into());
+        assert!(args(&config)
+            .windows(2)
+            .any(|w| w == ["--session", "ses_1"]));
+
+        config.resume_mode = ResumeMode::Last;
+        assert!(args(&config).contains(&"--continue".to_string()));
+
+        config.model = Some("anthropic/claude-sonnet".into());
+        config.reasoning_effort = Some(ReasoningEffort::High);
+        let args = args(&config);
+        assert!(args
+            .windows(2)
+            .any(|w| w == ["--model", "anthropic/claude-sonnet"]));
+        assert!(args.windows(2).any(|w| w == ["--variant", "high"]));
+    }
+
     #[test]
     fn codex_engine_name() {
-        let engine = CodexEngine;
+        let engine = CodexEngine::default();
         assert_eq!(engine.name(), "codex");
     }
 
     #[test]
     fn opencode_engine_name() {
-        let engine = OpenCodeEngine;
+        let engine = OpenCodeEngine::default();
         assert_eq!(engine.name(), "opencode");
     }
 
+    fn config_for(engine: EngineKind) -> RunConfig {
+        let dir = tempfile::tempdir().expect("tempdir");
+        forge_config::load_run_config(
+            dir.path(),
+            &CliOverrides {
+                engine: Some(engine),
+                ..isolated()
+            },
+        )
+        .expect("config")
+    }
+
     #[test]
     fn create_engine_returns_codex() {
-        let engine = create_engine(EngineKind::Codex);
+        let engine = create_engine(&config_for(EngineKind::Codex)).expect("engine");
         assert_eq!(engine.name(), "codex");
     }
 
     #[test]
     fn create_engine_returns_opencode() {
-        let engine = create_engine(EngineKind::OpenCode);
+        let engine = create_engine(&config_for(EngineKind::OpenCode)).expect("engine");
         assert_eq!(engine.name(), "opencode");
     }
+
+    #[test]
+    fn create_engine_rejects_custom_without_table() {
+        let mut config = config_for(EngineKind::Codex);
+        config.engine = EngineKind::Custom;
+        assert!(create_engine(&config).is_err());
+    }
+
+    #[test]
+    fn stdin_delivery_feeds_prompt_to_engine() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            dir.path().join(".forgerc"),
+            "engine = \"custom\"\n[custom_engine]\ncommand = \"cat\"\nargs = [\"-\"]\nprompt_delivery = \"stdin\"\n",
+        )
+        .expect("write forgerc");
+        let config = forge_config::load_run_config(dir.path(), &isolated()).expect("config");
+        let engine = create_engine(&config).expect("engine");
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+
+        let result = execute_with_engine(
+            engine.as_ref(),
+            EngineExecParams {
+                cwd: dir.path(),
+                config: &config,
+                prompt: Some("prompt via stdin".into()),
+                live_log: &live_log,
+                output_dir: dir.path(),
+            },
+            || Ok(()),
+        )
+        .expect("run");
+
+        assert!(result.exit_ok);
+        assert_eq!(result.stdout_tail, "prompt via stdin");
+    }
+
+    #[test]
+    fn engine_process_gets_filtered_environment() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            dir.path().join(".forgerc"),
+            "engine = \"custom\"\nengine_env_denylist = [\"HOME\"]\n[engine_env]\nAGENT_MODE = \"ci\"\n[custom_engine]\ncommand = \"sh\"\nargs = [\"-c\", \"printf '%s %s' \\\"${HOME:-unset}\\\" \\\"$AGENT_MODE\\\"\"]\n",
+        )
+        .expect("write forgerc");
+        let config = forge_config::load_run_config(dir.path(), &isolated()).expect("config");
+        let engine = create_engine(&config).expect("engine");
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+
+        let result = execute_with_engine(
+            engine.as_ref(),
+            EngineExecParams {
+                cwd: dir.path(),
+                config: &config,
+                prompt: None,
+                live_log: &live_log,
+                output_dir: dir.path(),
+            },
+            || Ok(()),
+        )
+        .expect("run");
+
+        assert!(result.exit_ok);
+        assert_eq!(result.stdout_tail, "unset ci");
+    }
+
+    #[test]
+    fn codex_prompt_argument_follows_transport() {
+        let config = config_for(EngineKind::Codex);
+        let dir = tempfile::tempdir().expect("tempdir");
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let params = EngineExecParams {
+            cwd: dir.path(),
+            config: &config,
+            prompt: Some("the task".into()),
+            live_log: &live_log,
+            output_dir: dir.path(),
+        };
+
+        let argv = CodexEngine::default().build_args(&params, PromptDelivery::Argv);
+        assert_eq!(argv.last().map(String::as_str), Some("the task"));
+        let stdin = CodexEngine::default().build_args(&params, PromptDelivery::Stdin);
+        assert_eq!(stdin.last().map(String::as_str), Some("-"));
+        let file = CodexEngine::default().build_args(&params, PromptDelivery::File);
+        assert!(file
+            .last()
+            .is_some_and(|arg| arg.contains(&prompt_file_path(&params).display().to_string())));
+    }
+
+    #[test]
+    fn codex_args_omit_flags_the_detected_version_lacks() {
+        let mut config = config_for(EngineKind::Codex);
+        config.engine_exec_args = vec!["--ephemeral".into(), "--skip-git-repo-check".into()];
+        let dir = tempfile::tempdir().expect("tempdir");
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let params = EngineExecParams {
+            cwd: dir.path(),
+            config: &config,
+            prompt: None,
+            live_log: &live_log,
+            output_dir: dir.path(),
+        };
+
+        let current = CodexEngine::default().with_version(Some(EngineVersion::new(0, 60, 0)));
+        let args = current.build_args(&params, PromptDelivery::Argv);
+        assert!(args.iter().any(|a| a == "--ephemeral"));
+        assert!(args
+            .iter()
+            .any(|a| a.starts_with("model_reasoning_summary=")));
+
+        let old = CodexEngine::default().with_version(Some(EngineVersion::new(0, 20, 0)));
+        let args = old.build_args(&params, PromptDelivery::Argv);
+        assert!(!args.iter().any(|a| a == "--ephemeral"));
+        assert!(!args
+            .iter()
+            .any(|a| a.starts_with("model_reasoning_summary=")));
+        assert!(args.iter().any(|a| a == "--skip-git-repo-check"));
+        assert!(args.iter().any(|a| a == "hide_agent_reasoning=false"));
+
+        let mut with_model = config.clone();
+        with_model.model = Some("gpt-5-codex".into());
+        let params = EngineExecParams {
+            config: &with_model,
+            ..params
+        };
+        let args = current.build_args(&params, PromptDelivery::Argv);
+        let exec = args.iter().position(|a| a == "exec").expect("exec");
+        assert_eq!(
+            args[exec + 1..exec + 4],
+            ["--ephemeral", "--skip-git-repo-check", "--model"]
+        );
+        assert_eq!(args[exec + 4], "gpt-5-codex");
+
+        let mut with_effort = with_model.clone();
+        with_effort.reasoning_effort = Some(ReasoningEffort::Low);
+        let params = EngineExecParams {
+            config: &with_effort,
+            ..params
+        };
+        let args = current.build_args(&params, PromptDelivery::Argv);
+        let effort = args
+            .iter()
+            .position(|a| a == r#"model_reasoning_effort="low""#)
+            .expect("effort flag");
+        assert_eq!(args[effort - 1], "--config");
+        assert!(effort < args.iter().position(|a| a == "exec").expect("exec"));
+    }
+
+    #[test]
+    fn oversized_prompt_goes_over_stdin_and_is_logged() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().expect("tempdir");
+        let script = dir.path().join("fake-codex.sh");
+        fs::write(
+            &script,
+            "#!/bin/sh\nfor last; do :; done\n[ \"$last\" = \"-\" ] && wc -c\n",
+        )
+        .expect("write script");
+        let mut perms = fs::metadata(&script).expect("meta").permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&script, perms).expect("chmod");
+
+        let mut config = config_for(EngineKind::Codex);
+        config.engine_cmd = script.display().to_string();
+        let live_log = LiveLog::new(dir.path().join("live.log"));
+        let prompt = "x".repeat(ARGV_PROMPT_LIMIT_BYTES * 2);
+
+        let result = execute_with_engine(
+            &CodexEngine::default(),
+            EngineExecParams {
+                cwd: dir.path(),
+                config: &config,
+                prompt: Some(prompt.clone()),
+                live_log: &live_log,
+                output_dir: dir.path(),
+            },
+            || Ok(()),
+        )
+        .expect("run");
+
+        assert!(result.exit_ok);
+        assert_eq!(result.stdout_tail.trim(), prompt.len().to_string());
+        let log = fs::read_to_string(live_log.path()).expect("live log");
+        assert!(log.contains("prompt delivered via stdin"));
+    }
 }
@@ -0,0 +1,136 @@
+use anyhow::{Context, Result};
+use chrono::{Local, SecondsFormat};
+use forge_types::{LiveLogEntry, LogLevel, LogSource};
+use serde_json::Value;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+/// Appends JSON envelopes to `live.log`, tagged with the run and loop they
+/// belong to.
+#[derive(Debug, Clone)]
+pub struct LiveLog {
+    path: PathBuf,
+    run_id: Option<String>,
+    loop_number: Option<u64>,
+}
+
+impl LiveLog {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            run_id: None,
+            loop_number: None,
+        }
+    }
+
+    pub fn with_run_id(mut self, run_id: impl Into<String>) -> Self {
+        self.run_id = Some(run_id.into());
+        self
+    }
+
+    /// The same log, tagging entries with `loop_number`.
+    pub fn for_loop(&self, loop_number: u64) -> Self {
+        Self {
+            loop_number: Some(loop_number),
+            ..self.clone()
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn run_id(&self) -> Option<&str> {
+        self.run_id.as_deref()
+    }
+
+    pub fn entry(&self, source: LogSource, level: LogLevel, payload: Value) -> LiveLogEntry {
+        LiveLogEntry {
+            ts: Local::now().to_rfc3339_opts(SecondsFormat::Millis, false),
+            run_id: self.run_id.clone(),
+            loop_number: self.loop_number,
+            source,
+            level,
+            payload,
+        }
+    }
+
+    pub fn append(&self, source: LogSource, level: LogLevel, payload: Value) -> Result<()> {
+        let mut line = serde_json::to_string(&self.entry(source, level, payload))
+            .context("failed to serialize live log entry")?;
+        line.push('\n');
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("failed to open {}", self.path.display()))?;
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("failed to append {}", self.path.display()))
+    }
+
+    /// A message from forge itself.
+    pub fn forge(&self, level: LogLevel, message: &str) -> Result<()> {
+        self.append(LogSource::Forge, level, Value::String(message.to_string()))
+    }
+
+    /// One engine output line; JSON events are embedded, anything else is
+    /// kept as text. Blank lines are skipped.
+    pub fn engine_line(&self, source: LogSource, line: &str) -> Result<()> {
+        let line = line.trim_end_matches(['\r', '\n']);
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let payload = match serde_json::from_str::<Value>(line) {
+            Ok(value) if value.is_object() => value,
+            _ => Value::String(line.to_string()),
+        };
+        let level = match source {
+            LogSource::EngineStderr => LogLevel::Warn,
+            _ => LogLevel::Info,
+        };
+        self.append(source, level, payload)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::DateTime;
+
+    fn read_entries(path: &Path) -> Vec<LiveLogEntry> {
+        std::fs::read_to_string(path)
+            .expect("live log")
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("envelope"))
+            .collect()
+    }
+
+    #[test]
+    fn entries_carry_timestamp_run_loop_and_source() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let log = LiveLog::new(dir.path().join("live.log")).with_run_id("run-1");
+        log.forge(LogLevel::Info, "run started").expect("forge");
+        log.for_loop(2)
+            .engine_line(LogSource::EngineStdout, "{\"type\":\"turn.started\"}\n")
+            .expect("stdout");
+        log.for_loop(2)
+            .engine_line(LogSource::EngineStderr, "plain warning\n")
+            .expect("stderr");
+        log.for_loop(2)
+            .engine_line(LogSource::EngineStdout, "\n")
+            .expect("blank");
+
+        let entries = read_entries(log.path());
+        assert_eq!(entries.len(), 3);
+        assert!(DateTime::parse_from_rfc3339(&entries[0].ts).is_ok());
+        assert_eq!(entries[0].run_id.as_deref(), Some("run-1"));
+        assert_eq!(entries[0].loop_number, None);
+        assert_eq!(entries[0].text(), Some("run started"));
+        assert_eq!(entries[1].loop_number, Some(2));
+        assert_eq!(entries[1].source, LogSource::EngineStdout);
+        assert_eq!(entries[1].payload["type"], "turn.started");
+        assert_eq!(entries[2].level, LogLevel::Warn);
+        assert_eq!(entries[2].text(), Some("plain warning"));
+    }
+}
@@ -0,0 +1,385 @@
+use crate::{file_reference_prompt, CodexAnalyzer, Engine, EngineExecParams, OutputAnalyzer};
+use anyhow::{Context, Result};
+use forge_config::PromptDelivery;
+use forge_types::TokenUsage;
+use serde::Deserialize;
+use serde_json::json;
+use std::cell::Cell;
+use std::fs;
+use std::path::Path;
+
+/// Scenario replayed by `engine = "mock"`, one entry per engine call.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MockScenario {
+    /// Keep replaying the last loop once the list runs out; otherwise the
+    /// extra calls fail.
+    #[serde(default = "default_repeat_last")]
+    pub repeat_last: bool,
+    #[serde(default)]
+    pub loops: Vec<MockLoop>,
+}
+
+fn default_repeat_last() -> bool {
+    true
+}
+
+/// One engine call. The Codex fields are rendered as `codex exec --json`
+/// events in this order: `thread.started`, `turn.started`, raw `steps`,
+/// `commands`, `file_changes`, `agent_message`, `errors`, `turn.completed`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MockLoop {
+    pub session_id: Option<String>,
+    pub steps: Vec<MockStep>,
+    pub commands: Vec<MockCommand>,
+    pub file_changes: Vec<String>,
+    pub agent_message: Option<String>,
+    pub errors: Vec<String>,
+    pub usage: Option<TokenUsage>,
+    pub exit_code: i32,
+}
+
+/// Raw output, optionally preceded by a pause. `silence_secs` is meant for
+/// watchdog scenarios and `delay_ms` for pacing.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MockStep {
+    pub delay_ms: u64,
+    pub silence_secs: u64,
+    pub stdout: Option<String>,
+    pub stderr: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct MockCommand {
+    pub command: String,
+    pub output: String,
+    pub exit_code: i64,
+}
+
+impl MockScenario {
+    pub fn load(path: &Path) -> Result<Self> {
+        let raw = fs::read_to_string(path)
+            .with_context(|| format!("failed to read mock scenario {}", path.display()))?;
+        toml::from_str(&raw)
+            .with_context(|| format!("failed to parse mock scenario {}", path.display()))
+    }
+}
+
+impl MockLoop {
+    fn has_codex_events(&self) -> bool {
+        self.session_id.is_some()
+            || !self.commands.is_empty()
+            || !self.file_changes.is_empty()
+            || self.agent_message.is_some()
+            || !self.errors.is_empty()
+            || self.usage.is_some()
+    }
+
+    /// Shell script that replays this loop on stdout/stderr and exits.
+    fn script(&self) -> String {
+        let mut out = String::new();
+        let codex = self.has_codex_events();
+        if let Some(id) = &self.session_id {
+            push_stdout(
+                &mut out,
+                &json!({"type": "thread.started", "thread_id": id}).to_string(),
+            );
+        }
+        if codex {
+            push_stdout(&mut out, &json!({"type": "turn.started"}).to_string());
+        }
+        for step in &self.steps {
+            let pause_ms = step.delay_ms + step.silence_secs * 1000;
+            if pause_ms > 0 {
+                out.push_str(&format!(
+                    "sleep {}.{:03}\n",
+                    pause_ms / 1000,
+                    pause_ms % 1000
+                ));
+            }
+            if let Some(text) = &step.stdout {
+                push_stdout(&mut out, text);
+            }
+            if let Some(text) = &step.stderr {
+                out.push_str(&format!("printf '%s\\n' {} >&2\n", shell_quote(text)));
+            }
+        }
+        for (idx, command) in self.commands.iter().enumerate() {
+            let id = format!("mock_cmd_{idx}");
+            push_stdout(
+                &mut out,
+                &json!({"type": "item.started", "item": {
+                    "id": id, "type": "command_execution", "command": command.command,
+                    "aggregated_output": "", "status": "in_progress"}})
+                .to_string(),
+            );
+            let status = if command.exit_code == 0 {
+                "completed"
+            } else {
+                "failed"
+            };
+            push_stdout(
+                &mut out,
+                &json!({"type": "item.completed", "item": {
+                    "id": id, "type": "command_execution", "command": command.command,
+                    "aggregated_output": command.output, "exit_code": command.exit_code,
+                    "status": status}})
+                .to_string(),
+            );
+        }
+        if !self.file_changes.is_empty() {
+            let changes = self
+                .file_changes
+                .iter()
+                .map(|path| json!({"path": path, "kind": "update"}))
+                .collect::<Vec<_>>();
+            push_stdout(
+                &mut out,
+                &json!({"type": "item.completed", "item": {
+                    "id": "mock_patch", "type": "file_change", "changes": changes,
+                    "status": "completed"}})
+                .to_string(),
+            );
+        }
+        if let Some(text) = &self.agent_message {
+            push_stdout(
+                &mut out,
+                &json!({"type": "item.completed", "item": {
+                    "id": "mock_msg", "type": "agent_message", "text": text}})
+                .to_string(),
+            );
+        }
+        for message in &self.errors {
+            push_stdout(
+                &mut out,
+                &json!({"type": "error", "message": message}).to_string(),
+            );
+        }
+        if codex {
+            let mut completed = json!({"type": "turn.completed"});
+            if let Some(usage) = &self.usage {
+                completed["usage"] = json!(usage);
+            }
+            push_stdout(&mut out, &completed.to_string());
+        }
+        out.push_str(&format!("exit {}\n", self.exit_code));
+        out
+    }
+}
+
+fn push_stdout(script: &mut String, line: &str) {
+    script.push_str(&format!("printf '%s\\n' {}\n", shell_quote(line)));
+}
+
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Replays a scenario file through `sh`, so the watchdog, timeouts, circuit
+/// breaker, monitor and analyze all see a real child process.
+pub struct MockEngine {
+    scenario: MockScenario,
+    calls: Cell<usize>,
+}
+
+impl MockEngine {
+    pub fn new(scenario: MockScenario) -> Self {
+        Self {
+            scenario,
+            calls: Cell::new(0),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self> {
+        Ok(Self::new(MockScenario::load(path)?))
+    }
+
+    /// Script for the current call; `spawned` moves on to the next one.
+    fn current_script(&self) -> String {
+        let call = self.calls.get();
+        let loops = &self.scenario.loops;
+        match loops.get(call) {
+            Some(mock_loop) => mock_loop.script(),
+            None if self.scenario.repeat_last && !loops.is_empty() => {
+                loops[loops.len() - 1].script()
+            }
+            None => format!(
+                "printf '%s\\n' {} >&2\nexit 1\n",
+                shell_quote(&format!(
+                    "mock scenario exhausted after {} loop(s)",
+                    loops.len()
+                ))
+            ),
+        }
+    }
+}
+
+impl Engine for MockEngine {
+    fn name(&self) -> &str {
+        "mock"
+    }
+
+    /// The prompt is passed as `$1` so it stays visible in process
+    /// listings, as with real engines.
+    fn build_args(&self, params: &EngineExecParams, delivery: PromptDelivery) -> Vec<String> {
+        let mut args = vec![
+            "-c".to_string(),
+            self.current_script(),
+            "forge-mock".to_string(),
+        ];
+        if let Some(prompt) = &params.prompt {
+            args.push(match delivery {
+                PromptDelivery::File => file_reference_prompt(params),
+                _ => prompt.clone(),
+            });
+        }
+        args
+    }
+
+    fn output_analyzer(&self, indicators: &[String]) -> Box<dyn OutputAnalyzer> {
+        Box::new(CodexAnalyzer::new(indicators))
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn prompt_deliveries(&self) -> Vec<PromptDelivery> {
+        vec![PromptDelivery::Argv, PromptDelivery::File]
+    }
+
+    /// Each started process advances the scenario by one loop.
+    fn spawned(&self) {
+        self.calls.set(self.calls.get() + 1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::isolated;
+    use crate::{execute_with_engine, parse_codex_events, CodexEvent, LiveLog};
+    use forge_config::{load_run_config, RunConfig};
+    use tempfile::tempdir;
+
+    const SCENARIO: &str = r#"
+repeat_last = false
+
+[[loops]]
+session_id = "mock-thread"
+file_changes = ["src/lib.rs"]
+agent_message = "halfway"
+usage = { input_tokens = 50, output_tokens = 5 }
+
+[[loops.commands]]
+command = "cargo test"
+output = "1 failed"
+exit_code = 101
+
+[[loops.steps]]
+stderr = "warming up"
+
+[[loops]]
+agent_message = "STATUS: COMPLETE\nEXIT_SIGNAL: true"
+exit_code = 0
+"#;
+
+    fn run_once(engine: &MockEngine, config: &RunConfig, cwd: &Path) -> crate::EngineRunResult {
+        execute_with_engine(
+            engine,
+            EngineExecParams {
+                cwd,
+                config,
+                prompt: Some("it's a prompt".into()),
+                live_log: &LiveLog::new(cwd.join("live.log")),
+                output_dir: cwd,
+            },
+            || Ok(()),
+        )
+        .expect("run")
+    }
+
+    fn mock_config(dir: &Path) -> RunConfig {
+        fs::write(dir.join("scenario.toml"), SCENARIO).expect("write scenario");
+        fs::write(
+            dir.join(".forgerc"),
+            "engine = \"mock\"\nmock_scenario = \"scenario.toml\"\n",
+        )
+        .expect("write forgerc");
+        load_run_config(dir, &isolated()).expect("config")
+    }
+
+    #[test]
+    fn replays_loops_as_codex_events_in_order() {
+        let dir = tempdir().expect("tempdir");
+        let config = mock_config(dir.path());
+        let engine = MockEngine::from_file(config.mock_scenario.as_deref().expect("scenario"))
+            .expect("engine");
+
+        let params = EngineExecParams {
+            cwd: dir.path(),
+            config: &config,
+            prompt: None,
+            live_log: &LiveLog::new(dir.path().join("live.log")),
+            output_dir: dir.path(),
+        };
+        // Building args, e.g. for a dry run, does not use up a loop.
+        assert_eq!(
+            engine.build_args(&params, PromptDelivery::Argv),
+            engine.build_args(&params, PromptDelivery::Argv)
+        );
+
+        let first = run_once(&engine, &config, dir.path());
+        assert!(first.exit_ok);
+        assert_eq!(first.stderr_tail.trim(), "warming up");
+        let events = parse_codex_events(&first.stdout_tail);
+        assert_eq!(
+            events[0],
+            CodexEvent::ThreadStarted {
+                thread_id: "mock-thread".into()
+            }
+        );
+        let analysis = &first.analysis;
+        assert!(analysis.has_progress_hint);
+        assert!(!analysis.exit_signal_true);
+        assert_eq!(analysis.final_message.as_deref(), Some("halfway"));
+        assert_eq!(
+            analysis
+                .token_usage
+                .as_ref()
+                .map(|usage| usage.input_tokens),
+            Some(50)
+        );
+
+        let second = run_once(&engine, &config, dir.path());
+        let analysis = &second.analysis;
+        assert!(analysis.exit_signal_true);
+        assert_eq!(analysis.completion_indicators, 1);
+
+        let third = run_once(&engine, &config, dir.path());
+        assert!(!third.exit_ok);
+        assert!(third
+            .stderr_tail
+            .contains("mock scenario exhausted after 2 loop(s)"));
+    }
+
+    #[test]
+    fn repeat_last_replays_final_loop() {
+        let scenario: MockScenario =
+            toml::from_str("[[loops]]\nagent_message = \"again\"\nexit_code = 3\n")
+                .expect("scenario");
+        let engine = MockEngine::new(scenario);
+        let first = engine.current_script();
+        engine.spawned();
+
+        assert_eq!(engine.current_script(), first);
+        assert!(first.ends_with("exit 3\n"));
+    }
+
+    #[test]
+    fn quotes_single_quotes_for_the_shell() {
+        assert_eq!(shell_quote("it's"), r"'it'\''s'");
+    }
+}
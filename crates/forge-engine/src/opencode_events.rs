@@ -0,0 +1,408 @@
+use crate::codex_events::has_exit_signal;
+use crate::output_parser::{OutputAnalyzer, TextAnalyzer};
+use forge_config::ThinkingMode;
+use forge_types::{OutputAnalysis, TokenUsage};
+use serde::Deserialize;
+use serde_json::Value;
+
+const SUMMARY_REASONING_CHARS: usize = 200;
+
+/// One line of `opencode run --format json` output.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum OpenCodeEvent {
+    StepStart {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+    },
+    Text {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        part: TextPart,
+    },
+    Reasoning {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        part: TextPart,
+    },
+    ToolUse {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        part: ToolPart,
+    },
+    StepFinish {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        part: StepFinishPart,
+    },
+    Error {
+        #[serde(rename = "sessionID", default)]
+        session_id: Option<String>,
+        error: OpenCodeError,
+    },
+    #[serde(other)]
+    Unknown,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct TextPart {
+    #[serde(default)]
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct ToolPart {
+    #[serde(default)]
+    pub tool: String,
+    #[serde(default)]
+    pub state: ToolState,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct ToolState {
+    pub status: String,
+    pub input: Value,
+    pub output: Option<String>,
+    pub error: Option<String>,
+    pub metadata: Value,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct StepFinishPart {
+    pub tokens: OpenCodeTokens,
+    pub cost: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OpenCodeTokens {
+    pub input: u64,
+    pub output: u64,
+    pub reasoning: u64,
+    pub cache: OpenCodeCacheTokens,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+pub struct OpenCodeCacheTokens {
+    pub read: u64,
+    pub write: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+pub struct OpenCodeError {
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub data: Value,
+}
+
+impl OpenCodeEvent {
+    pub fn session_id(&self) -> Option<&str> {
+        match self {
+            OpenCodeEvent::StepStart { session_id }
+            | OpenCodeEvent::Text { session_id, .. }
+            | OpenCodeEvent::Reasoning { session_id, .. }
+            | OpenCodeEvent::ToolUse { session_id, .. }
+            | OpenCodeEvent::StepFinish { session_id, .. }
+            | OpenCodeEvent::Error { session_id, .. } => session_id.as_deref(),
+            OpenCodeEvent::Unknown => None,
+        }
+    }
+}
+
+impl ToolPart {
+    /// Shell command for `bash` tool calls, otherwise the tool name.
+    pub fn command(&self) -> String {
+        self.state
+            .input
+            .get("command")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| self.tool.clone())
+    }
+
+    pub fn exit_code(&self) -> Option<i64> {
+        self.state.metadata.get("exit").and_then(Value::as_i64)
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.state.status == "error" || self.exit_code().is_some_and(|code| code != 0)
+    }
+
+    /// Paths touched by completed edit/write/patch tools.
+    pub fn changed_path(&self) -> Option<String> {
+        if self.state.status != "completed"
+            || !matches!(self.tool.as_str(), "edit" | "write" | "patch")
+        {
+            return None;
+        }
+        ["filePath", "file_path", "path"]
+            .iter()
+            .find_map(|key| self.state.input.get(*key).and_then(Value::as_str))
+            .map(ToString::to_string)
+    }
+}
+
+impl OpenCodeError {
+    pub fn message(&self) -> String {
+        self.data
+            .get("message")
+            .and_then(Value::as_str)
+            .map(ToString::to_string)
+            .unwrap_or_else(|| self.name.clone())
+    }
+}
+
+impl From<&OpenCodeTokens> for TokenUsage {
+    fn from(tokens: &OpenCodeTokens) -> Self {
+        TokenUsage {
+            input_tokens: tokens.input,
+            cached_input_tokens: tokens.cache.read,
+            output_tokens: tokens.output,
+            reasoning_tokens: tokens.reasoning,
+        }
+    }
+}
+
+pub fn parse_opencode_events(stdout: &str) -> Vec<OpenCodeEvent> {
+    stdout.lines().filter_map(parse_opencode_event).collect()
+}
+
+/// One line of output as a known OpenCode event, if it is one.
+pub fn parse_opencode_event(line: &str) -> Option<OpenCodeEvent> {
+    serde_json::from_str::<OpenCodeEvent>(line.trim())
+        .ok()
+        .filter(|event| *event != OpenCodeEvent::Unknown)
+}
+
+pub fn opencode_final_message(events: &[OpenCodeEvent]) -> Option<&str> {
+    events.iter().rev().find_map(|event| match event {
+        OpenCodeEvent::Text { part, .. } if !part.text.trim().is_empty() => {
+            Some(part.text.as_str())
+        }
+        _ => None,
+    })
+}
+
+pub fn opencode_error_messages(events: &[OpenCodeEvent]) -> Vec<String> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            OpenCodeEvent::Error { error, .. } => Some(error.message()),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Same contract as the Codex analysis: the exit gate reads only the final
+/// text part and errors only come from `error` events.
+pub fn analyze_opencode_events(events: &[OpenCodeEvent], indicators: &[String]) -> OutputAnalysis {
+    let mut analyzer = OpenCodeAnalyzer::new(indicators);
+    for event in events {
+        analyzer.push_event(event);
+    }
+    analyzer.event_analysis()
+}
+
+/// Streaming form of `analyze_opencode_events`, with the same text fallback
+/// as the Codex analyzer.
+pub struct OpenCodeAnalyzer {
+    indicators: Vec<String>,
+    saw_event: bool,
+    session_id: Option<String>,
+    final_message: Option<String>,
+    has_error: bool,
+    has_progress_hint: bool,
+    token_usage: Option<TokenUsage>,
+    text: TextAnalyzer,
+}
+
+impl OpenCodeAnalyzer {
+    pub fn new(indicators: &[String]) -> Self {
+        Self {
+            indicators: indicators.to_vec(),
+            saw_event: false,
+            session_id: None,
+            final_message: None,
+            has_error: false,
+            has_progress_hint: false,
+            token_usage: None,
+            text: TextAnalyzer::new(indicators),
+        }
+    }
+
+    pub fn push_event(&mut self, event: &OpenCodeEvent) {
+        self.saw_event = true;
+        if self.session_id.is_none() {
+            self.session_id = event.session_id().map(ToString::to_string);
+        }
+        match event {
+            OpenCodeEvent::Text { part, .. } if !part.text.trim().is_empty() => {
+                self.final_message = Some(part.text.clone());
+            }
+            OpenCodeEvent::ToolUse { part, .. } if part.changed_path().is_some() => {
+                self.has_progress_hint = true;
+            }
+            OpenCodeEvent::StepFinish { part, .. } => {
+                self.token_usage
+                    .get_or_insert_with(TokenUsage::default)
+                    .add(&TokenUsage::from(&part.tokens));
+            }
+            OpenCodeEvent::Error { .. } => self.has_error = true,
+            _ => {}
+        }
+    }
+
+    fn event_analysis(self) -> OutputAnalysis {
+        let message = self.final_message.as_deref().unwrap_or_default();
+        OutputAnalysis {
+            exit_signal_true: has_exit_signal(message),
+            completion_indicators: self
+                .indicators
+                .iter()
+                .filter(|indicator| message.contains(indicator.as_str()))
+                .count() as u32,
+            has_error: self.has_error,
+            has_progress_hint: self.has_progress_hint,
+            session_id: self.session_id,
+            final_message: self.final_message,
+            token_usage: self.token_usage,
+        }
+    }
+}
+
+impl OutputAnalyzer for OpenCodeAnalyzer {
+    fn push_stdout_line(&mut self, line: &str) {
+        if let Some(event) = parse_opencode_event(line) {
+            self.push_event(&event);
+        }
+        self.text.push_stdout_line(line);
+    }
+
+    fn push_stderr_line(&mut self, line: &str) {
+        self.text.push_stderr_line(line);
+    }
+
+    fn finish(self: Box<Self>) -> OutputAnalysis {
+        if self.saw_event {
+            self.event_analysis()
+        } else {
+            Box::new(self.text).finish()
+        }
+    }
+}
+
+/// Applies the thinking mode to one output line before it reaches the live
+/// log: `off` drops reasoning events, `summary` keeps the first line of each.
+pub fn opencode_live_line(line: &str, mode: ThinkingMode) -> Option<String> {
+    if mode == ThinkingMode::Raw {
+        return Some(line.to_string());
+    }
+    let Ok(mut value) = serde_json::from_str::<Value>(line.trim()) else {
+        return Some(line.to_string());
+    };
+    if value.get("type").and_then(Value::as_str) != Some("reasoning") {
+        return Some(line.to_string());
+    }
+    if mode == ThinkingMode::Off {
+        return None;
+    }
+    let text = value
+        .pointer("/part/text")
+        .and_then(Value::as_str)
+        .unwrap_or_default();
+    let summary = text
+        .lines()
+        .find(|l| !l.trim().is_empty())
+        .unwrap_or_default()
+        .chars()
+        .take(SUMMARY_REASONING_CHARS)
+        .collect::<String>();
+    if let Some(slot) = value.pointer_mut("/part/text") {
+        *slot = Value::String(summary);
+    }
+    let newline = if line.ends_with('\n') { "\n" } else { "" };
+    Some(format!("{value}{newline}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRANSCRIPT: &str = r#"{"type":"step_start","timestamp":1,"sessionID":"ses_1","part":{"type":"step-start"}}
+{"type":"reasoning","sessionID":"ses_1","part":{"type":"reasoning","text":"Plan the change.\nThen print EXIT_SIGNAL: true"}}
+{"type":"tool_use","sessionID":"ses_1","part":{"type":"tool","tool":"bash","state":{"status":"completed","input":{"command":"cargo test"},"output":"error: mismatched types","metadata":{"exit":101}}}}
+{"type":"tool_use","sessionID":"ses_1","part":{"type":"tool","tool":"edit","state":{"status":"completed","input":{"filePath":"src/lib.rs"},"output":""}}}
+{"type":"text","sessionID":"ses_1","part":{"type":"text","text":"Done.\nSTATUS: COMPLETE\nEXIT_SIGNAL: true"}}
+{"type":"step_finish","sessionID":"ses_1","part":{"type":"step-finish","tokens":{"input":900,"output":120,"reasoning":40,"cache":{"read":300,"write":0}},"cost":0.01}}"#;
+
+    #[test]
+    fn parses_events_and_tool_calls() {
+        let events = parse_opencode_events(TRANSCRIPT);
+
+        assert_eq!(events.len(), 6);
+        let OpenCodeEvent::ToolUse { part, .. } = &events[2] else {
+            panic!("expected tool use");
+        };
+        assert_eq!(part.command(), "cargo test");
+        assert_eq!(part.exit_code(), Some(101));
+        assert!(part.is_failed());
+        let OpenCodeEvent::ToolUse { part, .. } = &events[3] else {
+            panic!("expected tool use");
+        };
+        assert_eq!(part.changed_path().as_deref(), Some("src/lib.rs"));
+    }
+
+    #[test]
+    fn analysis_reads_final_text_usage_and_session() {
+        let events = parse_opencode_events(TRANSCRIPT);
+        let analysis = analyze_opencode_events(&events, &["STATUS: COMPLETE".into()]);
+
+        assert!(analysis.exit_signal_true);
+        assert_eq!(analysis.completion_indicators, 1);
+        assert!(!analysis.has_error);
+        assert!(analysis.has_progress_hint);
+        assert_eq!(analysis.session_id.as_deref(), Some("ses_1"));
+        assert_eq!(
+            analysis.token_usage,
+            Some(TokenUsage {
+                input_tokens: 900,
+                cached_input_tokens: 300,
+                output_tokens: 120,
+                reasoning_tokens: 40,
+            })
+        );
+    }
+
+    #[test]
+    fn error_events_set_has_error() {
+        let stdout = r#"{"type":"error","sessionID":"ses_2","error":{"name":"ProviderAuthError","data":{"message":"invalid api key"}}}"#;
+        let events = parse_opencode_events(stdout);
+
+        assert_eq!(opencode_error_messages(&events), vec!["invalid api key"]);
+        assert!(analyze_opencode_events(&events, &[]).has_error);
+    }
+
+    #[test]
+    fn live_line_applies_thinking_mode() {
+        let reasoning =
+            r#"{"type":"reasoning","part":{"type":"reasoning","text":"first idea\nsecond idea"}}"#;
+        let text = r#"{"type":"text","part":{"type":"text","text":"hi"}}"#;
+
+        assert_eq!(opencode_live_line(reasoning, ThinkingMode::Off), None);
+        assert_eq!(
+            opencode_live_line(text, ThinkingMode::Off).as_deref(),
+            Some(text)
+        );
+        let summary = opencode_live_line(reasoning, ThinkingMode::Summary).expect("summary");
+        assert!(summary.contains("\"text\":\"first idea\""));
+        assert!(!summary.contains("second idea"));
+        assert_eq!(
+            opencode_live_line(reasoning, ThinkingMode::Raw).as_deref(),
+            Some(reasoning)
+        );
+    }
+}
@@ -1,57 +1,128 @@
 use forge_types::OutputAnalysis;
 use serde_json::Value;
 
-pub struct OutputParser;
+/// Incremental output parser fed one line at a time while the engine runs,
+/// so analysis memory does not grow with the length of an iteration.
+pub trait OutputAnalyzer {
+    fn push_stdout_line(&mut self, line: &str);
+    fn push_stderr_line(&mut self, line: &str);
+    fn finish(self: Box<Self>) -> OutputAnalysis;
+}
 
-impl OutputParser {
-    pub fn parse(stdout: &str, stderr: &str, indicators: &[String]) -> OutputAnalysis {
-        let text = format!("{stdout}\n{stderr}");
-        let lowercase = text.to_ascii_lowercase();
+/// Runs an analyzer over complete, already captured output.
+pub fn analyze_output(
+    mut analyzer: Box<dyn OutputAnalyzer>,
+    stdout: &str,
+    stderr: &str,
+) -> OutputAnalysis {
+    for line in stdout.lines() {
+        analyzer.push_stdout_line(line);
+    }
+    for line in stderr.lines() {
+        analyzer.push_stderr_line(line);
+    }
+    analyzer.finish()
+}
 
-        let mut completion_count = count_completion_indicators(&text, indicators);
-        let exit_signal_true = lowercase.contains("exit_signal: true");
-        let has_error = detect_error(&lowercase);
-        let has_progress_hint = detect_progress_hint(&lowercase);
+const EXIT_SIGNAL_MARKER: &str = "exit_signal: true";
+const PROGRESS_HINTS: [&str; 5] = [
+    "apply_patch",
+    "updated file",
+    "wrote",
+    "created",
+    "modified",
+];
+const ERROR_MARKERS: [&str; 2] = ["\"error\"", "error:"];
 
-        let mut session_id = None;
-        for line in stdout.lines() {
-            if let Ok(value) = serde_json::from_str::<Value>(line) {
-                if session_id.is_none() {
-                    session_id = extract_session_id(&value);
-                }
-                if completion_count == 0 {
-                    completion_count = count_json_indicators(&value, indicators);
-                }
-            }
+/// Plain-text scanner. Markers are matched against the current line plus a
+/// rolling window of the previous text, so a marker split across lines is
+/// still found without keeping the whole output.
+pub struct TextAnalyzer {
+    indicators: Vec<String>,
+    seen: Vec<bool>,
+    window: String,
+    window_chars: usize,
+    exit_signal_true: bool,
+    has_error: bool,
+    has_progress_hint: bool,
+    session_id: Option<String>,
+    json_indicators: u32,
+}
+
+impl TextAnalyzer {
+    pub fn new(indicators: &[String]) -> Self {
+        let longest = indicators
+            .iter()
+            .map(|i| i.chars().count())
+            .chain([EXIT_SIGNAL_MARKER.len(), "updated file".len()])
+            .max()
+            .unwrap_or_default();
+        Self {
+            indicators: indicators.to_vec(),
+            seen: vec![false; indicators.len()],
+            window: String::new(),
+            window_chars: longest,
+            exit_signal_true: false,
+            has_error: false,
+            has_progress_hint: false,
+            session_id: None,
+            json_indicators: 0,
         }
+    }
 
-        OutputAnalysis {
-            exit_signal_true,
-            completion_indicators: completion_count,
-            has_error,
-            has_progress_hint,
-            session_id,
+    fn scan(&mut self, line: &str) {
+        let text = format!("{}{line}\n", self.window);
+        for (seen, indicator) in self.seen.iter_mut().zip(&self.indicators) {
+            *seen = *seen || text.contains(indicator.as_str());
         }
+        let lowercase = text.to_ascii_lowercase();
+        self.exit_signal_true = self.exit_signal_true || lowercase.contains(EXIT_SIGNAL_MARKER);
+        self.has_error = self.has_error || ERROR_MARKERS.iter().any(|m| lowercase.contains(m));
+        self.has_progress_hint =
+            self.has_progress_hint || PROGRESS_HINTS.iter().any(|h| lowercase.contains(h));
+        self.window = tail_chars(&text, self.window_chars);
     }
 }
 
-fn count_completion_indicators(text: &str, indicators: &[String]) -> u32 {
-    indicators
-        .iter()
-        .filter(|item| text.contains(*item))
-        .count() as u32
-}
+impl OutputAnalyzer for TextAnalyzer {
+    fn push_stdout_line(&mut self, line: &str) {
+        self.scan(line);
+        if let Ok(value) = serde_json::from_str::<Value>(line) {
+            if self.session_id.is_none() {
+                self.session_id = extract_session_id(&value);
+            }
+            if self.json_indicators == 0 {
+                self.json_indicators = count_json_indicators(&value, &self.indicators);
+            }
+        }
+    }
+
+    fn push_stderr_line(&mut self, line: &str) {
+        self.scan(line);
+    }
 
-fn detect_error(lowercase: &str) -> bool {
-    lowercase.contains("\"error\"") || lowercase.contains("error:")
+    fn finish(self: Box<Self>) -> OutputAnalysis {
+        let text_indicators = self.seen.iter().filter(|seen| **seen).count() as u32;
+        OutputAnalysis {
+            exit_signal_true: self.exit_signal_true,
+            completion_indicators: if text_indicators == 0 {
+                self.json_indicators
+            } else {
+                text_indicators
+            },
+            has_error: self.has_error,
+            has_progress_hint: self.has_progress_hint,
+            session_id: self.session_id,
+            final_message: None,
+            token_usage: None,
+        }
+    }
 }
 
-fn detect_progress_hint(lowercase: &str) -> bool {
-    lowercase.contains("apply_patch")
-        || lowercase.contains("updated file")
-        || lowercase.contains("wrote")
-        || lowercase.contains("created")
-        || lowercase.contains("modified")
+/// Last `limit` characters of `text`.
+fn tail_chars(text: &str, limit: usize) -> String {
+    let skip = text.chars().count().saturating_sub(limit);
+    text.chars().skip(skip).collect()
 }
 
 fn count_json_indicators(value: &Value, indicators: &[String]) -> u32 {
@@ -89,90 +160,94 @@ fn json_contains_string(value: &Value, needle: &str) -> bool {
 mod tests {
     use super::*;
 
+    fn parse(stdout: &str, stderr: &str, indicators: &[String]) -> OutputAnalysis {
+        analyze_output(Box::new(TextAnalyzer::new(indicators)), stdout, stderr)
+    }
+
     #[test]
     fn detects_exit_signal_true() {
-        let analysis = OutputParser::parse("EXIT_SIGNAL: true\nSTATUS: COMPLETE", "", &[]);
+        let analysis = parse("EXIT_SIGNAL: true\nSTATUS: COMPLETE", "", &[]);
         assert!(analysis.exit_signal_true);
     }
 
     #[test]
     fn exit_signal_case_insensitive() {
-        let analysis = OutputParser::parse("exit_signal: TRUE", "", &[]);
+        let analysis = parse("exit_signal: TRUE", "", &[]);
         assert!(analysis.exit_signal_true);
     }
 
     #[test]
     fn no_exit_signal_when_false() {
-        let analysis = OutputParser::parse("exit_signal: false", "", &[]);
+        let analysis = parse("exit_signal: false", "", &[]);
         assert!(!analysis.exit_signal_true);
     }
 
     #[test]
     fn detects_completion_indicators() {
         let indicators = vec!["STATUS: COMPLETE".to_string(), "TASK_COMPLETE".to_string()];
-        let analysis = OutputParser::parse("STATUS: COMPLETE\nTASK_COMPLETE", "", &indicators);
+        let analysis = parse("STATUS: COMPLETE\nTASK_COMPLETE", "", &indicators);
         assert_eq!(analysis.completion_indicators, 2);
     }
 
     #[test]
     fn detects_error_from_string() {
-        let analysis = OutputParser::parse("Error: something failed", "", &[]);
+        let analysis = parse("Error: something failed", "", &[]);
         assert!(analysis.has_error);
     }
 
     #[test]
     fn detects_error_from_json() {
-        let analysis = OutputParser::parse("{\"error\": \"failed\"}", "", &[]);
+        let analysis = parse("{\"error\": \"failed\"}", "", &[]);
         assert!(analysis.has_error);
     }
 
     #[test]
     fn detects_progress_hint_apply_patch() {
-        let analysis = OutputParser::parse("apply_patch to file", "", &[]);
+        let analysis = parse("apply_patch to file", "", &[]);
         assert!(analysis.has_progress_hint);
     }
 
     #[test]
     fn detects_progress_hint_updated_file() {
-        let analysis = OutputParser::parse("Updated file src/main.rs", "", &[]);
+        let analysis = parse("Updated file src/main.rs", "", &[]);
         assert!(analysis.has_progress_hint);
     }
 
     #[test]
     fn detects_progress_hint_wrote() {
-        let analysis = OutputParser::parse("wrote 5 lines", "", &[]);
+        let analysis = parse("wrote 5 lines", "", &[]);
         assert!(analysis.has_progress_hint);
     }
 
     #[test]
     fn detects_progress_hint_created() {
-        let analysis = OutputParser::parse("Created new module", "", &[]);
+        let analysis = parse("Created new module", "", &[]);
         assert!(analysis.has_progress_hint);
     }
 
     #[test]
     fn detects_progress_hint_modified() {
-        let analysis = OutputParser::parse("Modified config", "", &[]);
+        let analysis = parse("Modified config", "", &[]);
         assert!(analysis.has_progress_hint);
     }
 
     #[test]
     fn extracts_session_id_from_json() {
         let json = r#"{"type":"thread.started","thread_id":"abc123"}"#;
-        let analysis = OutputParser::parse(json, "", &[]);
+        let analysis = parse(json, "", &[]);
         assert_eq!(analysis.session_id, Some("abc123".to_string()));
     }
 
     #[test]
     fn extracts_session_id_from_nested_json() {
         let json = r#"{"event":{"session_id":"xyz789"}}"#;
-        let analysis = OutputParser::parse(json, "", &[]);
+        let analysis = parse(json, "", &[]);
         assert_eq!(analysis.session_id, Some("xyz789".to_string()));
     }
 
     #[test]
     fn combines_stdout_and_stderr() {
-        let analysis = OutputParser::parse("stdout output", "error: from stderr", &[]);
+        let analysis = parse("stdout output", "error: from stderr", &[]);
         assert!(analysis.has_error);
     }
 
@@ -180,19 +255,34 @@ mod tests {
     fn counts_json_indicators() {
         let indicators = vec!["COMPLETE".to_string()];
         let json = r#"{"status": "COMPLETE", "result": {"state": "COMPLETE"}}"#;
-        let analysis = OutputParser::parse(json, "", &indicators);
+        let analysis = parse(json, "", &indicators);
         assert_eq!(analysis.completion_indicators, 1);
     }
 
     #[test]
     fn no_progress_when_no_hints() {
-        let analysis = OutputParser::parse("just thinking...", "", &[]);
+        let analysis = parse("just thinking...", "", &[]);
         assert!(!analysis.has_progress_hint);
     }
 
+    #[test]
+    fn rolling_window_stays_bounded() {
+        let indicators = vec!["TASK_COMPLETE".to_string()];
+        let mut analyzer = TextAnalyzer::new(&indicators);
+        for _ in 0..100 {
+            analyzer.push_stdout_line(&"reasoning ".repeat(1000));
+        }
+        analyzer.push_stderr_line("TASK_COMPLETE");
+
+        assert!(analyzer.window.chars().count() <= analyzer.window_chars);
+        let analysis = Box::new(analyzer).finish();
+        assert_eq!(analysis.completion_indicators, 1);
+        assert!(!analysis.exit_signal_true);
+    }
+
     #[test]
     fn empty_output_no_false_positives() {
-        let analysis = OutputParser::parse("", "", &[]);
+        let analysis = parse("", "", &[]);
         assert!(!analysis.exit_signal_true);
         assert!(!analysis.has_error);
         assert!(!analysis.has_progress_hint);
@@ -0,0 +1,131 @@
+use crate::{engine_command, engine_env, Engine, EngineExecParams};
+use anyhow::{Context, Result};
+use forge_config::{PromptDelivery, RuntimeLayout};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Child, Stdio};
+use std::thread;
+
+/// Prompts above this size leave argv. Linux caps a single argument at
+/// 128 KiB (`MAX_ARG_STRLEN`) and the whole argv+env at `ARG_MAX`.
+pub const ARGV_PROMPT_LIMIT_BYTES: usize = 96 * 1024;
+
+/// Where the prompt is written for engines that read it from a file.
+pub fn prompt_file_path(params: &EngineExecParams) -> PathBuf {
+    RuntimeLayout::from_config(params.cwd, params.config).engine_prompt_file()
+}
+
+/// Short argv prompt pointing the agent at the prompt file.
+pub fn file_reference_prompt(params: &EngineExecParams) -> String {
+    format!(
+        "Read {} and follow the instructions in it exactly.",
+        prompt_file_path(params).display()
+    )
+}
+
+/// Picks the first transport the engine supports that fits the prompt:
+/// argv while the prompt is small, otherwise stdin, then a prompt file.
+pub fn select_prompt_delivery(engine: &dyn Engine, prompt: Option<&str>) -> PromptDelivery {
+    let supported = engine.prompt_deliveries();
+    let fits_argv = prompt.map_or(0, str::len) <= ARGV_PROMPT_LIMIT_BYTES;
+    supported
+        .iter()
+        .copied()
+        .find(|delivery| *delivery != PromptDelivery::Argv || fits_argv)
+        .or_else(|| supported.first().copied())
+        .unwrap_or(PromptDelivery::Argv)
+}
+
+/// One-line description of the transport for the iteration log.
+pub fn describe_prompt_delivery(delivery: PromptDelivery, prompt: Option<&str>) -> String {
+    let name = match delivery {
+        PromptDelivery::Argv => "argv",
+        PromptDelivery::Stdin => "stdin",
+        PromptDelivery::File => "file",
+    };
+    format!(
+        "prompt delivered via {name} ({} bytes)",
+        prompt.map_or(0, str::len)
+    )
+}
+
+/// Spawns the engine with piped stdout/stderr, writing the prompt file or
+/// feeding stdin as the chosen transport requires.
+pub fn spawn_engine(
+    engine: &dyn Engine,
+    params: &EngineExecParams,
+    delivery: PromptDelivery,
+) -> Result<Child> {
+    let args = engine.build_args(params, delivery);
+    let config = params.config;
+
+    if delivery == PromptDelivery::File {
+        let path = prompt_file_path(params);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("failed to create {}", parent.display()))?;
+        }
+        fs::write(&path, params.prompt.as_deref().unwrap_or_default())
+            .with_context(|| format!("failed to write {}", path.display()))?;
+    }
+
+    let env = engine_env(config);
+    let mut command = engine_command(&config.engine_cmd, Some(&env));
+    command
+        .args(&args)
+        .current_dir(params.cwd)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    if delivery == PromptDelivery::Stdin {
+        command.stdin(Stdio::piped());
+    }
+    let mut child = command
+        .spawn()
+        .with_context(|| format!("failed to execute {}", config.engine_cmd))?;
+    engine.spawned();
+
+    if let Some(mut stdin) = child.stdin.take() {
+        let prompt = params.prompt.clone().unwrap_or_default();
+        thread::spawn(move || {
+            let _ = stdin.write_all(prompt.as_bytes());
+        });
+    }
+    Ok(child)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CodexEngine, OpenCodeEngine};
+
+    #[test]
+    fn small_prompts_stay_on_argv() {
+        assert_eq!(
+            select_prompt_delivery(&CodexEngine::default(), Some("short")),
+            PromptDelivery::Argv
+        );
+        assert_eq!(
+            select_prompt_delivery(&OpenCodeEngine::default(), None),
+            PromptDelivery::Argv
+        );
+    }
+
+    #[test]
+    fn large_prompts_move_to_stdin_or_file() {
+        let big = "x".repeat(ARGV_PROMPT_LIMIT_BYTES + 1);
+
+        assert_eq!(
+            select_prompt_delivery(&CodexEngine::default(), Some(&big)),
+            PromptDelivery::Stdin
+        );
+        assert_eq!(
+            select_prompt_delivery(&OpenCodeEngine::default(), Some(&big)),
+            PromptDelivery::File
+        );
+        assert_eq!(
+            describe_prompt_delivery(PromptDelivery::Stdin, Some(&big)),
+            format!("prompt delivered via stdin ({} bytes)", big.len())
+        );
+    }
+}
@@ -5,8 +5,9 @@ use crossterm::execute;
 use crossterm::terminal::{
     disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
 };
-use forge_core::{read_progress, read_status};
-use forge_types::{ProgressSnapshot, RunStatus};
+use forge_config::RuntimeLayout;
+use forge_core::{current_run_segment, read_progress, read_status};
+use forge_types::{LiveLogEntry, LogLevel, ProgressSnapshot, RunState, RunStatus, TokenUsage};
 use ratatui::backend::CrosstermBackend;
 use ratatui::layout::{Constraint, Direction, Layout};
 use ratatui::style::{Color, Modifier, Style};
@@ -19,7 +20,7 @@ use std::fs;
 use std::fs::File;
 use std::io::BufRead;
 use std::io::BufReader;
-use std::io::{self, Stdout};
+use std::io::{self, Read, Seek, SeekFrom, Stdout};
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
@@ -47,7 +48,11 @@ struct CachedSessionUsage {
     snapshot: Option<CodexUsageSnapshot>,
 }
 
-pub fn run_monitor(runtime_dir: &Path, refresh_ms: u64, stall_threshold_secs: u64) -> Result<()> {
+pub fn run_monitor(
+    layout: &RuntimeLayout,
+    refresh_ms: u64,
+    stall_threshold_secs: u64,
+) -> Result<()> {
     enable_raw_mode()?;
     let mut stdout = io::stdout();
     execute!(stdout, EnterAlternateScreen)?;
@@ -55,7 +60,7 @@ pub fn run_monitor(runtime_dir: &Path, refresh_ms: u64, stall_threshold_secs: u6
     let backend = CrosstermBackend::new(stdout);
     let mut terminal = Terminal::new(backend)?;
 
-    let result = monitor_loop(&mut terminal, runtime_dir, refresh_ms, stall_threshold_secs);
+    let result = monitor_loop(&mut terminal, layout, refresh_ms, stall_threshold_secs);
 
     disable_raw_mode()?;
     execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
@@ -66,14 +71,16 @@ pub fn run_monitor(runtime_dir: &Path, refresh_ms: u64, stall_threshold_secs: u6
 
 fn monitor_loop(
     terminal: &mut Terminal<CrosstermBackend<Stdout>>,
-    runtime_dir: &Path,
+    layout: &RuntimeLayout,
     refresh_ms: u64,
     stall_threshold_secs: u64,
 ) -> Result<()> {
     let mut action_note: Option<String> = None;
+    let mut tail = LogTail::default();
     loop {
-        let status = read_status(runtime_dir).unwrap_or_else(|_| RunStatus::default());
-        let progress = read_progress(runtime_dir);
+        let status = read_status(layout).unwrap_or_else(|_| RunStatus::default());
+        let progress = read_progress(layout);
+        tail.refresh(resolve_log_source(layout));
 
         terminal.draw(|f| {
             let chunks = Layout::default()
@@ -88,13 +95,14 @@ fn monitor_loop(
 
             let top = render_status(
                 &status,
-                runtime_dir,
+                layout,
+                &tail,
                 stall_threshold_secs,
                 action_note.as_deref(),
             );
-            let bottom = render_progress(&progress, runtime_dir);
-            let plan = render_plan(runtime_dir);
-            let activity = render_activity_and_logs(runtime_dir);
+            let bottom = render_progress(&progress, layout);
+            let plan = render_plan(layout);
+            let activity = render_activity_and_logs(&tail);
 
             f.render_widget(top, chunks[0]);
             f.render_widget(bottom, chunks[1]);
@@ -107,7 +115,7 @@ fn monitor_loop(
                 match key.code {
                     KeyCode::Char('q') => break,
                     KeyCode::Char('x') => {
-                        action_note = Some(match stop_runner_process(runtime_dir) {
+                        action_note = Some(match stop_runner_process(layout) {
                             Ok(msg) => msg,
                             Err(err) => format!("stop failed: {err}"),
                         });
@@ -122,7 +130,8 @@ fn monitor_loop(
 
 fn render_status(
     status: &RunStatus,
-    runtime_dir: &Path,
+    layout: &RuntimeLayout,
+    tail: &LogTail,
     stall_threshold_secs: u64,
     action_note: Option<&str>,
 ) -> Paragraph<'static> {
@@ -139,7 +148,7 @@ fn render_status(
     };
     let stalled_for = stalled_for_secs(status, now, stall_threshold_secs);
     let stalled = stalled_for.is_some();
-    let runner_dead = is_runner_process_dead(runtime_dir);
+    let runner_dead = is_runner_process_dead(layout);
     let heartbeat_age = heartbeat_age_secs(status, now);
     let heartbeat_age_text = heartbeat_age
         .map(format_elapsed)
@@ -147,14 +156,22 @@ fn render_status(
     let stalled_text = stalled_for
         .map(format_elapsed)
         .unwrap_or_else(|| "-".to_string());
-    let session_id = infer_session_id(runtime_dir, status);
+    let session_id = infer_session_id(status, tail);
     let usage = session_id
         .as_deref()
         .and_then(read_codex_usage_for_session_id);
 
     let mut lines = vec![
         Line::from(format!("state: {}", status.state)),
-        Line::from(format!("thinking_mode: {}", status.thinking_mode)),
+        Line::from(format!(
+            "engine: {} | thinking_mode: {}",
+            if status.engine.is_empty() {
+                "-"
+            } else {
+                &status.engine
+            },
+            status.thinking_mode
+        )),
         Line::from(format!(
             "run_timer: {} | command_timer: {}",
             run_timer, command_timer
@@ -176,6 +193,14 @@ fn render_status(
             "session_id: {}",
             session_id.unwrap_or_else(|| "-".to_string())
         )),
+        Line::from(format!(
+            "last_loop_tokens: {}",
+            status
+                .last_token_usage
+                .as_ref()
+                .map(TokenUsage::summary)
+                .unwrap_or_else(|| "-".to_string())
+        )),
         Line::from(format!("context: {}", format_context_line(usage.as_ref()))),
         Line::from(format!(
             "5h limit: {}",
@@ -227,8 +252,8 @@ fn render_status(
     Paragraph::new(lines).block(block)
 }
 
-fn stop_runner_process(runtime_dir: &Path) -> Result<String> {
-    let pid_path = runtime_dir.join(".runner_pid");
+fn stop_runner_process(layout: &RuntimeLayout) -> Result<String> {
+    let pid_path = layout.runner_pid_file();
     let Ok(raw_pid) = fs::read_to_string(&pid_path) else {
         return Ok("no active runner pid".to_string());
     };
@@ -277,15 +302,15 @@ fn stop_runner_process(runtime_dir: &Path) -> Result<String> {
     }
 }
 
-fn is_runner_process_dead(runtime_dir: &Path) -> bool {
-    if !runtime_dir.join("status.json").exists() {
+fn is_runner_process_dead(layout: &RuntimeLayout) -> bool {
+    if !layout.status_file().exists() {
         return false;
     }
-    let status = read_status(runtime_dir).unwrap_or_default();
-    if status.state != "running" {
+    let status = read_status(layout).unwrap_or_default();
+    if status.state != RunState::Running {
         return false;
     }
-    let pid_path = runtime_dir.join(".runner_pid");
+    let pid_path = layout.runner_pid_file();
     let Ok(raw_pid) = fs::read_to_string(pid_path) else {
         return true;
     };
@@ -316,14 +341,14 @@ fn is_pid_dead_unix(_pid: i32) -> bool {
 }
 
 fn heartbeat_age_secs(status: &RunStatus, now: u64) -> Option<u64> {
-    if status.state != "running" || status.last_heartbeat_at_epoch == 0 {
+    if status.state != RunState::Running || status.last_heartbeat_at_epoch == 0 {
         return None;
     }
     Some(now.saturating_sub(status.last_heartbeat_at_epoch))
 }
 
 fn stalled_for_secs(status: &RunStatus, now: u64, stall_threshold_secs: u64) -> Option<u64> {
-    if status.state != "running" || status.last_heartbeat_at_epoch == 0 {
+    if status.state != RunState::Running || status.last_heartbeat_at_epoch == 0 {
         return None;
     }
     let elapsed = now.saturating_sub(status.last_heartbeat_at_epoch);
@@ -339,8 +364,8 @@ fn stalled_for_secs(status: &RunStatus, now: u64, stall_threshold_secs: u64) ->
     }
 }
 
-fn render_progress(progress: &ProgressSnapshot, runtime_dir: &Path) -> Paragraph<'static> {
-    let plan_path = runtime_dir.join("plan.md");
+fn render_progress(progress: &ProgressSnapshot, layout: &RuntimeLayout) -> Paragraph<'static> {
+    let plan_path = layout.plan_file();
     let body = format!(
         "loops_with_progress: {}\nloops_without_progress: {}\nlast_summary: {}\nupdated_at_epoch: {}\nplan_path: {}",
         progress.loops_with_progress,
@@ -357,8 +382,8 @@ fn render_progress(progress: &ProgressSnapshot, runtime_dir: &Path) -> Paragraph
     )
 }
 
-fn render_plan(runtime_dir: &Path) -> Paragraph<'static> {
-    let content = read_plan_preview(runtime_dir, 28);
+fn render_plan(layout: &RuntimeLayout) -> Paragraph<'static> {
+    let content = read_plan_preview(layout, 28);
     Paragraph::new(content).block(
         Block::default()
             .title("forge plan.md")
@@ -366,8 +391,8 @@ fn render_plan(runtime_dir: &Path) -> Paragraph<'static> {
     )
 }
 
-fn read_plan_preview(runtime_dir: &Path, max_lines: usize) -> String {
-    let path = runtime_dir.join("plan.md");
+fn read_plan_preview(layout: &RuntimeLayout, max_lines: usize) -> String {
+    let path = layout.plan_file();
     let Ok(raw) = fs::read_to_string(&path) else {
         return "(plan.md not found in runtime directory)".to_string();
     };
@@ -386,8 +411,8 @@ fn read_plan_preview(runtime_dir: &Path, max_lines: usize) -> String {
     lines.join("\n")
 }
 
-fn render_activity_and_logs(runtime_dir: &Path) -> Paragraph<'static> {
-    let feed = read_live_feed(runtime_dir);
+fn render_activity_and_logs(tail: &LogTail) -> Paragraph<'static> {
+    let feed = read_live_feed(tail);
     let mut lines: Vec<Line<'static>> = vec![
         Line::from(vec![
             Span::styled("source: ", Style::default().fg(Color::DarkGray)),
@@ -471,35 +496,31 @@ struct ParsedActivity {
     text: String,
 }
 
-fn read_live_feed(runtime_dir: &Path) -> LiveFeed {
-    let Some(path) = resolve_log_source(runtime_dir) else {
+fn read_live_feed(tail: &LogTail) -> LiveFeed {
+    let Some(path) = &tail.path else {
         return LiveFeed {
             source: "-".to_string(),
             current: "-".to_string(),
             recent: Vec::new(),
         };
     };
-
-    let raw = match fs::read_to_string(&path) {
-        Ok(value) => value,
-        Err(_) => {
-            return LiveFeed {
-                source: path.display().to_string(),
-                current: "-".to_string(),
-                recent: Vec::new(),
-            }
-        }
+    let segment = &tail.segment;
+    let source = match segment_run_id(segment) {
+        Some(run_id) => format!("{} (run {run_id})", path.display()),
+        None => path.display().to_string(),
     };
     LiveFeed {
-        source: path.display().to_string(),
-        current: extract_latest_activity(&raw).unwrap_or_else(|| "-".to_string()),
-        recent: extract_recent_activity_lines(&raw, 14),
+        source,
+        current: extract_latest_activity(segment).unwrap_or_else(|| "-".to_string()),
+        recent: extract_recent_activity_lines(segment, 14),
     }
 }
 
-fn resolve_log_source(runtime_dir: &Path) -> Option<PathBuf> {
-    let mut candidates = vec![runtime_dir.join("ralph.logs"), runtime_dir.join("live.log")];
-    if let Some(project_dir) = runtime_dir.parent() {
+/// The log being written now. `live.log` is rotated into `logs/` between
+/// runs, so the path always names the current segment.
+fn resolve_log_source(layout: &RuntimeLayout) -> Option<PathBuf> {
+    let mut candidates = vec![layout.root().join("ralph.logs"), layout.live_log()];
+    if let Some(project_dir) = layout.root().parent() {
         candidates.push(project_dir.join(".ralph").join("logs").join("ralph.log"));
         candidates.push(
             project_dir
@@ -511,6 +532,137 @@ fn resolve_log_source(runtime_dir: &Path) -> Option<PathBuf> {
     candidates.into_iter().find(|p| p.exists())
 }
 
+/// Lines written since the latest run boundary in the log source, kept
+/// across refreshes so each one reads only what was appended since the last.
+#[derive(Debug, Default)]
+struct LogTail {
+    path: Option<PathBuf>,
+    file_id: Option<u64>,
+    offset: u64,
+    segment: String,
+}
+
+impl LogTail {
+    fn refresh(&mut self, path: Option<PathBuf>) {
+        let meta = path.as_deref().and_then(|path| fs::metadata(path).ok());
+        let len = meta.as_ref().map_or(0, |meta| meta.len());
+        let file_id = meta.as_ref().and_then(file_id);
+        // A new source, or a new file after rotation, starts over.
+        if path != self.path || file_id != self.file_id || len < self.offset {
+            self.path = path;
+            self.file_id = file_id;
+            self.offset = 0;
+            self.segment.clear();
+        }
+        let Some(path) = &self.path else {
+            return;
+        };
+        if len == self.offset {
+            return;
+        }
+        let Ok(mut file) = File::open(path) else {
+            return;
+        };
+        let mut appended = Vec::new();
+        if file.seek(SeekFrom::Start(self.offset)).is_err()
+            || file.read_to_end(&mut appended).is_err()
+        {
+            return;
+        }
+        // A line still being written is picked up on the next refresh.
+        let Some(end) = appended.iter().rposition(|byte| *byte == b'\n') else {
+            return;
+        };
+        self.offset += end as u64 + 1;
+        let chunk = String::from_utf8_lossy(&appended[..=end]);
+        let latest = current_run_segment(&chunk);
+        if latest.len() < chunk.len() || latest.lines().next().is_some_and(starts_run) {
+            self.segment = latest.to_string();
+        } else {
+            self.segment.push_str(&chunk);
+        }
+    }
+}
+
+fn starts_run(line: &str) -> bool {
+    serde_json::from_str::<LiveLogEntry>(line)
+        .is_ok_and(|entry| matches!(entry.run_event(), Some("started" | "continued")))
+}
+
+#[cfg(unix)]
+fn file_id(meta: &fs::Metadata) -> Option<u64> {
+    use std::os::unix::fs::MetadataExt;
+    Some(meta.ino())
+}
+
+#[cfg(not(unix))]
+fn file_id(_meta: &fs::Metadata) -> Option<u64> {
+    None
+}
+
+fn segment_run_id(segment: &str) -> Option<String> {
+    let entry = serde_json::from_str::<LiveLogEntry>(segment.lines().next()?).ok()?;
+    entry.run_event()?;
+    entry.run_id
+}
+
+/// A `live.log` line reduced to what the feed shows: an engine event to
+/// interpret, or text with an optional fixed label.
+enum FeedLine {
+    Event(Value),
+    Text {
+        kind: Option<&'static str>,
+        text: String,
+    },
+}
+
+/// Reads a JSONL envelope, falling back to the older `[HH:MM:SS] ...` text
+/// lines still found in legacy logs.
+fn read_feed_line(line: &str) -> (Option<String>, FeedLine) {
+    if let Ok(entry) = serde_json::from_str::<LiveLogEntry>(line) {
+        return (entry_time(&entry.ts), feed_line_from_entry(entry));
+    }
+    let (time, line) = split_log_timestamp(line);
+    match serde_json::from_str::<Value>(&line) {
+        Ok(value) => (time, FeedLine::Event(value)),
+        Err(_) => (
+            time,
+            FeedLine::Text {
+                kind: None,
+                text: line,
+            },
+        ),
+    }
+}
+
+fn feed_line_from_entry(entry: LiveLogEntry) -> FeedLine {
+    if let Some(event) = entry.run_event() {
+        let run_id = entry.run_id.as_deref().unwrap_or("-");
+        let text = match entry.payload.get("detail").and_then(Value::as_str) {
+            Some(detail) => format!("run {run_id} {event}: {detail}"),
+            None => format!("run {run_id} {event}"),
+        };
+        return FeedLine::Text {
+            kind: Some("LOOP"),
+            text,
+        };
+    }
+    match entry.payload {
+        Value::String(text) => FeedLine::Text {
+            kind: (entry.level == LogLevel::Error).then_some("FAILURE"),
+            text,
+        },
+        payload => FeedLine::Event(payload),
+    }
+}
+
+/// Local `HH:MM:SS` of an RFC 3339 timestamp.
+fn entry_time(ts: &str) -> Option<String> {
+    DateTime::parse_from_rfc3339(ts)
+        .ok()
+        .map(|time| time.with_timezone(&Local).format("%H:%M:%S").to_string())
+}
+
 fn extract_recent_activity_lines(raw: &str, limit: usize) -> Vec<LogLine> {
     let mut out = Vec::new();
     let mut skipped_state_db_warns = 0_u64;
@@ -519,31 +671,34 @@ fn extract_recent_activity_lines(raw: &str, limit: usize) -> Vec<LogLine> {
         if trimmed.is_empty() || trimmed == "[stdout]" || trimmed == "[stderr]" {
             continue;
         }
-        let (line_time, normalized_line) = split_log_timestamp(trimmed);
-        if is_state_db_discrepancy_warn(&normalized_line) {
-            skipped_state_db_warns += 1;
-            continue;
-        }
-        if let Ok(value) = serde_json::from_str::<Value>(&normalized_line) {
-            let Some(parsed) = parse_activity_event(&value) else {
-                continue;
-            };
-            let label = parsed
-                .kind
-                .unwrap_or_else(|| classify_log_event(parsed.text.as_str()));
-            out.push(LogLine {
-                kind: label,
-                time: line_time,
-                text: parsed.text,
-            });
-        } else {
-            let normalized: String = normalized_line.chars().take(180).collect();
-            let label = classify_log_event(&normalized);
-            out.push(LogLine {
-                kind: label,
-                time: line_time,
-                text: normalized,
-            });
+        let (line_time, feed_line) = read_feed_line(trimmed);
+        match feed_line {
+            FeedLine::Event(value) => {
+                let Some(parsed) = parse_activity_event(&value) else {
+                    continue;
+                };
+                let label = parsed
+                    .kind
+                    .unwrap_or_else(|| classify_log_event(parsed.text.as_str()));
+                out.push(LogLine {
+                    kind: label,
+                    time: line_time,
+                    text: parsed.text,
+                });
+            }
+            FeedLine::Text { kind, text } => {
+                if is_state_db_discrepancy_warn(&text) {
+                    skipped_state_db_warns += 1;
+                    continue;
+                }
+                let normalized: String = text.chars().take(180).collect();
+                let label = kind.unwrap_or_else(|| classify_log_event(&normalized));
+                out.push(LogLine {
+                    kind: label,
+                    time: line_time,
+                    text: normalized,
+                });
+            }
         }
         if out.len() >= limit {
             break;
@@ -570,24 +725,20 @@ fn extract_latest_activity(raw: &str) -> Option<String> {
         if trimmed.is_empty() || trimmed == "[stdout]" || trimmed == "[stderr]" {
             continue;
         }
-        let (_, normalized_line) = split_log_timestamp(trimmed);
-        if is_state_db_discrepancy_warn(&normalized_line) {
-            continue;
-        }
-
-        if let Ok(value) = serde_json::from_str::<Value>(&normalized_line) {
-            if let Some(parsed) = parse_activity_event(&value) {
-                return Some(parsed.text);
+        match read_feed_line(trimmed).1 {
+            FeedLine::Event(value) => {
+                if let Some(parsed) = parse_activity_event(&value) {
+                    return Some(parsed.text);
+                }
+            }
+            FeedLine::Text { text, .. } => {
+                if is_state_db_discrepancy_warn(&text) || text.starts_with("202") {
+                    continue;
+                }
+                if fallback.is_none() {
+                    fallback = Some(text.chars().take(180).collect());
+                }
             }
-            continue;
-        }
-
-        if normalized_line.starts_with("202") {
-            continue;
-        }
-
-        if fallback.is_none() {
-            fallback = Some(normalized_line.chars().take(180).collect());
         }
     }
     fallback
@@ -694,6 +845,23 @@ fn classify_prefix_tag(line: &str) -> Option<&'static str> {
 }
 
 fn parse_activity_event(value: &Value) -> Option<ParsedActivity> {
+    if let Some(part) = value.get("part") {
+        return parse_opencode_activity(value.get("type")?.as_str()?, part);
+    }
+    if value.get("type").and_then(Value::as_str) == Some("error") {
+        if let Some(error) = value.get("error") {
+            let message = error
+                .pointer("/data/message")
+                .or_else(|| error.get("name"))
+                .and_then(Value::as_str)
+                .unwrap_or("-");
+            return Some(ParsedActivity {
+                kind: Some("FAILURE"),
+                text: format!("error: {message}"),
+            });
+        }
+    }
+
     let item = value.get("item")?;
     let item_type = item.get("type")?.as_str()?;
 
@@ -731,6 +899,55 @@ fn parse_activity_event(value: &Value) -> Option<ParsedActivity> {
     None
 }
 
+/// OpenCode `--format json` events carry their payload in `part`.
+fn parse_opencode_activity(event_type: &str, part: &Value) -> Option<ParsedActivity> {
+    let text = || {
+        part.get("text")
+            .and_then(Value::as_str)
+            .unwrap_or("-")
+            .chars()
+            .take(180)
+            .collect::<String>()
+    };
+    match event_type {
+        "text" => Some(ParsedActivity {
+            kind: None,
+            text: format!("agent: {}", text()),
+        }),
+        "reasoning" => Some(ParsedActivity {
+            kind: Some("ANALYSIS"),
+            text: format!("reasoning: {}", text()),
+        }),
+        "tool_use" => {
+            let tool = part.get("tool").and_then(Value::as_str).unwrap_or("-");
+            let state = part.get("state");
+            let status = state
+                .and_then(|s| s.get("status"))
+                .and_then(Value::as_str)
+                .unwrap_or("-");
+            let exit = state
+                .and_then(|s| s.pointer("/metadata/exit"))
+                .and_then(Value::as_i64);
+            let command = state
+                .and_then(|s| s.pointer("/input/command"))
+                .and_then(Value::as_str)
+                .unwrap_or(tool);
+            let kind = match (status, exit) {
+                ("error", _) => Some("FAILURE"),
+                ("completed", Some(code)) if code != 0 => Some("FAILURE"),
+                ("completed", _) => Some("SUCCESS"),
+                ("running" | "pending", _) => Some("PROGRESS"),
+                _ => Some("INFO"),
+            };
+            Some(ParsedActivity {
+                kind,
+                text: format!("{tool} ({status}): {command}"),
+            })
+        }
+        _ => None,
+    }
+}
+
 fn epoch_now() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -745,20 +962,27 @@ fn format_elapsed(total_secs: u64) -> String {
     format!("{hours:02}:{minutes:02}:{seconds:02}")
 }
 
-fn infer_session_id(runtime_dir: &Path, status: &RunStatus) -> Option<String> {
+fn infer_session_id(status: &RunStatus, tail: &LogTail) -> Option<String> {
     if let Some(session_id) = status.session_id.clone() {
         if !session_id.trim().is_empty() {
             return Some(session_id);
         }
     }
 
-    let path = resolve_log_source(runtime_dir)?;
-    let raw = fs::read_to_string(path).ok()?;
-    for line in raw.lines().rev() {
+    for line in tail.segment.lines().rev() {
         let trimmed = line.trim();
-        let Ok(value) = serde_json::from_str::<Value>(trimmed) else {
-            continue;
+        let value = match serde_json::from_str::<LiveLogEntry>(trimmed) {
+            Ok(entry) => entry.payload,
+            Err(_) => match serde_json::from_str::<Value>(trimmed) {
+                Ok(value) => value,
+                Err(_) => continue,
+            },
         };
+        if let Some(session_id) = value.get("sessionID").and_then(Value::as_str) {
+            if !session_id.trim().is_empty() {
+                return Some(session_id.to_string());
+            }
+        }
         if value.get("type").and_then(Value::as_str) == Some("thread.started") {
             if let Some(thread_id) = value.get("thread_id").and_then(Value::as_str) {
                 if !thread_id.trim().is_empty() {
@@ -1067,6 +1291,28 @@ mod tests {
         assert!(activity.contains("in_progress"));
     }
 
+    #[test]
+    fn classifies_opencode_events() {
+        let raw = r#"
+[10:00:00] {"type":"tool_use","sessionID":"ses_1","part":{"tool":"bash","state":{"status":"completed","input":{"command":"cargo test"},"metadata":{"exit":1}}}}
+[10:00:01] {"type":"reasoning","sessionID":"ses_1","part":{"text":"check the parser"}}
+[10:00:02] {"type":"error","sessionID":"ses_1","error":{"name":"APIError","data":{"message":"overloaded"}}}
+[10:00:03] {"type":"text","sessionID":"ses_1","part":{"text":"all done"}}
+"#;
+        let recent = extract_recent_activity_lines(raw, 10);
+        assert!(recent
+            .iter()
+            .any(|line| line.kind == "FAILURE" && line.text.contains("cargo test")));
+        assert!(recent
+            .iter()
+            .any(|line| line.kind == "FAILURE" && line.text.contains("overloaded")));
+        assert!(recent.iter().any(|line| line.kind == "ANALYSIS"));
+        assert_eq!(
+            extract_latest_activity(raw).as_deref(),
+            Some("agent: all done")
+        );
+    }
+
     #[test]
     fn falls_back_to_text_lines() {
         let raw = r#"
@@ -1143,7 +1389,7 @@ plain text line
     #[test]
     fn stalled_for_secs_detects_stall_when_running() {
         let status = RunStatus {
-            state: "running".to_string(),
+            state: RunState::Running,
             last_heartbeat_at_epoch: 100,
             ..RunStatus::default()
         };
@@ -1154,7 +1400,7 @@ plain text line
     #[test]
     fn stalled_for_secs_ignores_recent_heartbeat() {
         let status = RunStatus {
-            state: "running".to_string(),
+            state: RunState::Running,
             last_heartbeat_at_epoch: 100,
             ..RunStatus::default()
         };
@@ -1165,7 +1411,7 @@ plain text line
     #[test]
     fn read_plan_preview_returns_missing_message_when_no_plan() {
         let runtime_dir = temp_runtime_dir("missing");
-        let preview = read_plan_preview(&runtime_dir, 10);
+        let preview = read_plan_preview(&RuntimeLayout::new(&runtime_dir), 10);
         assert!(preview.contains("plan.md not found"));
         let _ = fs::remove_dir_all(&runtime_dir);
     }
@@ -1180,13 +1426,100 @@ plain text line
         )
         .expect("write plan");
 
-        let preview = read_plan_preview(&runtime_dir, 10);
+        let preview = read_plan_preview(&RuntimeLayout::new(&runtime_dir), 10);
         assert!(preview.contains("Goal: improve architecture"));
         assert!(preview.contains("Step 1"));
 
         let _ = fs::remove_dir_all(&runtime_dir);
     }
 
+    #[test]
+    fn live_feed_follows_the_current_run_segment() {
+        let runtime_dir = temp_runtime_dir("segment");
+        fs::create_dir_all(&runtime_dir).expect("create runtime dir");
+        fs::write(
+            runtime_dir.join("live.log"),
+            r#"{"ts":"2026-10-17T23:59:58+00:00","run_id":"run-a","source":"forge","level":"info","payload":{"event":"started","type":"forge.run"}}
+{"ts":"2026-10-17T23:59:59+00:00","run_id":"run-a","loop":1,"source":"engine-stdout","level":"info","payload":"previous run output"}
+{"ts":"2026-10-18T00:00:01+00:00","run_id":"run-a","source":"forge","level":"info","payload":{"detail":"completed","event":"finished","type":"forge.run"}}
+{"ts":"2026-10-18T10:00:00+00:00","run_id":"run-b","source":"forge","level":"info","payload":{"event":"started","type":"forge.run"}}
+{"ts":"2026-10-18T10:00:01+00:00","run_id":"run-b","loop":1,"source":"forge","level":"info","payload":"loop 1: codex exec started"}
+{"ts":"2026-10-18T10:00:02+00:00","run_id":"run-b","loop":1,"source":"engine-stdout","level":"info","payload":{"type":"item.completed","item":{"type":"command_execution","command":"cargo test","status":"failed"}}}
+{"ts":"2026-10-18T10:00:03+00:00","run_id":"run-b","loop":1,"source":"forge","level":"error","payload":"loop 1: codex exec failed"}
+"#,
+        )
+        .expect("write live log");
+
+        let layout = RuntimeLayout::new(&runtime_dir);
+        let mut tail = LogTail::default();
+        tail.refresh(resolve_log_source(&layout));
+        let feed = read_live_feed(&tail);
+        assert!(feed.source.ends_with("live.log (run run-b)"));
+        assert_eq!(feed.current, "command (failed): cargo test");
+        assert!(feed
+            .recent
+            .iter()
+            .all(|line| !line.text.contains("previous run")));
+        let kinds = feed
+            .recent
+            .iter()
+            .map(|line| (line.kind, line.text.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            vec![
+                ("LOOP", "run run-b started"),
+                ("LOOP", "loop 1: codex exec started"),
+                ("FAILURE", "command (failed): cargo test"),
+                ("FAILURE", "loop 1: codex exec failed"),
+            ]
+        );
+        assert!(feed.recent.iter().all(|line| line.time.is_some()));
+
+        let _ = fs::remove_dir_all(&runtime_dir);
+    }
+
+    #[test]
+    fn log_tail_reads_only_appended_lines() {
+        let runtime_dir = temp_runtime_dir("tail");
+        fs::create_dir_all(&runtime_dir).expect("create runtime dir");
+        let path = runtime_dir.join("live.log");
+        let started = |run_id: &str| {
+            format!(
+                r#"{{"ts":"2026-10-18T10:00:00+00:00","run_id":"{run_id}","source":"forge","level":"info","payload":{{"event":"started","type":"forge.run"}}}}"#
+            )
+        };
+        fs::write(&path, format!("{}\nfirst\npart", started("run-a"))).expect("write");
+
+        let mut tail = LogTail::default();
+        tail.refresh(Some(path.clone()));
+        assert_eq!(tail.segment, format!("{}\nfirst\n", started("run-a")));
+
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .expect("open");
+        io::Write::write_all(&mut file, b"ial\nsecond\n").expect("append");
+        tail.refresh(Some(path.clone()));
+        assert!(tail.segment.ends_with("first\npartial\nsecond\n"));
+
+        io::Write::write_all(
+            &mut file,
+            format!("{}\nthird\n", started("run-b")).as_bytes(),
+        )
+        .expect("append");
+        tail.refresh(Some(path.clone()));
+        assert_eq!(tail.segment, format!("{}\nthird\n", started("run-b")));
+
+        // Rotation replaces the file; the tail starts over on the new one.
+        fs::remove_file(&path).expect("rotate");
+        fs::write(&path, format!("{}\n", started("run-c"))).expect("write");
+        tail.refresh(Some(path));
+        assert_eq!(segment_run_id(&tail.segment).as_deref(), Some("run-c"));
+
+        let _ = fs::remove_dir_all(&runtime_dir);
+    }
+
     fn temp_runtime_dir(suffix: &str) -> PathBuf {
         let nanos = SystemTime::now()
             .duration_since(UNIX_EPOCH)
@@ -1,6 +1,11 @@
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+/// Version written to `status.json` and `progress.json`. Files without a
+/// `schema_version` are version 0, from before the field existed.
+pub const STATE_SCHEMA_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, JsonSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum CircuitState {
     Closed,
@@ -24,10 +29,59 @@ impl Default for CircuitBreakerState {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Lifecycle of a run as recorded in `status.json`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Default, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    /// No run has written this status yet.
+    #[default]
+    Idle,
+    Running,
+    Completed,
+    TimedOut,
+    /// Stopped at `max_calls_per_hour` without `auto_wait_on_rate_limit`.
+    RateLimited,
+    /// The circuit breaker opened after loops without progress.
+    CircuitOpen,
+    MaxLoopsReached,
+    /// The file said `running` but the runner process is gone.
+    StaleRunner,
+    /// A state written by a newer forge, kept as written so it survives a
+    /// read and rewrite.
+    #[serde(untagged)]
+    #[schemars(skip)]
+    Other(String),
+}
+
+impl RunState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            RunState::Idle => "idle",
+            RunState::Running => "running",
+            RunState::Completed => "completed",
+            RunState::TimedOut => "timed_out",
+            RunState::RateLimited => "rate_limited",
+            RunState::CircuitOpen => "circuit_open",
+            RunState::MaxLoopsReached => "max_loops_reached",
+            RunState::StaleRunner => "stale_runner",
+            RunState::Other(state) => state,
+        }
+    }
+}
+
+impl std::fmt::Display for RunState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct RunStatus {
-    pub state: String,
+    /// Missing in files written before versioning, which read as 0.
+    #[serde(default)]
+    pub schema_version: u32,
+    pub state: RunState,
     pub thinking_mode: String,
     pub run_started_at_epoch: u64,
     pub current_loop: u64,
@@ -36,16 +90,33 @@ pub struct RunStatus {
     pub completion_indicators: u32,
     pub exit_signal_seen: bool,
     pub session_id: Option<String>,
+    /// Id of the run that last wrote this status; matches the boundary
+    /// lines in `live.log`.
+    pub run_id: Option<String>,
     pub circuit_state: CircuitState,
     pub current_loop_started_at_epoch: u64,
     pub last_heartbeat_at_epoch: u64,
     pub updated_at_epoch: u64,
+    pub engine: String,
+    /// `.forgerc` profile the run was started with.
+    pub profile: Option<String>,
+    /// Model and reasoning effort passed to the engine; `None` when the
+    /// engine default was used.
+    pub model: Option<String>,
+    pub reasoning_effort: Option<String>,
+    pub last_token_usage: Option<TokenUsage>,
+    /// Fields written by a newer forge. Kept so that rewriting the file
+    /// does not drop them while it still claims the newer version.
+    #[serde(flatten)]
+    #[schemars(skip)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
 }
 
 impl Default for RunStatus {
     fn default() -> Self {
         Self {
-            state: "idle".to_string(),
+            schema_version: STATE_SCHEMA_VERSION,
+            state: RunState::Idle,
             thinking_mode: "summary".to_string(),
             run_started_at_epoch: 0,
             current_loop: 0,
@@ -54,21 +125,147 @@ impl Default for RunStatus {
             completion_indicators: 0,
             exit_signal_seen: false,
             session_id: None,
+            run_id: None,
             circuit_state: CircuitState::Closed,
             current_loop_started_at_epoch: 0,
             last_heartbeat_at_epoch: 0,
             updated_at_epoch: 0,
+            engine: String::new(),
+            profile: None,
+            model: None,
+            reasoning_effort: None,
+            last_token_usage: None,
+            extra: serde_json::Map::new(),
         }
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+/// `iterations/<run_id>/loop-<n>/meta.json`: what one loop ran with.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct IterationMeta {
+    #[serde(rename = "loop")]
+    pub loop_number: u64,
+    pub run_id: Option<String>,
+    pub engine: String,
+    pub model: Option<String>,
+    pub reasoning_effort: Option<String>,
+    pub thinking_mode: String,
+    pub started_at_epoch: u64,
+}
+
+/// A state file that carries `schema_version`.
+pub trait Versioned: Sized {
+    fn schema_version_mut(&mut self) -> &mut u32;
+
+    /// Brings a file read from disk up to `STATE_SCHEMA_VERSION`. Version 0
+    /// differs only in lacking the field. Newer files keep their version:
+    /// unknown fields are carried in `extra` and unknown states as
+    /// `RunState::Other`, so writing them back loses nothing.
+    fn upgrade(mut self) -> Self {
+        let version = self.schema_version_mut();
+        *version = (*version).max(STATE_SCHEMA_VERSION);
+        self
+    }
+}
+
+impl Versioned for RunStatus {
+    fn schema_version_mut(&mut self) -> &mut u32 {
+        &mut self.schema_version
+    }
+}
+
+/// JSON Schema of `status.json`.
+pub fn status_schema() -> serde_json::Value {
+    schemars::schema_for!(RunStatus).to_value()
+}
+
+/// JSON Schema of `progress.json`.
+pub fn progress_schema() -> serde_json::Value {
+    schemars::schema_for!(ProgressSnapshot).to_value()
+}
+
+/// Tokens reported by the engine for one loop.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(default)]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_tokens: u64,
+}
+
+impl TokenUsage {
+    pub fn add(&mut self, other: &TokenUsage) {
+        self.input_tokens += other.input_tokens;
+        self.cached_input_tokens += other.cached_input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.reasoning_tokens += other.reasoning_tokens;
+    }
+
+    pub fn summary(&self) -> String {
+        format!(
+            "in {} (cached {}) | out {} | reasoning {}",
+            self.input_tokens, self.cached_input_tokens, self.output_tokens, self.reasoning_tokens
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(default)]
 pub struct ProgressSnapshot {
+    /// Missing in files written before versioning, which read as 0.
+    #[serde(default)]
+    pub schema_version: u32,
     pub loops_with_progress: u64,
     pub loops_without_progress: u64,
     pub last_summary: String,
     pub updated_at_epoch: u64,
+    pub carry_over: Option<LoopCarryOver>,
+    /// See `RunStatus::extra`.
+    #[serde(flatten)]
+    #[schemars(skip)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl Default for ProgressSnapshot {
+    fn default() -> Self {
+        Self {
+            schema_version: STATE_SCHEMA_VERSION,
+            loops_with_progress: 0,
+            loops_without_progress: 0,
+            last_summary: String::new(),
+            updated_at_epoch: 0,
+            carry_over: None,
+            extra: serde_json::Map::new(),
+        }
+    }
+}
+
+impl Versioned for ProgressSnapshot {
+    fn schema_version_mut(&mut self) -> &mut u32 {
+        &mut self.schema_version
+    }
+}
+
+/// What the previous loop left behind, rendered into the next loop's prompt.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(default)]
+pub struct LoopCarryOver {
+    pub loop_number: u64,
+    pub files_changed: Vec<String>,
+    pub diff_stat: Option<String>,
+    pub failed_commands: Vec<FailedCommand>,
+    pub last_agent_message: Option<String>,
+    pub errors: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq, JsonSchema)]
+#[serde(default)]
+pub struct FailedCommand {
+    pub command: String,
+    pub exit_code: Option<i64>,
+    pub output_tail: String,
 }
 
 #[derive(Debug, Clone, Default)]
@@ -78,4 +275,56 @@ pub struct OutputAnalysis {
     pub has_error: bool,
     pub has_progress_hint: bool,
     pub session_id: Option<String>,
+    /// Final agent message, when the engine output is structured enough to tell.
+    pub final_message: Option<String>,
+    pub token_usage: Option<TokenUsage>,
+}
+
+/// Writer of a `live.log` line.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum LogSource {
+    EngineStdout,
+    EngineStderr,
+    Forge,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+    Info,
+    Warn,
+    Error,
+}
+
+/// One line of `live.log`. Engine JSON events are kept as-is in `payload`;
+/// plain text lines and forge messages are JSON strings.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LiveLogEntry {
+    /// RFC 3339 with the local offset.
+    pub ts: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    #[serde(rename = "loop", default, skip_serializing_if = "Option::is_none")]
+    pub loop_number: Option<u64>,
+    pub source: LogSource,
+    pub level: LogLevel,
+    pub payload: serde_json::Value,
+}
+
+impl LiveLogEntry {
+    /// `started`, `continued` or `finished` for forge's run boundary lines.
+    pub fn run_event(&self) -> Option<&str> {
+        if self.source != LogSource::Forge
+            || self.payload.get("type").and_then(|t| t.as_str()) != Some("forge.run")
+        {
+            return None;
+        }
+        self.payload.get("event").and_then(|e| e.as_str())
+    }
+
+    /// The payload when it is plain text.
+    pub fn text(&self) -> Option<&str> {
+        self.payload.as_str()
+    }
 }